@@ -0,0 +1,97 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use minecraft_packet_sniffer::protocol::types::VarInt;
+use minecraft_packet_sniffer::Session;
+use std::hint::black_box;
+use std::time::SystemTime;
+
+/// Length prefixed packet with this ID and data, compression off
+fn packet(id: i32, data: &[u8]) -> Vec<u8> {
+    let mut frame = VarInt { value: id }.to_bytes();
+    frame.extend(data);
+    let mut packet = VarInt {
+        value: frame.len() as i32,
+    }
+    .to_bytes();
+    packet.extend(frame);
+    packet
+}
+
+/// Session that went through login and configuration to play
+fn play_session() -> Session {
+    let mut session = Session::new([10, 0, 0, 1].into(), [10, 0, 0, 2].into());
+    let mut handshake = vec![0x81, 0x06, 0x09];
+    handshake.extend(b"localhost");
+    handshake.extend([0x63, 0xDD, 0x02]);
+    let mut payload = packet(0x00, &handshake);
+    // Login and configuration acknowledged
+    payload.extend(packet(0x03, &[]));
+    payload.extend(packet(0x03, &[]));
+    session
+        .feed_payload(&payload, true, SystemTime::UNIX_EPOCH)
+        .unwrap();
+    session
+}
+
+/// Clientbound play traffic: entities, block changes and a packet the registry doesn't know
+fn play_stream(repeat: usize) -> Vec<u8> {
+    let mut spawn = vec![0x2A];
+    spawn.extend([0x11; 16]);
+    spawn.push(0x47);
+    spawn.extend([0; 24]);
+    spawn.extend([0x40, 0x80, 0x00, 0x00]);
+    spawn.extend([0; 6]);
+
+    let mut block_update = ((12i64 << 38) | (-7i64 & 0x3FF_FFFF) << 12 | 70)
+        .to_be_bytes()
+        .to_vec();
+    block_update.push(0x01);
+
+    let mut remove = vec![10];
+    remove.extend((0..10u8).map(|x| x + 1));
+
+    let mut section_blocks = (1i64 << 42).to_be_bytes().to_vec();
+    section_blocks.push(50);
+    for i in 0..50u64 {
+        // Block state 0x123 at local position i, as a VarLong
+        let mut value = (0x123 << 12) | i;
+        while value >= 0x80 {
+            section_blocks.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        section_blocks.push(value as u8);
+    }
+
+    let mut stream = vec![];
+    for _ in 0..repeat {
+        stream.extend(packet(0x01, &spawn));
+        stream.extend(packet(0x09, &block_update));
+        stream.extend(packet(0x22, &[0, 0, 0, 4, 0xFF, 0xFF, 0xFF, 0xFE]));
+        stream.extend(packet(0x47, &remove));
+        stream.extend(packet(0x4E, &section_blocks));
+        stream.extend(packet(0x30, &[0x55; 256]));
+    }
+    stream
+}
+
+fn session(c: &mut Criterion) {
+    let stream = play_stream(100);
+    let mut group = c.benchmark_group("session");
+    group.throughput(Throughput::Bytes(stream.len() as u64));
+    group.bench_function("play_stream", |b| {
+        b.iter_batched(
+            play_session,
+            |mut session| {
+                let events = session
+                    .feed_payload(black_box(&stream), false, SystemTime::UNIX_EPOCH)
+                    .unwrap();
+                assert_eq!(events.len(), 600);
+                events
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, session);
+criterion_main!(benches);
@@ -0,0 +1,17 @@
+use thiserror::Error;
+
+#[derive(Debug, Clone, Error)]
+pub enum FilterError {
+    #[error("Unexpected character '{0}' at {1}")]
+    UnexpectedChar(char, usize),
+    #[error("Unterminated string starting at {0}")]
+    UnterminatedString(usize),
+    #[error("Unexpected {0} at {1}")]
+    UnexpectedToken(String, usize),
+    #[error("Unexpected end of filter")]
+    UnexpectedEnd,
+    #[error("Unknown field: {0}")]
+    UnknownField(String),
+    #[error("Invalid regex: {0}")]
+    InvalidRegex(#[from] regex::Error),
+}
@@ -0,0 +1,160 @@
+use crate::filter::error::FilterError;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    /// Field name or bare word, dots included (`fields.entity_id`)
+    Ident(String),
+    Str(String),
+    Num(f64),
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Match,
+    NotMatch,
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Ident(s) => write!(f, "'{s}'"),
+            Token::Str(s) => write!(f, "{s:?}"),
+            Token::Num(n) => write!(f, "{n}"),
+            Token::Eq => write!(f, "'=='"),
+            Token::Ne => write!(f, "'!='"),
+            Token::Lt => write!(f, "'<'"),
+            Token::Le => write!(f, "'<='"),
+            Token::Gt => write!(f, "'>'"),
+            Token::Ge => write!(f, "'>='"),
+            Token::Match => write!(f, "'=~'"),
+            Token::NotMatch => write!(f, "'!~'"),
+            Token::And => write!(f, "'&&'"),
+            Token::Or => write!(f, "'||'"),
+            Token::Not => write!(f, "'!'"),
+            Token::LParen => write!(f, "'('"),
+            Token::RParen => write!(f, "')'"),
+        }
+    }
+}
+
+/// Splits a filter into tokens, each with its position in the input
+pub fn tokenize(input: &str) -> Result<Vec<(Token, usize)>, FilterError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        let next = chars.get(i + 1).copied();
+
+        let token = match (c, next) {
+            _ if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            ('=', Some('=')) => Token::Eq,
+            ('=', Some('~')) => Token::Match,
+            ('!', Some('=')) => Token::Ne,
+            ('!', Some('~')) => Token::NotMatch,
+            ('<', Some('=')) => Token::Le,
+            ('>', Some('=')) => Token::Ge,
+            ('&', Some('&')) => Token::And,
+            ('|', Some('|')) => Token::Or,
+            ('!', _) => Token::Not,
+            ('<', _) => Token::Lt,
+            ('>', _) => Token::Gt,
+            ('(', _) => Token::LParen,
+            (')', _) => Token::RParen,
+            ('"', _) => {
+                let mut value = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(FilterError::UnterminatedString(start)),
+                        Some('"') => break,
+                        Some('\\') if i + 1 < chars.len() => {
+                            value.push(chars[i + 1]);
+                            i += 2;
+                        }
+                        Some(&c) => {
+                            value.push(c);
+                            i += 1;
+                        }
+                    }
+                }
+                i += 1;
+                tokens.push((Token::Str(value), start));
+                continue;
+            }
+            _ if c.is_ascii_digit() || (c == '-' && next.is_some_and(|x| x.is_ascii_digit())) => {
+                i += 1;
+                while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.') {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                let value = match text.strip_prefix("0x") {
+                    Some(hex) => i64::from_str_radix(hex, 16).map(|x| x as f64).ok(),
+                    None => text.parse().ok(),
+                };
+                let value = value.ok_or(FilterError::UnexpectedToken(text, start))?;
+                tokens.push((Token::Num(value), start));
+                continue;
+            }
+            _ if c.is_alphabetic() || c == '_' => {
+                while i < chars.len()
+                    && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '.')
+                {
+                    i += 1;
+                }
+                tokens.push((Token::Ident(chars[start..i].iter().collect()), start));
+                continue;
+            }
+            _ => return Err(FilterError::UnexpectedChar(c, start)),
+        };
+
+        i += match token {
+            Token::Not | Token::Lt | Token::Gt | Token::LParen | Token::RParen => 1,
+            _ => 2,
+        };
+        tokens.push((token, start));
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tokens() {
+        let tokens = tokenize(r#"!(size >= 0x10) || name =~ "Chat \"x\"""#)
+            .unwrap()
+            .into_iter()
+            .map(|(token, _)| token)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Not,
+                Token::LParen,
+                Token::Ident("size".to_string()),
+                Token::Ge,
+                Token::Num(16.0),
+                Token::RParen,
+                Token::Or,
+                Token::Ident("name".to_string()),
+                Token::Match,
+                Token::Str("Chat \"x\"".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,309 @@
+mod error;
+mod lexer;
+mod parser;
+
+use crate::sink::PacketRecord;
+use regex::Regex;
+use std::cell::OnceCell;
+use std::cmp::Ordering;
+use std::fmt::{Debug, Formatter};
+use std::str::FromStr;
+
+pub use error::FilterError;
+
+/// Packet filter expression, e.g. `state == play && name =~ "Chat" && dir == c2s`.
+///
+/// Fields are `state`, `name`, `id`, `dir` (`c2s`/`s2c`), `size` (uncompressed bytes),
+/// `player`, `client`, `server` and `fields.<path>` for decoded packet fields.
+/// They are compared with `==`, `!=`, `<`, `<=`, `>`, `>=`, matched against a regex with
+/// `=~`/`!~`, and combined with `&&`, `||`, `!` and parentheses. A field on its own is
+/// true if it is set and not false, zero or empty.
+#[derive(Clone)]
+pub struct Filter {
+    source: String,
+    expr: Expr,
+}
+
+impl Filter {
+    pub fn parse(source: &str) -> Result<Self, FilterError> {
+        let tokens = lexer::tokenize(source)?;
+        Ok(Self {
+            source: source.to_string(),
+            expr: parser::Parser::new(tokens).parse()?,
+        })
+    }
+
+    pub fn matches(&self, record: &PacketRecord) -> bool {
+        let context = Context {
+            record,
+            fields: OnceCell::new(),
+        };
+        self.expr.eval(&context)
+    }
+}
+
+impl FromStr for Filter {
+    type Err = FilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl Debug for Filter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Filter({:?})", self.source)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Field, Op, Value),
+    Match(Field, Regex),
+    Truthy(Field),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Field {
+    State,
+    Name,
+    Id,
+    Direction,
+    Size,
+    Player,
+    Client,
+    Server,
+    /// Path into the decoded packet, object keys or array indices
+    Packet(Vec<String>),
+}
+
+impl Field {
+    fn from_name(name: &str) -> Result<Self, FilterError> {
+        Ok(match name {
+            "state" => Field::State,
+            "name" => Field::Name,
+            "id" => Field::Id,
+            "dir" => Field::Direction,
+            "size" => Field::Size,
+            "player" => Field::Player,
+            "client" => Field::Client,
+            "server" => Field::Server,
+            _ => match name.strip_prefix("fields.") {
+                Some(path) if !path.is_empty() => {
+                    Field::Packet(path.split('.').map(str::to_string).collect())
+                }
+                _ => return Err(FilterError::UnknownField(name.to_string())),
+            },
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Null,
+    Bool(bool),
+    Num(f64),
+    Str(String),
+}
+
+impl Value {
+    fn from_json(value: &serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Null => Value::Null,
+            serde_json::Value::Bool(b) => Value::Bool(*b),
+            serde_json::Value::Number(n) => n.as_f64().map_or(Value::Null, Value::Num),
+            serde_json::Value::String(s) => Value::Str(s.clone()),
+            value => Value::Str(value.to_string()),
+        }
+    }
+
+    fn truthy(&self) -> bool {
+        match self {
+            Value::Null => false,
+            Value::Bool(b) => *b,
+            Value::Num(n) => *n != 0.0,
+            Value::Str(s) => !s.is_empty(),
+        }
+    }
+
+    fn text(&self) -> String {
+        match self {
+            Value::Null => String::new(),
+            Value::Bool(b) => b.to_string(),
+            Value::Num(n) => n.to_string(),
+            Value::Str(s) => s.clone(),
+        }
+    }
+
+    /// Numbers compare with numbers (strings holding one included), strings with strings
+    fn compare(&self, other: &Value) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Num(a), Value::Num(b)) => a.partial_cmp(b),
+            (Value::Num(a), Value::Str(b)) => a.partial_cmp(&b.parse().ok()?),
+            (Value::Str(a), Value::Num(b)) => a.parse::<f64>().ok()?.partial_cmp(b),
+            (Value::Str(a), Value::Str(b)) => Some(a.cmp(b)),
+            (a, b) => (a == b).then_some(Ordering::Equal),
+        }
+    }
+}
+
+/// Decoded fields are only serialized when the filter looks at them
+struct Context<'a, 'b> {
+    record: &'a PacketRecord<'b>,
+    fields: OnceCell<serde_json::Value>,
+}
+
+impl Context<'_, '_> {
+    fn get(&self, field: &Field) -> Value {
+        let record = self.record;
+        match field {
+            Field::State => Value::Str(record.state.to_string().to_lowercase()),
+            Field::Name => Value::Str(record.packet.name().to_string()),
+            Field::Id => Value::Num(record.packet_id as f64),
+            Field::Direction => {
+                Value::Str(if record.server_bounded { "c2s" } else { "s2c" }.into())
+            }
+            Field::Size => Value::Num(record.frame.len() as f64),
+            Field::Player => record
+                .player
+                .map_or(Value::Null, |x| Value::Str(x.to_string())),
+            Field::Client => Value::Str(record.client.to_string()),
+            Field::Server => Value::Str(record.server.to_string()),
+            Field::Packet(path) => {
+                let fields = self.fields.get_or_init(|| {
+                    serde_json::to_value(record.packet).unwrap_or(serde_json::Value::Null)
+                });
+                path.iter()
+                    .try_fold(fields, |value, key| match value {
+                        serde_json::Value::Array(array) => array.get(key.parse::<usize>().ok()?),
+                        value => value.get(key),
+                    })
+                    .map_or(Value::Null, Value::from_json)
+            }
+        }
+    }
+}
+
+impl Expr {
+    fn eval(&self, context: &Context) -> bool {
+        match self {
+            Expr::And(a, b) => a.eval(context) && b.eval(context),
+            Expr::Or(a, b) => a.eval(context) || b.eval(context),
+            Expr::Not(a) => !a.eval(context),
+            Expr::Truthy(field) => context.get(field).truthy(),
+            Expr::Match(field, regex) => match context.get(field) {
+                Value::Null => false,
+                value => regex.is_match(&value.text()),
+            },
+            Expr::Compare(field, op, value) => {
+                let ordering = context.get(field).compare(value);
+                match op {
+                    Op::Eq => ordering == Some(Ordering::Equal),
+                    Op::Ne => ordering != Some(Ordering::Equal),
+                    Op::Lt => ordering == Some(Ordering::Less),
+                    Op::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+                    Op::Gt => ordering == Some(Ordering::Greater),
+                    Op::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::protocol::packets::{LoginStart, UnloadChunk};
+    use crate::protocol::registry::Packet;
+    use crate::protocol::types::State;
+    use std::time::UNIX_EPOCH;
+    use uuid::Uuid;
+
+    fn record<'a>(
+        state: State,
+        server_bounded: bool,
+        frame: &'a [u8],
+        player: Option<&'a str>,
+        packet: &'a Packet,
+    ) -> PacketRecord<'a> {
+        PacketRecord {
+            timestamp: UNIX_EPOCH,
+            client: "10.0.0.2:50000".parse().unwrap(),
+            server: "10.0.0.1:25565".parse().unwrap(),
+            server_bounded,
+            state,
+            player,
+            packet_id: frame[0] as i32,
+            raw: &[],
+            frame,
+            packet,
+        }
+    }
+
+    #[test]
+    fn expressions() {
+        let login_start = Packet::from(LoginStart {
+            name: "Notch".to_string(),
+            uuid: Uuid::from_u128(1),
+        });
+        let login = record(State::Login, true, &[0; 30], None, &login_start);
+        let unload = Packet::from(UnloadChunk {
+            chunk_x: 4,
+            chunk_z: -2,
+        });
+        let play = record(State::Play, false, &[0x22; 9], Some("Notch"), &unload);
+
+        let cases = [
+            ("state == play && dir == s2c", false, true),
+            (r#"name =~ "^Login" || size > 100000"#, true, false),
+            ("!(id != 0x22)", false, true),
+            (r#"player == "Notch""#, false, true),
+            ("player", false, true),
+            ("fields.name == Notch", true, false),
+            ("fields.chunk_x >= 4 && fields.chunk_z == -2", false, true),
+            (r#"client !~ ":50000$""#, false, false),
+        ];
+        for (source, login_matches, play_matches) in cases {
+            let filter = Filter::parse(source).unwrap();
+            assert_eq!(filter.matches(&login), login_matches, "{source} on login");
+            assert_eq!(filter.matches(&play), play_matches, "{source} on play");
+        }
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            Filter::parse("nmae == x"),
+            Err(FilterError::UnknownField(_))
+        ));
+        assert!(matches!(
+            Filter::parse("size >"),
+            Err(FilterError::UnexpectedEnd)
+        ));
+        assert!(matches!(
+            Filter::parse("(size > 1"),
+            Err(FilterError::UnexpectedEnd)
+        ));
+        assert!(matches!(
+            Filter::parse("size > 1 size"),
+            Err(FilterError::UnexpectedToken(_, 9))
+        ));
+        assert!(matches!(
+            Filter::parse("name =~ \"(\""),
+            Err(FilterError::InvalidRegex(_))
+        ));
+    }
+}
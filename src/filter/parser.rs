@@ -0,0 +1,114 @@
+use crate::filter::error::FilterError;
+use crate::filter::lexer::Token;
+use crate::filter::{Expr, Field, Op, Value};
+use regex::Regex;
+
+/// Recursive descent over the tokens, `||` binding loosest, then `&&`, then `!`
+pub struct Parser {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+}
+
+impl Parser {
+    pub fn new(tokens: Vec<(Token, usize)>) -> Self {
+        Self {
+            tokens,
+            position: 0,
+        }
+    }
+
+    pub fn parse(mut self) -> Result<Expr, FilterError> {
+        let expr = self.or()?;
+        match self.tokens.get(self.position) {
+            None => Ok(expr),
+            Some((token, at)) => Err(FilterError::UnexpectedToken(token.to_string(), *at)),
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+
+    fn next(&mut self) -> Result<(Token, usize), FilterError> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .cloned()
+            .ok_or(FilterError::UnexpectedEnd)?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn or(&mut self) -> Result<Expr, FilterError> {
+        let mut expr = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.position += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, FilterError> {
+        let mut expr = self.unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.position += 1;
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, FilterError> {
+        match self.next()? {
+            (Token::Not, _) => Ok(Expr::Not(Box::new(self.unary()?))),
+            (Token::LParen, _) => {
+                let expr = self.or()?;
+                match self.next()? {
+                    (Token::RParen, _) => Ok(expr),
+                    (token, at) => Err(FilterError::UnexpectedToken(token.to_string(), at)),
+                }
+            }
+            (Token::Ident(name), _) => self.comparison(Field::from_name(&name)?),
+            (token, at) => Err(FilterError::UnexpectedToken(token.to_string(), at)),
+        }
+    }
+
+    fn comparison(&mut self, field: Field) -> Result<Expr, FilterError> {
+        let op = match self.peek() {
+            Some(Token::Eq) => Op::Eq,
+            Some(Token::Ne) => Op::Ne,
+            Some(Token::Lt) => Op::Lt,
+            Some(Token::Le) => Op::Le,
+            Some(Token::Gt) => Op::Gt,
+            Some(Token::Ge) => Op::Ge,
+            Some(Token::Match | Token::NotMatch) => {
+                let negate = self.next()?.0 == Token::NotMatch;
+                let pattern = match self.next()? {
+                    (Token::Str(s) | Token::Ident(s), _) => s,
+                    (token, at) => return Err(FilterError::UnexpectedToken(token.to_string(), at)),
+                };
+                let expr = Expr::Match(field, Regex::new(&pattern)?);
+                return Ok(if negate {
+                    Expr::Not(Box::new(expr))
+                } else {
+                    expr
+                });
+            }
+            _ => return Ok(Expr::Truthy(field)),
+        };
+        self.position += 1;
+
+        // Bare words on the right are plain strings, so `state == play` works without quotes
+        let value = match self.next()? {
+            (Token::Str(s), _) => Value::Str(s),
+            (Token::Num(n), _) => Value::Num(n),
+            (Token::Ident(s), _) => match s.as_str() {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                "null" => Value::Null,
+                _ => Value::Str(s),
+            },
+            (token, at) => return Err(FilterError::UnexpectedToken(token.to_string(), at)),
+        };
+        Ok(Expr::Compare(field, op, value))
+    }
+}
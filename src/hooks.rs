@@ -0,0 +1,189 @@
+use crate::latency::RoundTrip;
+use crate::protocol::types::State;
+use crate::protocol::DecodeError;
+use crate::session::{DecodedPacket, SessionEvent};
+use std::fmt::{Debug, Formatter};
+use std::net::IpAddr;
+
+type Callbacks<F> = Vec<Box<F>>;
+
+/// Callbacks run as a [`crate::Session`] decodes, each given the client IP of its connection.
+///
+/// ```
+/// use minecraft_packet_sniffer::hooks::Hooks;
+/// use minecraft_packet_sniffer::Session;
+/// use std::sync::Arc;
+///
+/// let mut hooks = Hooks::new();
+/// hooks
+///     .on_player(|client, name| println!("{client} is {name}"))
+///     .on_closed(|client| println!("{client} left"));
+/// let session = Session::with_hooks([10, 0, 0, 1].into(), [10, 0, 0, 2].into(), Arc::new(hooks));
+/// ```
+#[allow(clippy::type_complexity)]
+#[derive(Default)]
+pub struct Hooks {
+    opened: Callbacks<dyn Fn(IpAddr) + Send + Sync>,
+    state_changed: Callbacks<dyn Fn(IpAddr, State, State) + Send + Sync>,
+    compression: Callbacks<dyn Fn(IpAddr, i32) + Send + Sync>,
+    player: Callbacks<dyn Fn(IpAddr, &str) + Send + Sync>,
+    packet: Callbacks<dyn Fn(IpAddr, &DecodedPacket) + Send + Sync>,
+    malformed: Callbacks<dyn Fn(IpAddr, &DecodeError) + Send + Sync>,
+    round_trip: Callbacks<dyn Fn(IpAddr, &RoundTrip) + Send + Sync>,
+    closed: Callbacks<dyn Fn(IpAddr) + Send + Sync>,
+}
+
+impl Hooks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A session was started for a new connection
+    pub fn on_opened(&mut self, f: impl Fn(IpAddr) + Send + Sync + 'static) -> &mut Self {
+        self.opened.push(Box::new(f));
+        self
+    }
+
+    /// The connection switched state, with the state before and after
+    pub fn on_state_changed(
+        &mut self,
+        f: impl Fn(IpAddr, State, State) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.state_changed.push(Box::new(f));
+        self
+    }
+
+    /// The server set the compression threshold, negative turns compression off
+    pub fn on_compression(&mut self, f: impl Fn(IpAddr, i32) + Send + Sync + 'static) -> &mut Self {
+        self.compression.push(Box::new(f));
+        self
+    }
+
+    /// The client logged in with a name
+    pub fn on_player(&mut self, f: impl Fn(IpAddr, &str) + Send + Sync + 'static) -> &mut Self {
+        self.player.push(Box::new(f));
+        self
+    }
+
+    pub fn on_packet(
+        &mut self,
+        f: impl Fn(IpAddr, &DecodedPacket) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.packet.push(Box::new(f));
+        self
+    }
+
+    /// A packet couldn't be decoded and was skipped
+    pub fn on_malformed(
+        &mut self,
+        f: impl Fn(IpAddr, &DecodeError) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.malformed.push(Box::new(f));
+        self
+    }
+
+    /// A keep alive, ping or TCP segment was answered
+    pub fn on_round_trip(
+        &mut self,
+        f: impl Fn(IpAddr, &RoundTrip) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.round_trip.push(Box::new(f));
+        self
+    }
+
+    pub fn on_closed(&mut self, f: impl Fn(IpAddr) + Send + Sync + 'static) -> &mut Self {
+        self.closed.push(Box::new(f));
+        self
+    }
+
+    pub(crate) fn opened(&self, client: IpAddr) {
+        for f in &self.opened {
+            f(client);
+        }
+    }
+
+    /// Runs the callbacks for `event`
+    pub fn dispatch(&self, client: IpAddr, event: &SessionEvent) {
+        match event {
+            SessionEvent::Packet(packet) => self.packet.iter().for_each(|f| f(client, packet)),
+            SessionEvent::StateChanged { from, to } => self
+                .state_changed
+                .iter()
+                .for_each(|f| f(client, *from, *to)),
+            SessionEvent::Compression(threshold) => {
+                self.compression.iter().for_each(|f| f(client, *threshold))
+            }
+            SessionEvent::Player(name) => self.player.iter().for_each(|f| f(client, name)),
+            SessionEvent::Malformed(e) => self.malformed.iter().for_each(|f| f(client, e)),
+            SessionEvent::RoundTrip(x) => self.round_trip.iter().for_each(|f| f(client, x)),
+            SessionEvent::Closed => self.closed.iter().for_each(|f| f(client)),
+        }
+    }
+}
+
+impl Debug for Hooks {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let count = self.opened.len()
+            + self.state_changed.len()
+            + self.compression.len()
+            + self.player.len()
+            + self.packet.len()
+            + self.malformed.len()
+            + self.round_trip.len()
+            + self.closed.len();
+        write!(f, "Hooks({count})")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Session;
+    use std::sync::{Arc, Mutex};
+    use std::time::SystemTime;
+
+    #[test]
+    fn hooks() {
+        let log = Arc::new(Mutex::new(vec![]));
+        let mut hooks = Hooks::new();
+        let l = log.clone();
+        hooks.on_opened(move |_| l.lock().unwrap().push("opened".to_string()));
+        let l = log.clone();
+        hooks.on_state_changed(move |_, from, to| l.lock().unwrap().push(format!("{from} {to}")));
+        let l = log.clone();
+        hooks.on_compression(move |_, x| l.lock().unwrap().push(format!("compression {x}")));
+        let l = log.clone();
+        hooks.on_player(move |_, name| l.lock().unwrap().push(name.to_string()));
+        let l = log.clone();
+        hooks.on_packet(move |_, packet| l.lock().unwrap().push(packet.packet.name().to_string()));
+
+        let mut session =
+            Session::with_hooks([10, 0, 0, 1].into(), [10, 0, 0, 2].into(), Arc::new(hooks));
+        let mut payload = vec![
+            0x10, 0x00, 0x81, 0x06, 0x09, b'l', b'o', b'c', b'a', b'l', b'h', b'o', b's', b't',
+            0x63, 0xDD, 0x02, 0x17, 0x00, 0x05,
+        ];
+        payload.extend(b"Notch");
+        payload.extend([0; 16]);
+        session
+            .feed_payload(&payload, true, SystemTime::UNIX_EPOCH)
+            .unwrap();
+        // Threshold of 256
+        session
+            .feed_payload(&[0x03, 0x03, 0x80, 0x02], false, SystemTime::UNIX_EPOCH)
+            .unwrap();
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            [
+                "opened",
+                "Handshake",
+                "Handshaking Login",
+                "Notch",
+                "LoginStart",
+                "SetCompression",
+                "compression 256"
+            ]
+        );
+    }
+}
@@ -0,0 +1,178 @@
+//! Round trip times measured from requests the other side has to answer
+
+use crate::protocol::registry::{
+    ConfigurationClientbound, ConfigurationServerbound, Packet, PlayClientbound, PlayServerbound,
+    StatusClientbound, StatusServerbound,
+};
+use crate::sink::PacketRecord;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::time::{Duration, SystemTime};
+
+/// Unanswered requests older than this are forgotten
+const PENDING_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// What a round trip was measured from
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Probe {
+    /// Server Keep Alive answered by the client, in configuration and play
+    KeepAlive,
+    /// Server Ping answered by a Pong, in configuration and play
+    Ping,
+    /// Status Ping Request answered by the server's Pong Response
+    StatusPing,
+    /// Server data acknowledged by the client, the network between the capture and the client
+    TcpClient,
+    /// Client data acknowledged by the server, the network between the capture and the server
+    TcpServer,
+}
+
+impl Display for Probe {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Probe::KeepAlive => "keep_alive",
+                Probe::Ping => "ping",
+                Probe::StatusPing => "status_ping",
+                Probe::TcpClient => "tcp_client",
+                Probe::TcpServer => "tcp_server",
+            }
+        )
+    }
+}
+
+/// One measured round trip
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RoundTrip {
+    /// Capture time of the answer
+    pub timestamp: SystemTime,
+    pub probe: Probe,
+    pub rtt: Duration,
+}
+
+/// Matches the requests of one connection to their answers by ID
+#[derive(Debug, Default)]
+pub struct Tracker {
+    pending: HashMap<(Probe, i64), SystemTime>,
+}
+
+impl Tracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Looks at one decoded packet, returning the round trip it completes
+    pub fn packet(&mut self, record: &PacketRecord) -> Option<RoundTrip> {
+        let (probe, id, answer) = match record.packet {
+            Packet::ConfigurationClientbound(ConfigurationClientbound::KeepAlive(x))
+            | Packet::PlayClientbound(PlayClientbound::KeepAlive(x)) => {
+                (Probe::KeepAlive, x.keep_alive_id, false)
+            }
+            Packet::ConfigurationServerbound(ConfigurationServerbound::KeepAlive(x))
+            | Packet::PlayServerbound(PlayServerbound::KeepAlive(x)) => {
+                (Probe::KeepAlive, x.keep_alive_id, true)
+            }
+            Packet::ConfigurationClientbound(ConfigurationClientbound::Ping(x))
+            | Packet::PlayClientbound(PlayClientbound::Ping(x)) => {
+                (Probe::Ping, x.id.into(), false)
+            }
+            Packet::ConfigurationServerbound(ConfigurationServerbound::Pong(x))
+            | Packet::PlayServerbound(PlayServerbound::Pong(x)) => (Probe::Ping, x.id.into(), true),
+            Packet::StatusServerbound(StatusServerbound::PingRequest(x)) => {
+                (Probe::StatusPing, x.timestamp, false)
+            }
+            Packet::StatusClientbound(StatusClientbound::PongResponse(x)) => {
+                (Probe::StatusPing, x.timestamp, true)
+            }
+            _ => return None,
+        };
+        let timestamp = record.timestamp;
+
+        if answer {
+            let sent = self.pending.remove(&(probe, id))?;
+            let rtt = timestamp.duration_since(sent).ok()?;
+            return Some(RoundTrip {
+                timestamp,
+                probe,
+                rtt,
+            });
+        }
+        // Clients that stop answering would otherwise leave their requests behind
+        if self.pending.len() >= 16 {
+            self.pending.retain(|_, sent| {
+                timestamp
+                    .duration_since(*sent)
+                    .is_ok_and(|x| x < PENDING_TIMEOUT)
+            });
+        }
+        self.pending.insert((probe, id), timestamp);
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::protocol::packets::{
+        ClientboundKeepAlive, Ping, PingRequest, Pong, PongResponse, ServerboundKeepAlive,
+    };
+    use crate::protocol::types::State;
+
+    fn record(packet: &Packet, ms: u64) -> PacketRecord<'_> {
+        PacketRecord {
+            timestamp: SystemTime::UNIX_EPOCH + Duration::from_millis(ms),
+            client: "10.0.0.2:50000".parse().unwrap(),
+            server: "10.0.0.1:25565".parse().unwrap(),
+            server_bounded: false,
+            state: State::Play,
+            player: None,
+            packet_id: 0,
+            raw: &[],
+            frame: &[],
+            packet,
+        }
+    }
+
+    #[test]
+    fn tracker() {
+        let mut tracker = Tracker::new();
+        let keep_alive = |id| {
+            Packet::PlayClientbound(PlayClientbound::KeepAlive(ClientboundKeepAlive {
+                keep_alive_id: id,
+            }))
+        };
+        let answer = |id| {
+            Packet::PlayServerbound(PlayServerbound::KeepAlive(ServerboundKeepAlive {
+                keep_alive_id: id,
+            }))
+        };
+        assert_eq!(tracker.packet(&record(&keep_alive(42), 1000)), None);
+        let ping = Packet::ConfigurationClientbound(ConfigurationClientbound::Ping(Ping { id: 7 }));
+        assert_eq!(tracker.packet(&record(&ping, 1010)), None);
+
+        assert_eq!(
+            tracker.packet(&record(&answer(42), 1050)),
+            Some(RoundTrip {
+                timestamp: SystemTime::UNIX_EPOCH + Duration::from_millis(1050),
+                probe: Probe::KeepAlive,
+                rtt: Duration::from_millis(50),
+            })
+        );
+        // Answered once only
+        assert_eq!(tracker.packet(&record(&answer(42), 1060)), None);
+        let pong = Packet::ConfigurationServerbound(ConfigurationServerbound::Pong(Pong { id: 7 }));
+        let pong = tracker.packet(&record(&pong, 1030));
+        assert_eq!(
+            pong.map(|x| (x.probe, x.rtt)),
+            Some((Probe::Ping, Duration::from_millis(20)))
+        );
+
+        let request = Packet::from(PingRequest { timestamp: 1234 });
+        assert_eq!(tracker.packet(&record(&request, 2000)), None);
+        let response = Packet::from(PongResponse { timestamp: 1234 });
+        let pong = tracker.packet(&record(&response, 2003));
+        assert_eq!(pong.map(|x| x.probe), Some(Probe::StatusPing));
+    }
+}
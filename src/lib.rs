@@ -0,0 +1,73 @@
+//! Decoder for the Minecraft Java Edition protocol as seen on the wire.
+//!
+//! [`Session`] is the entry point: it follows one client's connection to a server, taking
+//! captured Ethernet frames, IP packets or plain TCP payloads and giving back typed packets
+//! ([`protocol::packets`]) and session events (state changes, player names, close).
+//!
+//! ```
+//! use minecraft_packet_sniffer::{Session, SessionEvent};
+//! use std::time::SystemTime;
+//!
+//! let mut session = Session::new([10, 0, 0, 1].into(), [10, 0, 0, 2].into());
+//! // A status request handshake, then the status request itself
+//! let payload = [
+//!     0x10, 0x00, 0x81, 0x06, 0x09, b'l', b'o', b'c', b'a', b'l', b'h', b'o', b's', b't',
+//!     0x63, 0xDD, 0x01, 0x01, 0x00,
+//! ];
+//! for event in session.feed_payload(&payload, true, SystemTime::now())? {
+//!     if let SessionEvent::Packet(packet) = event {
+//!         println!("{} {}", packet.state, packet.packet.name());
+//!     }
+//! }
+//! # Ok::<(), color_eyre::Report>(())
+//! ```
+//!
+//! Lower level pieces are public too: [`parser`] for Ethernet, IP and TCP headers,
+//! [`TCPConnection`] for reassembly, [`MinecraftListener`] for decompression and decoding,
+//! and [`protocol::types::Datatype`] for the protocol's data types. [`stream`] has the same as
+//! tokio streams over a live or file capture.
+
+pub mod filter;
+pub mod hooks;
+pub mod latency;
+pub mod metrics;
+pub mod parser;
+pub mod pool;
+pub mod protocol;
+pub mod proxy;
+pub mod session;
+pub mod sink;
+pub mod sniffer;
+pub mod stream;
+pub mod tcp_connection;
+pub mod tui;
+pub mod world;
+
+use crate::parser::ether_ip::IPPacketInfo;
+use std::net::{IpAddr, SocketAddr};
+
+pub use protocol::MinecraftListener;
+pub use session::{DecodedPacket, Session, SessionEvent};
+pub use tcp_connection::TCPConnection;
+
+/// Client IP of a packet to or from `server_ip`, and whether it is server bounded
+pub fn get_client_ip_from_packet(packet: &IPPacketInfo, server_ip: IpAddr) -> (IpAddr, bool) {
+    if packet.src_ip == server_ip {
+        (packet.dst_ip, false)
+    } else {
+        (packet.src_ip, true)
+    }
+}
+
+/// Client address of a TCP packet to or from `server_ip`, and whether it is server bounded.
+/// Several connections can come from one IP, so this is what tells them apart.
+pub fn get_client_from_packet(packet: &IPPacketInfo, server_ip: IpAddr) -> (SocketAddr, bool) {
+    let (client_ip, server_bounded) = get_client_ip_from_packet(packet, server_ip);
+    // The ports start the TCP header, 0 if it's too short to have them
+    let offset = if server_bounded { 0 } else { 2 };
+    let port = packet
+        .payload
+        .get(offset..offset + 2)
+        .map_or(0, |x| u16::from_be_bytes([x[0], x[1]]));
+    (SocketAddr::new(client_ip, port), server_bounded)
+}
@@ -1,120 +1,344 @@
-mod parser;
-mod protocol;
-mod sniffer;
-mod tcp_connection;
-
-use crate::parser::ether_ip::IPPacketInfo;
-use crate::parser::PacketParser;
-use crate::sniffer::sniffer;
-use color_eyre::eyre::{bail, OptionExt};
+use bytes::Bytes;
+use clap::Parser;
+use color_eyre::eyre::{bail, WrapErr};
 use color_eyre::Result;
-use flume::{Receiver, Sender};
-use log::{debug, error, info, LevelFilter};
-use pcap::{Capture, Device, Error};
-use std::collections::HashMap;
-use std::net::IpAddr;
-use std::str::FromStr;
+use log::{debug, info, warn, LevelFilter};
+use minecraft_packet_sniffer::filter::Filter;
+use minecraft_packet_sniffer::get_client_from_packet;
+use minecraft_packet_sniffer::metrics::{self, Metrics};
+use minecraft_packet_sniffer::parser::ether_ip::IPPacketInfo;
+use minecraft_packet_sniffer::parser::PacketParser;
+use minecraft_packet_sniffer::pool::WorkerPool;
+use minecraft_packet_sniffer::protocol;
+use minecraft_packet_sniffer::proxy::{self, Rules};
+use minecraft_packet_sniffer::sink::{
+    Database, JsonOutput, Latencies, PcapngOutput, Script, SinkOptions, Statistics,
+};
+use minecraft_packet_sniffer::stream::{timestamp, CaptureSource};
+use minecraft_packet_sniffer::tui::{self, TuiEvent};
+use minecraft_packet_sniffer::world::{BlockRegistry, WorldExport};
+use pcap::{Activated, Capture, Error};
+use std::net::{AddrParseError, IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::Arc;
 use std::thread;
+use std::time::{Duration, Instant};
+use std::num::NonZero;
+use tokio_util::sync::CancellationToken;
 
-pub fn get_client_ip_from_packet(packet: &IPPacketInfo, server_ip: IpAddr) -> (IpAddr, bool) {
-    if packet.src_ip == server_ip {
-        (packet.dst_ip, false)
-    } else {
-        (packet.src_ip, true)
-    }
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Args {
+    /// Address of the Minecraft server to watch, the port defaults to 25565
+    #[arg(long, default_value = "192.168.1.2", value_parser = server_address)]
+    server: SocketAddr,
+    /// Capture device to listen on
+    #[arg(long, default_value = r"\Device\NPF_{7BA758E3-7038-4689-95D4-975A621288E4}")]
+    device: String,
+    /// Read packets from a capture file instead of a live device
+    #[arg(long)]
+    replay: Option<PathBuf>,
+    /// Instead of capturing, listen on this address and forward clients to the server, decoding
+    /// the traffic on the way through (offline mode servers only)
+    #[arg(long, conflicts_with = "replay")]
+    proxy: Option<SocketAddr>,
+    /// Rules file to drop, delay, duplicate, rewrite or inject packets going through the proxy,
+    /// one `<action> if <filter>` per line
+    #[arg(long, requires = "proxy")]
+    rules: Option<PathBuf>,
+    /// Rebuild the world each client saw as Anvil region files, one folder per client
+    #[arg(long, requires = "blocks")]
+    world: Option<PathBuf>,
+    /// blocks.json report from the vanilla data generator, used to name block states
+    #[arg(long)]
+    blocks: Option<PathBuf>,
+    /// Save each session as a ReplayMod recording (.mcpr) into this folder
+    #[arg(long)]
+    mcpr: Option<PathBuf>,
+    /// Write every decoded packet as a line of JSON to this file, `-` for stdout
+    #[arg(long)]
+    json: Option<PathBuf>,
+    /// Store connections, players and packets in this SQLite database
+    #[arg(long)]
+    database: Option<PathBuf>,
+    /// Write the Minecraft packets, one TCP segment each and commented with their name, to this pcapng file
+    #[arg(long)]
+    pcapng: Option<PathBuf>,
+    /// Only log and export packets matching this expression,
+    /// e.g. `state == play && name =~ "Chat" && dir == c2s && player == "Notch"`
+    #[arg(long)]
+    filter: Option<Filter>,
+    /// Run this Rhai script on every connection, see the README for the functions it can define
+    #[arg(long)]
+    script: Option<PathBuf>,
+    /// Count packets and bytes by connection, direction, state and packet type, logged when the
+    /// capture ends, on Ctrl-C and on SIGUSR1
+    #[arg(long)]
+    stats: bool,
+    /// Measure round trip times from keep alives, pings and TCP acknowledgements, writing each to
+    /// this CSV file (`-` for stdout); per player percentiles are logged like `--stats`
+    #[arg(long)]
+    latency: Option<PathBuf>,
+    /// Serve Prometheus metrics on this address, at `/metrics`
+    #[arg(long)]
+    metrics: Option<SocketAddr>,
+    /// Browse the packets in a terminal UI instead of logging them
+    #[arg(long)]
+    tui: bool,
+    /// Write a Wireshark Lua dissector for the decoded packets to this file and exit
+    #[arg(long)]
+    wireshark: Option<PathBuf>,
+    /// Threads decoding packets, connections are spread over them [default: number of CPUs]
+    #[arg(long)]
+    workers: Option<usize>,
+    /// Packets each worker can have waiting before the capture waits on it
+    #[arg(long, default_value_t = 1024)]
+    queue: usize,
 }
 
 fn main() -> Result<()> {
     color_eyre::install()?;
+    let args = Args::parse();
+    // Logs would draw over the UI
     env_logger::builder()
-        .filter_level(LevelFilter::Info)
+        .filter_level(if args.tui {
+            LevelFilter::Off
+        } else {
+            LevelFilter::Info
+        })
         .init();
 
-    let server_str = "192.168.1.2";
-    let server_ip = IpAddr::from_str(server_str)?;
+    if let Some(path) = args.wireshark {
+        std::fs::write(&path, protocol::wireshark::dissector())
+            .wrap_err(format!("Can't write {}", path.display()))?;
+        info!("Wrote the Wireshark dissector to {}", path.display());
+        return Ok(());
+    }
 
-    let device_list = Device::list()?;
-    println!("{:#?}", device_list);
+    let server = args.server;
+    let rules = match &args.rules {
+        Some(path) => Rules::load(path)?,
+        None => Rules::default(),
+    };
+    let proxy = args.proxy.map(|listen| (listen, rules));
+    let pool = PoolOptions {
+        workers: args
+            .workers
+            .unwrap_or_else(|| thread::available_parallelism().map_or(4, NonZero::get)),
+        queue: args.queue,
+    };
 
-    let device = device_list
-        .into_iter()
-        .find(|x| x.name == *r"\Device\NPF_{7BA758E3-7038-4689-95D4-975A621288E4}")
-        .ok_or_eyre("Device not found")?;
+    let mut sink_options = SinkOptions {
+        mcpr: args.mcpr,
+        filter: args.filter.map(Arc::new),
+        ..Default::default()
+    };
+    if let Some(path) = args.json {
+        sink_options.json = Some(Arc::new(JsonOutput::open(&path)?));
+    }
+    if let Some(path) = args.database {
+        sink_options.database = Some(Arc::new(Database::open(&path)?));
+    }
+    if let Some(path) = args.pcapng {
+        sink_options.pcapng = Some(Arc::new(PcapngOutput::create(&path)?));
+    }
+    if let Some(path) = args.script {
+        sink_options.script = Some(Arc::new(Script::load(&path)?));
+    }
+    if let Some(listen) = args.metrics {
+        let metrics = Arc::new(Metrics::new());
+        metrics::serve(listen, metrics.clone())?;
+        sink_options.metrics = Some(metrics);
+    }
+    let reports = Reports {
+        statistics: args.stats.then(|| Arc::new(Statistics::new())),
+        latencies: match args.latency {
+            Some(path) => Some(Arc::new(Latencies::open(&path)?)),
+            None => None,
+        },
+    };
+    sink_options.stats = reports.statistics.clone();
+    sink_options.latency = reports.latencies.clone();
+    #[cfg(unix)]
+    if reports.statistics.is_some() || reports.latencies.is_some() {
+        report_on_signal(reports.clone())?;
+    }
+    let shutdown = CancellationToken::new();
+    stop_on_ctrlc(shutdown.clone())?;
+    if let (Some(dir), Some(blocks)) = (args.world, args.blocks) {
+        let blocks = BlockRegistry::load(&blocks)?;
+        info!("Loaded {} block states", blocks.len());
+        sink_options.world = Some(Arc::new(WorldExport { dir, blocks }));
+    }
 
-    let mut cap = Capture::from_device(device)
-        .unwrap()
-        .promisc(true)
-        .timeout(0)
-        .immediate_mode(true)
-        .open()?;
-    cap.filter(&format!("tcp and net {server_str} and port 25565"), true)?;
+    if args.tui {
+        let (tx, rx) = flume::unbounded();
+        sink_options.tui = Some(tx.clone());
+        thread::spawn(move || {
+            let result = capture(
+                proxy,
+                args.replay,
+                args.device,
+                server,
+                pool,
+                sink_options,
+                shutdown,
+            );
+            let message = match result {
+                Ok(()) => "Capture finished".to_string(),
+                Err(e) => format!("Capture error: {e}"),
+            };
+            let _ = tx.send(TuiEvent::Message(message));
+        });
+        return tui::run(rx);
+    }
 
-    let mut listener_channels = HashMap::new();
-    let (close_noti_tx, close_noti_rx) = flume::bounded(5);
+    let result = capture(
+        proxy,
+        args.replay,
+        args.device,
+        server,
+        pool,
+        sink_options,
+        shutdown.clone(),
+    );
+    reports.log();
+    if shutdown.is_cancelled() {
+        std::process::exit(130);
+    }
+    result
+}
+
+/// `<IP>` or `<IP>:<PORT>`, the port defaulting to Minecraft's
+fn server_address(address: &str) -> std::result::Result<SocketAddr, AddrParseError> {
+    address
+        .parse()
+        .or_else(|_| Ok(SocketAddr::new(address.parse()?, 25565)))
+}
+
+/// Summaries logged when the capture ends, or on request
+#[derive(Clone)]
+struct Reports {
+    statistics: Option<Arc<Statistics>>,
+    latencies: Option<Arc<Latencies>>,
+}
+
+impl Reports {
+    fn log(&self) {
+        if let Some(statistics) = &self.statistics {
+            info!("Traffic statistics:\n{statistics}");
+        }
+        if let Some(latencies) = &self.latencies {
+            info!("Latency:\n{latencies}");
+        }
+    }
+}
 
-    loop {
+/// Logs the reports on SIGUSR1 without stopping
+#[cfg(unix)]
+fn report_on_signal(reports: Reports) -> Result<()> {
+    use signal_hook::consts::SIGUSR1;
+    use signal_hook::iterator::Signals;
+
+    let mut signals = Signals::new([SIGUSR1])?;
+    thread::spawn(move || {
+        for _ in signals.forever() {
+            reports.log();
+        }
+    });
+    Ok(())
+}
+
+/// Cancels `shutdown` on Ctrl-C so the capture drains its workers and the sinks finish their
+/// files, a second Ctrl-C exits right away
+fn stop_on_ctrlc(shutdown: CancellationToken) -> Result<()> {
+    ctrlc::set_handler(move || {
+        if shutdown.is_cancelled() {
+            std::process::exit(130);
+        }
+        info!("Stopping, Ctrl-C again to exit now");
+        shutdown.cancel();
+    })?;
+    Ok(())
+}
+
+struct PoolOptions {
+    workers: usize,
+    queue: usize,
+}
+
+fn capture(
+    proxy: Option<(SocketAddr, Rules)>,
+    replay: Option<PathBuf>,
+    device: String,
+    server: SocketAddr,
+    pool: PoolOptions,
+    sink_options: SinkOptions,
+    shutdown: CancellationToken,
+) -> Result<()> {
+    if let Some((listen, rules)) = proxy {
+        return proxy::run(listen, server, sink_options, rules, shutdown);
+    }
+    let source = match replay {
+        Some(path) => CaptureSource::File(path),
+        None => CaptureSource::Device(device),
+    };
+    capture_loop(
+        source.open(server)?,
+        server.ip(),
+        pool,
+        sink_options,
+        shutdown,
+    )
+}
+
+fn capture_loop<T: Activated + ?Sized>(
+    mut cap: Capture<T>,
+    server_ip: IpAddr,
+    pool: PoolOptions,
+    sink_options: SinkOptions,
+    shutdown: CancellationToken,
+) -> Result<()> {
+    let metrics = sink_options.metrics.clone();
+    let pool = WorkerPool::new(pool.workers, pool.queue, server_ip, sink_options);
+    if let Some(metrics) = &metrics {
+        metrics.set_workers(pool.stats());
+    }
+    let mut last_stats = Instant::now();
+
+    // The capture timeout lets a quiet device notice the shutdown
+    while !shutdown.is_cancelled() {
+        if let Some(metrics) = &metrics
+            && last_stats.elapsed() >= Duration::from_secs(1)
+        {
+            last_stats = Instant::now();
+            // Capture files have no statistics
+            if let Ok(stats) = cap.stats() {
+                metrics.set_capture(stats.received, stats.dropped, stats.if_dropped);
+            }
+        }
         let packet = match cap.next_packet() {
             Ok(p) => p,
             Err(Error::TimeoutExpired) => continue,
+            Err(Error::NoMorePackets) => break,
             Err(e) => bail!(e),
         };
 
-        if let Ok(ip) = close_noti_rx.try_recv() {
-            listener_channels.remove(&ip);
-        }
-
-        let ip_packet = IPPacketInfo::from_bytes(packet.data)?;
-        let (client_ip, server_bounded) = get_client_ip_from_packet(&ip_packet, server_ip);
-
-        // let tx = listener_channels.entry(client_ip).or_insert({
-        //     debug!("Creating new channel for {client_ip}");
-        //     let (tx, rx) = flume::bounded(20);
-        //     thread::spawn(move || listener_thread_wrapper(rx, server_ip, client_ip));
-        //     tx
-        // });
-
-        let tx = match listener_channels.get(&client_ip) {
-            Some(tx) => tx,
-            None => {
-                debug!("Creating new channel for {client_ip}");
-                let (tx, rx) = flume::bounded(20);
-                let tx_clone = close_noti_tx.clone();
-                thread::spawn(move || sniffer_wrapper(rx, server_ip, client_ip, tx_clone));
-                listener_channels.insert(client_ip, tx);
-                listener_channels.get(&client_ip).expect("Just added above")
+        let timestamp = timestamp(packet.header);
+        // pcap reuses its buffer and the packet is decoded on a worker, so it's copied once here
+        let ip_packet = match IPPacketInfo::from_bytes(&Bytes::copy_from_slice(packet.data)) {
+            Ok(ip_packet) => ip_packet,
+            Err(e) => {
+                warn!("Skipping frame of {} bytes: {e}", packet.data.len());
+                debug!("Frame: {:x?}", packet.data);
+                continue;
             }
         };
-
-        if tx.send((ip_packet, server_bounded)).is_err() {
-            debug!("Channel errored: Removing {client_ip}");
-            listener_channels.remove(&client_ip);
-            continue;
-        }
-
-        listener_channels.retain(|k, v| {
-            if v.is_disconnected() {
-                debug!("Channel closed: Removing");
-                false
-            } else {
-                true
-            }
-        });
+        let (client, server_bounded) = get_client_from_packet(&ip_packet, server_ip);
+        pool.dispatch(ip_packet, client, server_bounded, timestamp)?;
     }
 
+    // At the end of a capture file or on Ctrl-C, let the workers drain what they have left
+    pool.finish();
     Ok(())
 }
-
-fn sniffer_wrapper(
-    rx: Receiver<(IPPacketInfo, bool)>,
-    server_ip: IpAddr,
-    client_ip: IpAddr,
-    close_noti_tx: Sender<IpAddr>,
-) {
-    info!("New connection from {client_ip}");
-    let res = sniffer(rx, server_ip, client_ip);
-    if let Err(e) = res {
-        error!("Sniffer error: {e}");
-    };
-    info!("Connection closed from {client_ip}");
-    let _ = close_noti_tx.send(client_ip);
-}
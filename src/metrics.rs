@@ -0,0 +1,349 @@
+//! Prometheus metrics of a running sniffer, served over HTTP on `/metrics`
+
+use crate::pool::WorkerStats;
+use crate::protocol::types::State;
+use crate::protocol::DecodeError;
+use crate::sink::{Counter, PacketRecord};
+use color_eyre::eyre::WrapErr;
+use itertools::Itertools;
+use log::{debug, info, warn};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+/// Counters shared by every connection, rendered in the Prometheus text format
+#[derive(Debug, Default)]
+pub struct Metrics {
+    connections: AtomicI64,
+    connections_total: AtomicU64,
+    /// By direction, state and packet name
+    packets: Mutex<HashMap<(bool, State, &'static str), Counter>>,
+    errors: Mutex<HashMap<&'static str, u64>>,
+    gaps: AtomicU64,
+    workers: OnceLock<Arc<[WorkerStats]>>,
+    /// Packets the capture received, dropped and the interface dropped, as pcap counts them
+    capture: [AtomicU64; 3],
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn opened(&self) {
+        self.connections.fetch_add(1, Ordering::Relaxed);
+        self.connections_total.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn closed(&self) {
+        self.connections.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    pub fn packet(&self, record: &PacketRecord) {
+        let key = (record.server_bounded, record.state, record.packet.name());
+        let mut packets = self.packets.lock().unwrap();
+        let counter = packets.entry(key).or_default();
+        counter.packets += 1;
+        counter.compressed += record.raw.len() as u64;
+        counter.uncompressed += record.frame.len() as u64;
+    }
+
+    pub fn error(&self, error: &DecodeError) {
+        *self
+            .errors
+            .lock()
+            .unwrap()
+            .entry(error.kind.name())
+            .or_default() += 1;
+    }
+
+    /// TCP segments that arrived ahead of a missing one
+    pub fn gaps(&self, gaps: u64) {
+        self.gaps.fetch_add(gaps, Ordering::Relaxed);
+    }
+
+    /// Queues of the worker pool, to report backpressure
+    pub fn set_workers(&self, workers: Arc<[WorkerStats]>) {
+        let _ = self.workers.set(workers);
+    }
+
+    /// Totals from `pcap`'s capture statistics
+    pub fn set_capture(&self, received: u32, dropped: u32, if_dropped: u32) {
+        for (counter, value) in self.capture.iter().zip([received, dropped, if_dropped]) {
+            counter.store(value.into(), Ordering::Relaxed);
+        }
+    }
+
+    /// Every metric in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let connections = self.connections.load(Ordering::Relaxed).max(0) as u64;
+        metric(
+            &mut out,
+            "connections",
+            "gauge",
+            "Open connections",
+            single(connections),
+        );
+        let total = self.connections_total.load(Ordering::Relaxed);
+        metric(
+            &mut out,
+            "connections_total",
+            "counter",
+            "Connections seen",
+            single(total),
+        );
+
+        let packets = self.packets.lock().unwrap();
+        let packets: Vec<_> = packets
+            .iter()
+            .sorted_by_key(|(key, _)| **key)
+            .map(|((server_bounded, state, name), counter)| {
+                let direction = if *server_bounded { "c2s" } else { "s2c" };
+                let labels =
+                    format!(r#"{{direction="{direction}",state="{state}",packet="{name}"}}"#);
+                (labels, *counter)
+            })
+            .collect();
+        for (name, help, value) in [
+            (
+                "packets_total",
+                "Decoded packets",
+                (|x| x.packets) as fn(&Counter) -> u64,
+            ),
+            (
+                "bytes_total",
+                "Bytes of decoded packets as sent, compressed if compression is on",
+                |x| x.compressed,
+            ),
+            (
+                "uncompressed_bytes_total",
+                "Bytes of decoded packets after decompression",
+                |x| x.uncompressed,
+            ),
+        ] {
+            let samples = packets.iter().map(|(labels, x)| (labels.clone(), value(x)));
+            metric(&mut out, name, "counter", help, samples);
+        }
+
+        let errors = self.errors.lock().unwrap();
+        let errors = errors
+            .iter()
+            .sorted()
+            .map(|(kind, count)| (format!(r#"{{kind="{kind}"}}"#), *count));
+        metric(
+            &mut out,
+            "parse_errors_total",
+            "counter",
+            "Packets that couldn't be decoded",
+            errors,
+        );
+        let gaps = single(self.gaps.load(Ordering::Relaxed));
+        metric(
+            &mut out,
+            "tcp_gaps_total",
+            "counter",
+            "TCP segments that arrived ahead of a missing one",
+            gaps,
+        );
+
+        if let Some(workers) = self.workers.get() {
+            for (name, kind, help, value) in [
+                (
+                    "worker_queued",
+                    "gauge",
+                    "Packets waiting in the worker's queue",
+                    (|x| x.queued.load(Ordering::Relaxed) as u64) as fn(&WorkerStats) -> u64,
+                ),
+                (
+                    "worker_queue_peak",
+                    "gauge",
+                    "Most packets waiting in the worker's queue at once",
+                    |x| x.peak_queued.load(Ordering::Relaxed) as u64,
+                ),
+                (
+                    "worker_stalls_total",
+                    "counter",
+                    "Times the capture waited for the worker's full queue",
+                    |x| x.stalls.load(Ordering::Relaxed),
+                ),
+                (
+                    "worker_packets_total",
+                    "counter",
+                    "Packets the worker handled",
+                    |x| x.packets.load(Ordering::Relaxed),
+                ),
+                (
+                    "worker_evicted_total",
+                    "counter",
+                    "Connections the worker closed for being idle",
+                    |x| x.evicted.load(Ordering::Relaxed),
+                ),
+            ] {
+                let samples = workers
+                    .iter()
+                    .enumerate()
+                    .map(|(i, x)| (format!(r#"{{worker="{i}"}}"#), value(x)));
+                metric(&mut out, name, kind, help, samples);
+            }
+        }
+
+        let capture = self.capture.each_ref().map(|x| x.load(Ordering::Relaxed));
+        for ((name, help), value) in [
+            ("pcap_received_total", "Packets the capture received"),
+            (
+                "pcap_dropped_total",
+                "Packets dropped because the capture buffer was full",
+            ),
+            (
+                "pcap_if_dropped_total",
+                "Packets the network interface or its driver dropped",
+            ),
+        ]
+        .into_iter()
+        .zip(capture)
+        {
+            metric(&mut out, name, "counter", help, single(value));
+        }
+        out
+    }
+}
+
+/// Sample without labels
+fn single(value: u64) -> [(String, u64); 1] {
+    [(String::new(), value)]
+}
+
+/// Appends one metric with its help and type, and one line per set of labels
+fn metric(
+    out: &mut String,
+    name: &str,
+    kind: &str,
+    help: &str,
+    samples: impl IntoIterator<Item = (String, u64)>,
+) {
+    let _ = writeln!(out, "# HELP minecraft_sniffer_{name} {help}");
+    let _ = writeln!(out, "# TYPE minecraft_sniffer_{name} {kind}");
+    for (labels, value) in samples {
+        let _ = writeln!(out, "minecraft_sniffer_{name}{labels} {value}");
+    }
+}
+
+/// Serves `metrics` on `listen` from a background thread
+pub fn serve(listen: SocketAddr, metrics: Arc<Metrics>) -> color_eyre::Result<()> {
+    let listener = TcpListener::bind(listen).wrap_err(format!("Can't listen on {listen}"))?;
+    info!("Serving metrics on http://{listen}/metrics");
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let result = stream.and_then(|stream| respond(stream, &metrics));
+            if let Err(e) = result {
+                debug!("Metrics request failed: {e}");
+            }
+        }
+        warn!("Metrics server stopped");
+    });
+    Ok(())
+}
+
+/// Answers a single HTTP request and closes the connection
+fn respond(mut stream: TcpStream, metrics: &Metrics) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request = String::new();
+    reader.read_line(&mut request)?;
+    // Skip the headers, nothing in them matters
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 2 {
+        line.clear();
+    }
+
+    let (status, body) = match request.split_whitespace().take(2).collect_tuple() {
+        Some(("GET", "/metrics")) => ("200 OK", metrics.render()),
+        Some((_, "/metrics")) => ("405 Method Not Allowed", String::new()),
+        _ => ("404 Not Found", String::new()),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::protocol::packets::LoginStart;
+    use crate::protocol::registry::Packet;
+    use crate::protocol::types::error::DatatypeError;
+    use crate::protocol::DecodeErrorKind;
+    use std::io::Read;
+    use std::time::UNIX_EPOCH;
+    use uuid::Uuid;
+
+    #[test]
+    fn metrics() {
+        let metrics = Arc::new(Metrics::new());
+        metrics.opened();
+        metrics.opened();
+        metrics.closed();
+        let packet = Packet::from(LoginStart {
+            name: "Steve".to_string(),
+            uuid: Uuid::from_u128(1),
+        });
+        metrics.packet(&PacketRecord {
+            timestamp: UNIX_EPOCH,
+            client: "10.0.0.2:50000".parse().unwrap(),
+            server: "10.0.0.1:25565".parse().unwrap(),
+            server_bounded: true,
+            state: State::Login,
+            player: None,
+            packet_id: 0,
+            raw: &[0; 20],
+            frame: &[0; 30],
+            packet: &packet,
+        });
+        metrics.error(&DecodeError {
+            client: [10, 0, 0, 2].into(),
+            server: [10, 0, 0, 1].into(),
+            state: State::Play,
+            server_bounded: false,
+            packet_id: None,
+            offset: 0,
+            kind: DecodeErrorKind::Datatype(DatatypeError::NotEnoughData),
+        });
+        metrics.gaps(3);
+        metrics.set_workers(Arc::new([WorkerStats::default()]));
+        metrics.set_capture(100, 2, 1);
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+        serve(addr, metrics).unwrap();
+        let mut client = TcpStream::connect(addr).unwrap();
+        client
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        for line in [
+            "minecraft_sniffer_connections 1",
+            "minecraft_sniffer_connections_total 2",
+            r#"minecraft_sniffer_packets_total{direction="c2s",state="Login",packet="LoginStart"} 1"#,
+            r#"minecraft_sniffer_bytes_total{direction="c2s",state="Login",packet="LoginStart"} 20"#,
+            r#"minecraft_sniffer_parse_errors_total{kind="not_enough_data"} 1"#,
+            "minecraft_sniffer_tcp_gaps_total 3",
+            r#"minecraft_sniffer_worker_queued{worker="0"} 0"#,
+            "minecraft_sniffer_pcap_dropped_total 2",
+        ] {
+            assert!(response.lines().any(|x| x == line), "{line} missing");
+        }
+    }
+}
@@ -8,4 +8,6 @@ pub enum PacketError {
     IncorrectLength,
     #[error("Packet has unknown protocol: {0}")]
     UnknownProtocol(u16),
+    #[error("Not enough data to parse {field} at byte {offset}")]
+    Truncated { field: &'static str, offset: usize },
 }
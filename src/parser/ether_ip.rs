@@ -1,6 +1,5 @@
-use super::{error::PacketError, slice_to_array_unchecked, PacketParser};
-use color_eyre::eyre::{bail, WrapErr};
-use color_eyre::Result;
+use super::{error::PacketError, field, slice_to_array_unchecked, PacketParser};
+use bytes::Bytes;
 use itertools::Itertools;
 use std::fmt::{Debug, Display, Formatter};
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
@@ -13,106 +12,69 @@ pub struct IPPacketInfo {
     pub payload_protocol: u8, // TODO: Make enum
     pub src_ip: IpAddr,
     pub dst_ip: IpAddr,
-    pub payload: Vec<u8>,
+    pub payload: Bytes,
 }
 
 impl PacketParser for IPPacketInfo {
-    fn from_bytes(data: &[u8]) -> Result<Self> {
-        let dst_mac: [u8; 6] = slice_to_array_unchecked(
-            data.get(0..6)
-                .ok_or(PacketError::IncorrectLength)
-                .wrap_err("Not enough data to parse dst_mac")?,
-        );
-
-        let src_mac: [u8; 6] = slice_to_array_unchecked(
-            data.get(6..12)
-                .ok_or(PacketError::IncorrectLength)
-                .wrap_err("Not enough data to parse src_mac")?,
-        );
-
-        let ether_type: u16 = u16::from_be_bytes(slice_to_array_unchecked(
-            data.get(12..14)
-                .ok_or(PacketError::IncorrectLength)
-                .wrap_err("Not enough data to parse ether_type")?,
-        ));
+    fn from_bytes(data: &Bytes) -> Result<Self, PacketError> {
+        let dst_mac: [u8; 6] = slice_to_array_unchecked(field(data, 0..6, "dst_mac")?);
+
+        let src_mac: [u8; 6] = slice_to_array_unchecked(field(data, 6..12, "src_mac")?);
+
+        let ether_type: u16 =
+            u16::from_be_bytes(slice_to_array_unchecked(field(data, 12..14, "ether_type")?));
 
         let (payload_protocol, src_ip, dst_ip, payload_start, payload_end) = match ether_type {
             0x0800 => {
                 // IPv4
-                let header_length = (*data
-                    .get(14)
-                    .ok_or(PacketError::IncorrectLength)
-                    .wrap_err("Not enough data to parse header_length")?
-                    & 0b00001111)
-                    * 4;
-
-                let total_length_bytes: [u8; 2] = slice_to_array_unchecked(
-                    data.get(16..18)
-                        .ok_or(PacketError::IncorrectLength)
-                        .wrap_err("Not enough data to parse total_length")?,
-                );
+                let header_length = (field(data, 14..15, "header_length")?[0] & 0b00001111) * 4;
+
+                let total_length_bytes: [u8; 2] =
+                    slice_to_array_unchecked(field(data, 16..18, "total_length")?);
                 let total_length = u16::from_be_bytes(total_length_bytes);
 
-                let payload_length = total_length - header_length as u16;
+                let payload_length = total_length
+                    .checked_sub(header_length as u16)
+                    .ok_or(PacketError::IncorrectLength)?;
 
-                let protocol = *data
-                    .get(23)
-                    .ok_or(PacketError::IncorrectLength)
-                    .wrap_err("Not enough data to parse payload_protocol")?;
+                let protocol = field(data, 23..24, "payload_protocol")?[0];
 
-                let src_ip_bytes: [u8; 4] = slice_to_array_unchecked(
-                    data.get(26..30)
-                        .ok_or(PacketError::IncorrectLength)
-                        .wrap_err("Not enough data to parse src_ip_bytes")?,
-                );
+                let src_ip_bytes: [u8; 4] =
+                    slice_to_array_unchecked(field(data, 26..30, "src_ip_bytes")?);
                 let src_ip = IpAddr::V4(Ipv4Addr::from(src_ip_bytes));
 
-                let dst_ip_bytes: [u8; 4] = slice_to_array_unchecked(
-                    data.get(30..34)
-                        .ok_or(PacketError::IncorrectLength)
-                        .wrap_err("Not enough data to parse dst_ip_bytes")?,
-                );
+                let dst_ip_bytes: [u8; 4] =
+                    slice_to_array_unchecked(field(data, 30..34, "dst_ip_bytes")?);
                 let dst_ip = IpAddr::V4(Ipv4Addr::from(dst_ip_bytes));
 
                 (protocol, src_ip, dst_ip, 34, 34 + payload_length as usize)
             }
             0x86DD => {
                 // IPv6
-                let payload_length_bytes: [u8; 2] = slice_to_array_unchecked(
-                    data.get(18..20)
-                        .ok_or(PacketError::IncorrectLength)
-                        .wrap_err("Not enough data to parse payload_length")?,
-                );
+                let payload_length_bytes: [u8; 2] =
+                    slice_to_array_unchecked(field(data, 18..20, "payload_length")?);
                 let payload_length = u16::from_be_bytes(payload_length_bytes);
 
-                let protocol = *data
-                    .get(20)
-                    .ok_or(PacketError::IncorrectLength)
-                    .wrap_err("Not enough data to parse payload_protocol")?;
+                let protocol = field(data, 20..21, "payload_protocol")?[0];
 
-                let src_ip_bytes: [u8; 16] = slice_to_array_unchecked(
-                    data.get(22..38)
-                        .ok_or(PacketError::IncorrectLength)
-                        .wrap_err("Not enough data to parse src_ip_bytes")?,
-                );
+                let src_ip_bytes: [u8; 16] =
+                    slice_to_array_unchecked(field(data, 22..38, "src_ip_bytes")?);
                 let src_ip = IpAddr::V6(Ipv6Addr::from(src_ip_bytes));
 
-                let dst_ip_bytes: [u8; 16] = slice_to_array_unchecked(
-                    data.get(38..54)
-                        .ok_or(PacketError::IncorrectLength)
-                        .wrap_err("Not enough data to parse dst_ip_bytes")?,
-                );
+                let dst_ip_bytes: [u8; 16] =
+                    slice_to_array_unchecked(field(data, 38..54, "dst_ip_bytes")?);
                 let dst_ip = IpAddr::V6(Ipv6Addr::from(dst_ip_bytes));
 
                 (protocol, src_ip, dst_ip, 54, 54 + payload_length as usize)
             }
 
             _ => {
-                bail!(PacketError::UnknownProtocol(ether_type));
+                return Err(PacketError::UnknownProtocol(ether_type));
             }
         };
 
-        let payload = data[payload_start..payload_end].to_vec(); // TODO: Check for error
+        field(data, payload_start..payload_end, "payload")?;
+        let payload = data.slice(payload_start..payload_end);
 
         Ok(Self {
             src_mac,
@@ -147,6 +109,6 @@ mod test {
 
     #[test]
     fn test() {
-        IPPacketInfo::from_bytes(b"123").unwrap();
+        IPPacketInfo::from_bytes(&Bytes::from_static(b"123")).unwrap();
     }
 }
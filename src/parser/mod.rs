@@ -6,10 +6,13 @@ pub mod udp;
 mod test;
 
 use std::fmt::Debug;
-use color_eyre::Result;
+use std::ops::{Bound, RangeBounds};
+use std::slice::SliceIndex;
+use bytes::Bytes;
+use error::PacketError;
 
 pub trait PacketParser {
-    fn from_bytes(data: &[u8]) -> Result<Self>
+    fn from_bytes(data: &Bytes) -> Result<Self, PacketError>
     where
         Self: Sized;
 }
@@ -21,4 +24,20 @@ where
     <[T; N] as TryFrom<&'a [T]>>::Error: Debug,
 {
     slice.try_into().unwrap()
-}
\ No newline at end of file
+}
+
+/// Bytes of a header field, or which field the packet ends in
+pub fn field<'a, R>(data: &'a [u8], range: R, name: &'static str) -> Result<&'a [u8], PacketError>
+where
+    R: RangeBounds<usize> + SliceIndex<[u8], Output = [u8]>,
+{
+    let offset = match range.start_bound() {
+        Bound::Included(&x) => x,
+        Bound::Excluded(&x) => x + 1,
+        Bound::Unbounded => 0,
+    };
+    data.get(range).ok_or(PacketError::Truncated {
+        field: name,
+        offset,
+    })
+}
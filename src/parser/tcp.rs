@@ -1,8 +1,8 @@
 use crate::parser::error::PacketError;
 use crate::parser::ether_ip::IPPacketInfo;
-use crate::parser::{slice_to_array_unchecked, PacketParser};
+use crate::parser::{field, slice_to_array_unchecked, PacketParser};
 use bitflags::bitflags;
-use color_eyre::eyre::Context;
+use bytes::Bytes;
 use std::fmt::{Display, Formatter};
 // #[derive(Debug, Copy, Clone)]
 // #[repr(u8)]
@@ -39,72 +39,45 @@ pub struct TCPPacketInfo {
     pub window_size: u16,
     pub checksum: u16,
     pub options: Option<Vec<u8>>, // TODO: Make struct
-    pub payload: Vec<u8>,
+    pub payload: Bytes,
 }
 
 impl PacketParser for TCPPacketInfo {
     // FIXME: This is incorrect, refer back to TCP header
-    fn from_bytes(data: &[u8]) -> color_eyre::Result<Self>
+    fn from_bytes(data: &Bytes) -> Result<Self, PacketError>
     where
         Self: Sized,
     {
-        let src_port = u16::from_be_bytes(slice_to_array_unchecked(
-            data.get(0..2)
-                .ok_or(PacketError::IncorrectLength)
-                .wrap_err("Not enough data to parse src_port")?,
-        ));
-
-        let dst_port = u16::from_be_bytes(slice_to_array_unchecked(
-            data.get(2..4)
-                .ok_or(PacketError::IncorrectLength)
-                .wrap_err("Not enough data to parse dst_port")?,
-        ));
-
-        let seq_num = u32::from_be_bytes(slice_to_array_unchecked(
-            data.get(4..8)
-                .ok_or(PacketError::IncorrectLength)
-                .wrap_err("Not enough data to parse seq_num")?,
-        ));
-
-        let ack_num = u32::from_be_bytes(slice_to_array_unchecked(
-            data.get(8..12)
-                .ok_or(PacketError::IncorrectLength)
-                .wrap_err("Not enough data to parse ack_num")?,
-        ));
-
-        let flags_num = u16::from_be_bytes(slice_to_array_unchecked(
-            data.get(12..14)
-                .ok_or(PacketError::IncorrectLength)
-                .wrap_err("Not enough data to parse flags")?,
-        ));
+        let src_port = u16::from_be_bytes(slice_to_array_unchecked(field(data, 0..2, "src_port")?));
+
+        let dst_port = u16::from_be_bytes(slice_to_array_unchecked(field(data, 2..4, "dst_port")?));
+
+        let seq_num = u32::from_be_bytes(slice_to_array_unchecked(field(data, 4..8, "seq_num")?));
+
+        let ack_num = u32::from_be_bytes(slice_to_array_unchecked(field(data, 8..12, "ack_num")?));
+
+        let flags_num = u16::from_be_bytes(slice_to_array_unchecked(field(data, 12..14, "flags")?));
         let flags = TCPFlag::from_bits_truncate(flags_num);
 
         let data_offset_bytes = ((flags_num & 0b1111000000000000) >> 12) * 4;
 
-        let window_size = u16::from_be_bytes(slice_to_array_unchecked(
-            data.get(14..16)
-                .ok_or(PacketError::IncorrectLength)
-                .wrap_err("Not enough data to parse window_size")?,
-        ));
+        let window_size = u16::from_be_bytes(slice_to_array_unchecked(field(
+            data,
+            14..16,
+            "window_size",
+        )?));
 
-        let checksum = u16::from_be_bytes(slice_to_array_unchecked(
-            data.get(16..18)
-                .ok_or(PacketError::IncorrectLength)
-                .wrap_err("Not enough data to parse window_size")?,
-        ));
+        let checksum =
+            u16::from_be_bytes(slice_to_array_unchecked(field(data, 16..18, "checksum")?));
 
         let options = if data_offset_bytes > 20 {
-            Some(
-                data.get(20..data_offset_bytes as usize)
-                    .ok_or(PacketError::IncorrectLength)
-                    .wrap_err("Not enough data to parse window_size")?
-                    .to_vec(),
-            )
+            Some(field(data, 20..data_offset_bytes as usize, "options")?.to_vec())
         } else {
             None
         };
 
-        let payload = data[data_offset_bytes as usize..].to_vec(); // TODO: Check error
+        field(data, data_offset_bytes as usize.., "payload")?;
+        let payload = data.slice(data_offset_bytes as usize..);
 
         Ok(Self {
             src_port,
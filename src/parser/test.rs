@@ -1,5 +1,6 @@
 #![cfg(test)]
 use crate::parser::ether_ip::IPPacketInfo;
+use bytes::Bytes;
 use crate::parser::PacketParser;
 use crate::parser::tcp::TCPPacketInfo;
 use crate::parser::udp::UDPPacketInfo;
@@ -20,7 +21,7 @@ fn test() {
 
 
 
-    let ip = IPPacketInfo::from_bytes(&packet_bytes).unwrap();
+    let ip = IPPacketInfo::from_bytes(&Bytes::copy_from_slice(&packet_bytes)).unwrap();
     println!("IP: {ip}");
     let tcp = TCPPacketInfo::from_bytes(&ip.payload).unwrap();
     println!("TCP: {tcp:?}");
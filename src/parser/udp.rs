@@ -1,6 +1,6 @@
 use crate::parser::error::PacketError;
-use crate::parser::{slice_to_array_unchecked, PacketParser};
-use color_eyre::eyre::Context;
+use crate::parser::{field, slice_to_array_unchecked, PacketParser};
+use bytes::Bytes;
 use std::fmt::{Display, Formatter};
 
 #[derive(Debug, Clone)]
@@ -8,33 +8,22 @@ pub struct UDPPacketInfo {
     pub src_port: u16,
     pub dst_port: u16,
     pub checksum: u16,
-    pub payload: Vec<u8>,
+    pub payload: Bytes,
 }
 
 impl PacketParser for UDPPacketInfo {
-    fn from_bytes(data: &[u8]) -> color_eyre::Result<Self>
+    fn from_bytes(data: &Bytes) -> Result<Self, PacketError>
     where
         Self: Sized,
     {
-        let src_port = u16::from_be_bytes(slice_to_array_unchecked(
-            data.get(0..2)
-                .ok_or(PacketError::IncorrectLength)
-                .wrap_err("Not enough data to parse src_port")?,
-        ));
+        let src_port = u16::from_be_bytes(slice_to_array_unchecked(field(data, 0..2, "src_port")?));
 
-        let dst_port = u16::from_be_bytes(slice_to_array_unchecked(
-            data.get(2..4)
-                .ok_or(PacketError::IncorrectLength)
-                .wrap_err("Not enough data to parse dst_port")?,
-        ));
+        let dst_port = u16::from_be_bytes(slice_to_array_unchecked(field(data, 2..4, "dst_port")?));
 
-        let checksum = u16::from_be_bytes(slice_to_array_unchecked(
-            data.get(6..8)
-                .ok_or(PacketError::IncorrectLength)
-                .wrap_err("Not enough data to parse checksum")?,
-        ));
+        let checksum = u16::from_be_bytes(slice_to_array_unchecked(field(data, 6..8, "checksum")?));
 
-        let payload = data[8..].to_vec(); // TODO: Check error
+        field(data, 8.., "payload")?;
+        let payload = data.slice(8..);
 
         Ok(Self {
             src_port,
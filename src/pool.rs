@@ -0,0 +1,257 @@
+use crate::parser::ether_ip::IPPacketInfo;
+use crate::sink::SinkOptions;
+use crate::sniffer::Sniffer;
+use color_eyre::eyre::bail;
+use flume::{Receiver, Sender, TrySendError};
+use log::{debug, error, info};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
+
+/// Connections without packets for this long are closed, clients send a keep alive every 15s
+const IDLE_TIMEOUT: Duration = Duration::from_secs(120);
+const SWEEP_INTERVAL: Duration = Duration::from_secs(10);
+
+type Job = (IPPacketInfo, SocketAddr, bool, SystemTime);
+
+/// Counters of one worker, updated as it goes
+#[derive(Debug, Default)]
+pub struct WorkerStats {
+    /// Packets waiting in the queue
+    pub queued: AtomicUsize,
+    /// Most packets waiting at once
+    pub peak_queued: AtomicUsize,
+    /// Packets handled
+    pub packets: AtomicU64,
+    /// Times the capture had to wait for a full queue
+    pub stalls: AtomicU64,
+    /// Open connections
+    pub connections: AtomicUsize,
+    /// Connections closed for being idle
+    pub evicted: AtomicU64,
+}
+
+impl Display for WorkerStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} packets, {} connections, {} evicted, {} queued (peak {}), {} stalls",
+            self.packets.load(Ordering::Relaxed),
+            self.connections.load(Ordering::Relaxed),
+            self.evicted.load(Ordering::Relaxed),
+            self.queued.load(Ordering::Relaxed),
+            self.peak_queued.load(Ordering::Relaxed),
+            self.stalls.load(Ordering::Relaxed),
+        )
+    }
+}
+
+/// Fixed set of threads decoding the capture, each connection is hashed onto one of them by client
+/// address
+pub struct WorkerPool {
+    queues: Vec<Sender<Job>>,
+    workers: Vec<JoinHandle<()>>,
+    stats: Arc<[WorkerStats]>,
+}
+
+impl WorkerPool {
+    /// Starts `workers` threads with room for `queue` packets each before the capture has to wait
+    pub fn new(workers: usize, queue: usize, server_ip: IpAddr, sink_options: SinkOptions) -> Self {
+        let workers = workers.max(1);
+        let stats: Arc<[WorkerStats]> = (0..workers).map(|_| WorkerStats::default()).collect();
+        let (queues, workers) = (0..workers)
+            .map(|index| {
+                let (tx, rx) = flume::bounded(queue);
+                let sink_options = sink_options.clone();
+                let stats = stats.clone();
+                let handle = thread::Builder::new()
+                    .name(format!("worker-{index}"))
+                    .spawn(move || worker(rx, server_ip, &sink_options, &stats[index]))
+                    .expect("Failed to spawn worker thread");
+                (tx, handle)
+            })
+            .unzip();
+        Self {
+            queues,
+            workers,
+            stats,
+        }
+    }
+
+    pub fn stats(&self) -> Arc<[WorkerStats]> {
+        self.stats.clone()
+    }
+
+    /// Queues a packet on its connection's worker, waiting if that worker is behind
+    pub fn dispatch(
+        &self,
+        ip_packet: IPPacketInfo,
+        client: SocketAddr,
+        server_bounded: bool,
+        timestamp: SystemTime,
+    ) -> color_eyre::Result<()> {
+        let mut hasher = DefaultHasher::new();
+        client.hash(&mut hasher);
+        let index = (hasher.finish() % self.queues.len() as u64) as usize;
+        let (queue, stats) = (&self.queues[index], &self.stats[index]);
+
+        let queued = stats.queued.fetch_add(1, Ordering::Relaxed) + 1;
+        stats.peak_queued.fetch_max(queued, Ordering::Relaxed);
+        let job = (ip_packet, client, server_bounded, timestamp);
+        let res = match queue.try_send(job) {
+            Err(TrySendError::Full(job)) => {
+                stats.stalls.fetch_add(1, Ordering::Relaxed);
+                debug!("Worker {index} is full, waiting");
+                queue.send(job).map_err(|_| ())
+            }
+            res => res.map_err(|_| ()),
+        };
+        if res.is_err() {
+            bail!("Worker {index} stopped");
+        }
+        Ok(())
+    }
+
+    /// Lets the workers drain their queues and close every connection
+    pub fn finish(self) {
+        drop(self.queues);
+        for worker in self.workers {
+            let _ = worker.join();
+        }
+        for (index, stats) in self.stats.iter().enumerate() {
+            info!("Worker {index}: {stats}");
+        }
+    }
+}
+
+fn worker(rx: Receiver<Job>, server_ip: IpAddr, sink_options: &SinkOptions, stats: &WorkerStats) {
+    let mut connections: HashMap<SocketAddr, Sniffer> = HashMap::new();
+    let mut last_sweep = SystemTime::UNIX_EPOCH;
+
+    for (ip_packet, client, server_bounded, timestamp) in rx.iter() {
+        stats.queued.fetch_sub(1, Ordering::Relaxed);
+        stats.packets.fetch_add(1, Ordering::Relaxed);
+
+        let sniffer = connections
+            .entry(client)
+            .or_insert_with(|| Sniffer::new(server_ip, client.ip(), sink_options));
+        let open = sniffer
+            .feed(&ip_packet, server_bounded, timestamp)
+            .unwrap_or_else(|e| {
+                error!("Sniffer error: {e}");
+                false
+            });
+        if !open && let Some(sniffer) = connections.remove(&client) {
+            sniffer.finish();
+        }
+
+        // Clients that vanish without a FIN, or a SYN scan, would otherwise stay forever
+        if timestamp
+            .duration_since(last_sweep)
+            .is_ok_and(|x| x >= SWEEP_INTERVAL)
+        {
+            last_sweep = timestamp;
+            let idle = connections.extract_if(|_, sniffer| {
+                timestamp
+                    .duration_since(sniffer.last_seen)
+                    .is_ok_and(|x| x > IDLE_TIMEOUT)
+            });
+            for (client, sniffer) in idle {
+                debug!("Connection from {client} idle, closing");
+                sniffer.finish();
+                stats.evicted.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        stats
+            .connections
+            .store(connections.len(), Ordering::Relaxed);
+    }
+
+    for (_, sniffer) in connections.drain() {
+        sniffer.finish();
+    }
+    stats.connections.store(0, Ordering::Relaxed);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::get_client_from_packet;
+    use bytes::Bytes;
+    use std::time::UNIX_EPOCH;
+
+    fn ip_packet(client: u8, server_ip: IpAddr) -> IPPacketInfo {
+        IPPacketInfo {
+            src_mac: [0; 6],
+            dst_mac: [0; 6],
+            ether_type: 0x0800,
+            payload_protocol: 6,
+            src_ip: [10, 0, 1, client].into(),
+            dst_ip: server_ip,
+            // Not a TCP header, the connection errors out and closes
+            payload: Bytes::from_static(&[0; 4]),
+        }
+    }
+
+    #[test]
+    fn pool() {
+        let server_ip: IpAddr = [10, 0, 0, 1].into();
+        let pool = WorkerPool::new(3, 2, server_ip, SinkOptions::default());
+        let stats = pool.stats();
+        for i in 0..40 {
+            let packet = ip_packet(i % 8, server_ip);
+            let (client, _) = get_client_from_packet(&packet, server_ip);
+            let timestamp = UNIX_EPOCH + Duration::from_secs(i as u64 * 60);
+            pool.dispatch(packet, client, true, timestamp).unwrap();
+        }
+        pool.finish();
+
+        assert_eq!(stats.len(), 3);
+        let packets: u64 = stats
+            .iter()
+            .map(|x| x.packets.load(Ordering::Relaxed))
+            .sum();
+        assert_eq!(packets, 40);
+        for stats in stats.iter() {
+            assert_eq!(stats.queued.load(Ordering::Relaxed), 0);
+            assert_eq!(stats.connections.load(Ordering::Relaxed), 0);
+            assert!(stats.peak_queued.load(Ordering::Relaxed) <= 40);
+        }
+    }
+
+    #[test]
+    fn ports() {
+        let server_ip: IpAddr = [10, 0, 0, 1].into();
+        let pool = WorkerPool::new(1, 8, server_ip, SinkOptions::default());
+        let stats = pool.stats();
+        // Two connections from behind the same NAT, each opened with a SYN
+        for port in [50000u16, 50001] {
+            let mut tcp = port.to_be_bytes().to_vec();
+            tcp.extend(25565u16.to_be_bytes());
+            tcp.extend([0; 8]);
+            tcp.extend([0x50, 0x02, 0xFF, 0xFF, 0, 0, 0, 0]);
+            let packet = IPPacketInfo {
+                payload: Bytes::from(tcp),
+                ..ip_packet(5, server_ip)
+            };
+            let (client, server_bounded) = get_client_from_packet(&packet, server_ip);
+            assert_eq!(client, SocketAddr::new([10, 0, 1, 5].into(), port));
+            pool.dispatch(packet, client, server_bounded, UNIX_EPOCH)
+                .unwrap();
+        }
+        // Counted once each packet is handled, keyed by IP they would stay at 1
+        let start = std::time::Instant::now();
+        while stats[0].connections.load(Ordering::Relaxed) < 2
+            && start.elapsed() < Duration::from_secs(5)
+        {
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(stats[0].connections.load(Ordering::Relaxed), 2);
+        pool.finish();
+    }
+}
@@ -0,0 +1,57 @@
+use crate::protocol::types::error::DatatypeError;
+use crate::protocol::types::State;
+use std::net::IpAddr;
+use thiserror::Error;
+
+/// What went wrong decoding a packet
+#[derive(Debug, Error)]
+pub enum DecodeErrorKind {
+    #[error(transparent)]
+    Datatype(#[from] DatatypeError),
+    #[error("Can't decompress: {0}")]
+    Decompress(#[from] std::io::Error),
+    #[error("Uncompressed length {0} is outside of 0 to 2^23")]
+    DataLength(i32),
+    #[error("Decompressed to {actual} bytes instead of {expected}")]
+    DecompressedLength { expected: usize, actual: usize },
+    #[error("There is no client bounded Handshaking packet")]
+    ClientBoundHandshake,
+    /// The rest of the direction can't be split into packets after this
+    #[error("Packet length {0} is outside of 1 to 2^21 - 1")]
+    Length(i32),
+}
+
+impl DecodeErrorKind {
+    /// Short name of the kind, for grouping errors
+    pub fn name(&self) -> &'static str {
+        match self {
+            DecodeErrorKind::Datatype(e) => e.name(),
+            DecodeErrorKind::Decompress(_) => "decompress",
+            DecodeErrorKind::DataLength(_) => "data_length",
+            DecodeErrorKind::DecompressedLength { .. } => "decompressed_length",
+            DecodeErrorKind::ClientBoundHandshake => "clientbound_handshake",
+            DecodeErrorKind::Length(_) => "length",
+        }
+    }
+}
+
+/// A packet that couldn't be decoded, with where it was in the connection.
+///
+/// Packets are length prefixed, so the next one can still be decoded after this.
+#[derive(Debug, Error)]
+#[error(
+    "Malformed {direction} packet {id} from {client} <-> {server} in {state} at byte {offset}: {kind}",
+    direction = if *.server_bounded { "C->S" } else { "S->C" },
+    id = .packet_id.map_or("?".to_string(), |x| format!("0x{x:02X}"))
+)]
+pub struct DecodeError {
+    pub client: IpAddr,
+    pub server: IpAddr,
+    pub state: State,
+    pub server_bounded: bool,
+    /// Unknown if the packet couldn't be decompressed or its ID is malformed
+    pub packet_id: Option<i32>,
+    /// Where decoding stopped, in the uncompressed packet if compression is on
+    pub offset: usize,
+    pub kind: DecodeErrorKind,
+}
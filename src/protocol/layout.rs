@@ -0,0 +1,150 @@
+//! Finding and rewriting single fields of a raw packet, from its wire layout in the registry
+
+use crate::protocol::registry::{Field, FieldKind};
+use crate::protocol::types::error::DatatypeError;
+use crate::protocol::types::{Datatype, VarInt, VarLong};
+use std::ops::Range;
+
+/// Byte range and kind of the field at `path` in `data`, the packet data after its ID.
+/// Nested struct fields are `.` separated, fields inside arrays or optionals can't be reached.
+pub fn locate(
+    fields: &'static [Field],
+    data: &[u8],
+    path: &str,
+) -> Result<Option<(Range<usize>, &'static FieldKind)>, DatatypeError> {
+    let (name, rest) = match path.split_once('.') {
+        Some((name, rest)) => (name, Some(rest)),
+        None => (path, None),
+    };
+    let mut bytes = data;
+    for field in fields {
+        let start = data.len() - bytes.len();
+        if field.name == name {
+            return match (rest, &field.kind) {
+                (None, kind) => {
+                    skip(kind, &mut bytes)?;
+                    Ok(Some((start..data.len() - bytes.len(), kind)))
+                }
+                (Some(rest), FieldKind::Struct(fields)) => {
+                    let found = locate(fields, &data[start..], rest)?;
+                    Ok(found.map(|(range, kind)| (range.start + start..range.end + start, kind)))
+                }
+                (Some(_), _) => Ok(None),
+            };
+        }
+        skip(&field.kind, &mut bytes)?;
+    }
+    Ok(None)
+}
+
+/// Moves `bytes` past one field of `kind`
+fn skip(kind: &FieldKind, bytes: &mut &[u8]) -> Result<(), DatatypeError> {
+    let length = match kind {
+        FieldKind::Bool | FieldKind::Byte | FieldKind::UnsignedByte | FieldKind::Angle => 1,
+        FieldKind::Short | FieldKind::UnsignedShort => 2,
+        FieldKind::Int | FieldKind::Float => 4,
+        FieldKind::Long | FieldKind::Double | FieldKind::Position => 8,
+        FieldKind::Uuid => 16,
+        FieldKind::VarInt => {
+            VarInt::from_bytes(bytes)?;
+            0
+        }
+        FieldKind::VarLong => {
+            VarLong::from_bytes(bytes)?;
+            0
+        }
+        FieldKind::String | FieldKind::Identifier => {
+            let length = VarInt::from_bytes(bytes)?.value;
+            usize::try_from(length).map_err(|_| DatatypeError::Invalid)?
+        }
+        FieldKind::Array(kind) => {
+            for _ in 0..VarInt::from_bytes(bytes)?.value {
+                skip(kind, bytes)?;
+            }
+            0
+        }
+        FieldKind::Optional(kind) => {
+            if bool::from_bytes(bytes)? {
+                skip(kind, bytes)?;
+            }
+            0
+        }
+        FieldKind::Struct(fields) => {
+            for field in *fields {
+                skip(&field.kind, bytes)?;
+            }
+            0
+        }
+        FieldKind::Bytes | FieldKind::Rest(_) => bytes.len(),
+    };
+    let (_, rest) = bytes
+        .split_at_checked(length)
+        .ok_or(DatatypeError::NotEnoughData)?;
+    *bytes = rest;
+    Ok(())
+}
+
+/// Wire encoding of `value` as a field of `kind`, `None` if it doesn't fit or the kind is
+/// not a single value
+pub fn encode(kind: &FieldKind, value: &str) -> Option<Vec<u8>> {
+    let bytes = match kind {
+        FieldKind::Bool => vec![value.parse::<bool>().ok()? as u8],
+        FieldKind::Byte => value.parse::<i8>().ok()?.to_be_bytes().to_vec(),
+        FieldKind::UnsignedByte | FieldKind::Angle => {
+            value.parse::<u8>().ok()?.to_be_bytes().to_vec()
+        }
+        FieldKind::Short => value.parse::<i16>().ok()?.to_be_bytes().to_vec(),
+        FieldKind::UnsignedShort => value.parse::<u16>().ok()?.to_be_bytes().to_vec(),
+        FieldKind::Int => value.parse::<i32>().ok()?.to_be_bytes().to_vec(),
+        FieldKind::Long => value.parse::<i64>().ok()?.to_be_bytes().to_vec(),
+        FieldKind::Float => value.parse::<f32>().ok()?.to_be_bytes().to_vec(),
+        FieldKind::Double => value.parse::<f64>().ok()?.to_be_bytes().to_vec(),
+        FieldKind::VarInt => VarInt {
+            value: value.parse().ok()?,
+        }
+        .to_bytes(),
+        FieldKind::VarLong => VarLong {
+            value: value.parse().ok()?,
+        }
+        .to_bytes(),
+        FieldKind::String | FieldKind::Identifier => {
+            let mut bytes = VarInt {
+                value: value.len().try_into().ok()?,
+            }
+            .to_bytes();
+            bytes.extend(value.as_bytes());
+            bytes
+        }
+        FieldKind::Uuid => uuid::Uuid::parse_str(value).ok()?.as_bytes().to_vec(),
+        _ => return None,
+    };
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::protocol::registry;
+    use crate::protocol::types::State;
+
+    #[test]
+    fn client_information() {
+        let info = registry::find(State::Configuration, true, 0x00).unwrap();
+        let mut data = vec![0x05];
+        data.extend(b"en_us");
+        // View distance, chat mode and the rest
+        data.extend([0x0C, 0x00, 0x01, 0x7F, 0x01, 0x00, 0x01, 0x00]);
+
+        let (range, kind) = locate(info.fields, &data, "view_distance")
+            .unwrap()
+            .unwrap();
+        assert_eq!(range, 6..7);
+        assert_eq!(encode(kind, "2"), Some(vec![0x02]));
+        assert_eq!(encode(kind, "200"), None);
+        let (range, _) = locate(info.fields, &data, "particle_status")
+            .unwrap()
+            .unwrap();
+        assert_eq!(range, 13..14);
+        assert!(locate(info.fields, &data, "missing").unwrap().is_none());
+    }
+}
@@ -1,18 +1,27 @@
+use crate::protocol::error::{DecodeError, DecodeErrorKind};
 use crate::protocol::packets::*;
-use crate::protocol::types::{Datatype, State, VarInt};
-use color_eyre::eyre::bail;
+use crate::protocol::registry::{
+    self, HandshakingServerbound, LoginClientbound, Packet, PlayClientbound, Transition,
+};
+use crate::protocol::types::{Datatype, EntityType, State, VarInt};
+use bytes::Bytes;
 use flate2::bufread::ZlibDecoder;
 use itertools::Itertools;
 use log::{debug, info};
-use std::collections::VecDeque;
+use std::collections::HashMap;
 use std::io::Read;
 use std::net::IpAddr;
 
+/// Largest uncompressed packet the protocol allows
+const MAX_PACKET_LENGTH: usize = 1 << 23;
+
+#[derive(Debug)]
 pub struct MinecraftListener {
     state: State,
     compression: bool,
     server_ip: IpAddr,
     client_ip: IpAddr,
+    entities: HashMap<i32, EntityType>,
 }
 
 impl MinecraftListener {
@@ -22,152 +31,212 @@ impl MinecraftListener {
             compression: false,
             server_ip,
             client_ip,
+            entities: HashMap::new(),
+        }
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    /// Undoes compression, leaving the packet ID followed by the packet data.
+    /// Uncompressed packets are sliced out of `bytes` rather than copied.
+    pub fn decompress(&self, bytes: &Bytes, server_bounded: bool) -> Result<Bytes, DecodeError> {
+        debug!("Data: {bytes:x?}");
+
+        if !self.compression {
+            return Ok(bytes.clone());
+        }
+        let mut data = &bytes[..];
+        let data_length = VarInt::from_bytes(&mut data)
+            .map_err(|e| self.error(server_bounded, None, 0, e.into()))?;
+        let offset = bytes.len() - data.len();
+        if data_length.value == 0 {
+            return Ok(bytes.slice(offset..));
+        }
+        // The length comes off the wire, so it's checked before inflating anything
+        let expected = match usize::try_from(data_length.value) {
+            Ok(expected) if expected <= MAX_PACKET_LENGTH => expected,
+            _ => {
+                let kind = DecodeErrorKind::DataLength(data_length.value);
+                return Err(self.error(server_bounded, None, 0, kind));
+            }
+        };
+        let mut output = Vec::with_capacity(expected);
+        // Read one byte past it to notice a packet decompressing to more
+        ZlibDecoder::new(data)
+            .take(expected as u64 + 1)
+            .read_to_end(&mut output)
+            .map_err(|e| self.error(server_bounded, None, offset, e.into()))?;
+        if output.len() != expected {
+            let kind = DecodeErrorKind::DecompressedLength {
+                expected,
+                actual: output.len(),
+            };
+            return Err(self.error(server_bounded, None, offset, kind));
         }
+        Ok(output.into())
     }
 
     pub fn parse_packet(
         &mut self,
-        bytes: &[u8],
+        bytes: &Bytes,
         server_bounded: bool,
-    ) -> color_eyre::Result<Box<dyn MinecraftPacket>> {
-        let mut bytes = VecDeque::from(bytes.to_vec());
-        debug!("Data: {bytes:x?}");
-        
-        let (packet_id, mut bytes) = {
-            let mut bytes = if !self.compression {
-                bytes
-            } else {
-                let data_length = VarInt::from_bytes(&mut bytes)?;
-                if data_length.value == 0 {
-                    bytes
-                } else {
-                    let bytes_vec: Vec<_> = bytes.into_iter().collect();
-                    let mut decoder = ZlibDecoder::new(&bytes_vec[..]);
-                    let mut output = Vec::new();
-                    decoder.read_to_end(&mut output)?;
-                    let temp = VecDeque::from(output);
-                    assert_eq!(temp.len(), data_length.value as usize);
-                    temp
-                }
-            };
-            let packet_id = VarInt::from_bytes(&mut bytes)?.value;
-            (packet_id, bytes)
+    ) -> Result<Packet, DecodeError> {
+        let frame = self.decompress(bytes, server_bounded)?;
+        self.parse_frame(&frame, server_bounded)
+    }
+
+    /// Parses an already decompressed packet, see [`Self::decompress`]. Packets the registry
+    /// doesn't know keep a slice of `frame` rather than a copy.
+    pub fn parse_frame(
+        &mut self,
+        frame: &Bytes,
+        server_bounded: bool,
+    ) -> Result<Packet, DecodeError> {
+        let mut bytes = &frame[..];
+        let packet_id = VarInt::from_bytes(&mut bytes)
+            .map_err(|e| self.error(server_bounded, None, 0, e.into()))?
+            .value;
+
+        if !server_bounded && self.state == State::Handshaking {
+            let offset = frame.len() - bytes.len();
+            let kind = DecodeErrorKind::ClientBoundHandshake;
+            return Err(self.error(server_bounded, Some(packet_id), offset, kind));
+        }
+        let Some(info) = registry::find(self.state, server_bounded, packet_id) else {
+            return Ok(Packet::Unknown(Unknown {
+                packet_id,
+                server_bounded,
+                payload: frame.slice_ref(bytes),
+            }));
         };
+        let mut packet = (info.parse)(&mut bytes).map_err(|e| {
+            let offset = frame.len() - bytes.len();
+            self.error(server_bounded, Some(packet_id), offset, e.into())
+        })?;
 
-        if server_bounded {
-            // Server bounded
-            match self.state {
-                State::Handshaking => match packet_id {
-                    0 => {
-                        let packet = Handshake::from_bytes(&mut bytes)?;
-                        info!("Switching state: {}", packet.next_state);
-                        self.state = packet.next_state;
-                        Ok(Box::new(packet))
-                    }
-                    _ => Ok(Box::new({
-                        let mut unknown = Unknown::from_bytes(&mut bytes)?;
-                        unknown.packet_id = packet_id;
-                        unknown.server_bounded = server_bounded;
-                        unknown
-                    })),
-                },
-                State::Status => match packet_id {
-                    0 => Ok(Box::new(StatusRequest::from_bytes(&mut bytes)?)),
-                    1 => Ok(Box::new(PingRequest::from_bytes(&mut bytes)?)),
-                    _ => Ok(Box::new({
-                        let mut unknown = Unknown::from_bytes(&mut bytes)?;
-                        unknown.packet_id = packet_id;
-                        unknown.server_bounded = server_bounded;
-                        unknown
-                    })),
-                },
-                State::Login => match packet_id {
-                    0 => Ok(Box::new(LoginStart::from_bytes(&mut bytes)?)),
-                    3 => {
-                        self.state = State::Configuration;
-                        Ok(Box::new(LoginAcknowledged::from_bytes(&mut bytes)?))
+        match info.transition {
+            Some(Transition::Handshake) => {
+                if let Packet::HandshakingServerbound(HandshakingServerbound::Handshake(
+                    handshake,
+                )) = &packet
+                {
+                    info!("Switching state: {}", handshake.next_state);
+                    self.state = handshake.next_state;
+                }
+            }
+            Some(Transition::To(state)) => {
+                info!("Switching state: {state}");
+                self.state = state;
+            }
+            Some(Transition::Compression) => {
+                if let Packet::LoginClientbound(LoginClientbound::SetCompression(packet)) = &packet
+                {
+                    self.compression = packet.threshold.value >= 0;
+                    if self.compression {
+                        info!("Compression enabled");
+                    } else {
+                        info!("Compression disabled");
                     }
-                    _ => Ok(Box::new({
-                        let mut unknown = Unknown::from_bytes(&mut bytes)?;
-                        unknown.packet_id = packet_id;
-                        unknown.server_bounded = server_bounded;
-                        unknown
-                    })),
-                },
-                State::Transfer => match packet_id {
-                    _ => Ok(Box::new({
-                        let mut unknown = Unknown::from_bytes(&mut bytes)?;
-                        unknown.packet_id = packet_id;
-                        unknown.server_bounded = server_bounded;
-                        unknown
-                    })),
-                },
-                State::Configuration => match packet_id {
-                    0 => Ok(Box::new(ClientInformation::from_bytes(&mut bytes)?)),
-                    2 => Ok(Box::new(ServerboundPluginMessage::from_bytes(&mut bytes)?)),
-                    7 => Ok(Box::new(ServerboundKnownPacks::from_bytes(&mut bytes)?)),
-                    _ => Ok(Box::new({
-                        let mut unknown = Unknown::from_bytes(&mut bytes)?;
-                        unknown.packet_id = packet_id;
-                        unknown.server_bounded = server_bounded;
-                        unknown
-                    })),
-                },
+                }
             }
-        } else {
-            // Client bounded
-            match self.state {
-                State::Handshaking => bail!("There is no client bounded Handshaking packet"), // TODO: Create custom error type
-                State::Status => match packet_id {
-                    0 => Ok(Box::new(StatusResponse::from_bytes(&mut bytes)?)),
-                    1 => Ok(Box::new(PongResponse::from_bytes(&mut bytes)?)),
-                    _ => Ok(Box::new({
-                        let mut unknown = Unknown::from_bytes(&mut bytes)?;
-                        unknown.packet_id = packet_id;
-                        unknown.server_bounded = server_bounded;
-                        unknown
-                    })),
-                },
-                State::Login => match packet_id {
-                    2 => Ok(Box::new(LoginSuccess::from_bytes(&mut bytes)?)),
-                    3 => {
-                        let packet = SetCompression::from_bytes(&mut bytes)?;
-                        self.compression = packet.threshold.value >= 0;
-                        if self.compression {
-                            info!("Compression enabled");
-                        } else {
-                            info!("Compression disabled");
-                        }
-
-                        Ok(Box::new(packet))
+            None => {}
+        }
+
+        // Entity types aren't in the metadata packets, remember them from the spawns
+        if let Packet::PlayClientbound(packet) = &mut packet {
+            match packet {
+                PlayClientbound::SpawnEntity(packet) => {
+                    self.entities
+                        .insert(packet.entity_id.value, packet.entity_type);
+                }
+                PlayClientbound::RemoveEntities(packet) => {
+                    for entity_id in &packet.entity_ids {
+                        self.entities.remove(&entity_id.value);
                     }
-                    _ => Ok(Box::new({
-                        let mut unknown = Unknown::from_bytes(&mut bytes)?;
-                        unknown.packet_id = packet_id;
-                        unknown.server_bounded = server_bounded;
-                        unknown
-                    })),
-                },
-                State::Transfer => match packet_id {
-                    _ => Ok(Box::new({
-                        let mut unknown = Unknown::from_bytes(&mut bytes)?;
-                        unknown.packet_id = packet_id;
-                        unknown.server_bounded = server_bounded;
-                        unknown
-                    })),
-                },
-                State::Configuration => match packet_id {
-                    1 => Ok(Box::new(ClientboundPluginMessage::from_bytes(&mut bytes)?)),
-                    12 => Ok(Box::new(FeatureFlags::from_bytes(&mut bytes)?)),
-                    14 => Ok(Box::new(ClientboundKnownPacks::from_bytes(&mut bytes)?)),
-                    _ => Ok(Box::new({
-                        let mut unknown = Unknown::from_bytes(&mut bytes)?;
-                        unknown.packet_id = packet_id;
-                        unknown.server_bounded = server_bounded;
-                        unknown
-                    })),
-                },
+                }
+                PlayClientbound::SetEntityMetadata(packet) => {
+                    packet.entity_type = self.entities.get(&packet.entity_id.value).copied();
+                }
+                _ => {}
+            }
+        }
+        Ok(packet)
+    }
+
+    /// Error at `offset` of a packet sent in the current state
+    pub fn error(
+        &self,
+        server_bounded: bool,
+        packet_id: Option<i32>,
+        offset: usize,
+        kind: DecodeErrorKind,
+    ) -> DecodeError {
+        DecodeError {
+            client: self.client_ip,
+            server: self.server_ip,
+            state: self.state,
+            server_bounded,
+            packet_id,
+            offset,
+            kind,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    #[test]
+    fn decompressed_length() {
+        let mut listener = MinecraftListener::new([10, 0, 0, 1].into(), [10, 0, 0, 2].into());
+        listener.compression = true;
+        let mut encoder = ZlibEncoder::new(vec![0x05], Compression::default());
+        encoder.write_all(&[0x00, 0x01, 0x02]).unwrap();
+        let packet = encoder.finish().unwrap();
+
+        let error = listener.decompress(&packet.into(), false).unwrap_err();
+        assert!(matches!(
+            error.kind,
+            DecodeErrorKind::DecompressedLength {
+                expected: 5,
+                actual: 3
             }
+        ));
+        assert_eq!(error.offset, 1);
+        assert_eq!(error.packet_id, None);
+    }
+
+    #[test]
+    fn data_length() {
+        let mut listener = MinecraftListener::new([10, 0, 0, 1].into(), [10, 0, 0, 2].into());
+        listener.compression = true;
+        for length in [-1, MAX_PACKET_LENGTH as i32 + 1] {
+            let packet = VarInt { value: length }.to_bytes();
+            let mut encoder = ZlibEncoder::new(packet, Compression::default());
+            encoder.write_all(&[0x00]).unwrap();
+            let packet = encoder.finish().unwrap();
+
+            let error = listener.decompress(&packet.into(), false).unwrap_err();
+            assert!(matches!(error.kind, DecodeErrorKind::DataLength(x) if x == length));
         }
     }
+
+    #[test]
+    fn unknown() {
+        let mut listener = MinecraftListener::new([10, 0, 0, 1].into(), [10, 0, 0, 2].into());
+        let frame = Bytes::from_static(&[0x05, 0x01, 0x02]);
+        let Packet::Unknown(packet) = listener.parse_frame(&frame, true).unwrap() else {
+            panic!("0x05 isn't a handshaking packet");
+        };
+        assert_eq!(packet.packet_id, 5);
+        assert_eq!(&packet.payload[..], [0x01, 0x02]);
+        // Not copied
+        assert_eq!(packet.payload.as_ptr(), frame[1..].as_ptr());
+    }
 }
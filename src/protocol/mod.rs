@@ -1,5 +1,15 @@
 pub mod types;
+pub mod error;
 pub mod packets;
+pub mod layout;
 pub mod listener;
+pub mod registry;
+pub mod wireshark;
 
-pub use listener::MinecraftListener;
\ No newline at end of file
+pub use error::{DecodeError, DecodeErrorKind};
+pub use listener::MinecraftListener;
+pub use registry::Packet;
+
+/// Protocol version the packet and registry IDs follow
+pub const PROTOCOL_VERSION: i32 = 769;
+pub const MINECRAFT_VERSION: &str = "1.21.4";
\ No newline at end of file
@@ -1,24 +1,24 @@
+use crate::protocol::types::error::DatatypeError;
 use crate::protocol::packets::format::direction_str;
 use crate::protocol::packets::MinecraftPacket;
 use crate::protocol::types::*;
-use std::collections::VecDeque;
+use serde::Serialize;
 use std::fmt::{Debug, Display, Formatter};
 use itertools::Itertools;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ServerboundPluginMessage {
     channel: Identifier,
     data: Vec<u8>,
 }
 
 impl MinecraftPacket for ServerboundPluginMessage {
-    fn from_bytes(bytes: &mut VecDeque<u8>) -> color_eyre::Result<Self>
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
     where
         Self: Sized,
     {
         let channel = Identifier::from_bytes(bytes)?;
-        let length = bytes.len();
-        let data = Vec::from_array_bytes(length, bytes)?;
+        let data = std::mem::take(bytes).to_vec();
 
         Ok(Self { channel, data })
     }
@@ -36,7 +36,7 @@ impl Display for ServerboundPluginMessage {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ClientInformation {
     locale: String,
     view_distance: i8,
@@ -50,7 +50,7 @@ pub struct ClientInformation {
 }
 
 impl MinecraftPacket for ClientInformation {
-    fn from_bytes(bytes: &mut VecDeque<u8>) -> color_eyre::Result<Self>
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
     where
         Self: Sized,
     {
@@ -158,14 +158,14 @@ impl Display for ClientInformation {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ClientboundPluginMessage {
     channel: Identifier,
     data: Vec<u8>,
 }
 
 impl MinecraftPacket for ClientboundPluginMessage {
-    fn from_bytes(bytes: &mut VecDeque<u8>) -> color_eyre::Result<Self>
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
     where
         Self: Sized,
     {
@@ -189,7 +189,7 @@ impl Display for ClientboundPluginMessage {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct FeatureFlags {
     feature_flags: Vec<Identifier>,
 }
@@ -202,7 +202,7 @@ impl Display for FeatureFlags {
 }
 
 impl MinecraftPacket for FeatureFlags {
-    fn from_bytes(bytes: &mut VecDeque<u8>) -> color_eyre::Result<Self>
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
     where
         Self: Sized
     {
@@ -213,13 +213,13 @@ impl MinecraftPacket for FeatureFlags {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ClientboundKnownPacks {
     known_packs: Vec<KnownPack>
 }
 
 impl MinecraftPacket for ClientboundKnownPacks {
-    fn from_bytes(bytes: &mut VecDeque<u8>) -> color_eyre::Result<Self>
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
     where
         Self: Sized
     {
@@ -237,13 +237,13 @@ impl Display for ClientboundKnownPacks {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ServerboundKnownPacks {
     known_packs: Vec<KnownPack>
 }
 
 impl MinecraftPacket for ServerboundKnownPacks {
-    fn from_bytes(bytes: &mut VecDeque<u8>) -> color_eyre::Result<Self>
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
     where
         Self: Sized
     {
@@ -259,3 +259,91 @@ impl Display for ServerboundKnownPacks {
         write!(f, "{} | ServerboundKnownPacks | Known packs: {:?}", direction_str(true), self.known_packs)
     }
 }
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FinishConfiguration;
+
+impl MinecraftPacket for FinishConfiguration {
+    fn from_bytes(_bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized
+    {
+        Ok(Self)
+    }
+}
+
+impl Display for FinishConfiguration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} | FinishConfiguration", direction_str(false))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AcknowledgeFinishConfiguration;
+
+impl MinecraftPacket for AcknowledgeFinishConfiguration {
+    fn from_bytes(_bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized
+    {
+        Ok(Self)
+    }
+}
+
+impl Display for AcknowledgeFinishConfiguration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} | AcknowledgeFinishConfiguration", direction_str(true))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RegistryEntry {
+    pub entry_id: Identifier,
+    /// Left out when the client already has the entry from a known pack
+    pub data: Option<Nbt>,
+}
+
+impl Datatype<'_> for RegistryEntry {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized
+    {
+        let entry_id = Identifier::from_bytes(bytes)?;
+        let data = Option::from_bytes(bytes)?;
+        Ok(Self { entry_id, data })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RegistryData {
+    pub registry_id: Identifier,
+    /// Network IDs are the position in this list
+    pub entries: Vec<RegistryEntry>,
+}
+
+impl MinecraftPacket for RegistryData {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized
+    {
+        let registry_id = Identifier::from_bytes(bytes)?;
+        let entries = Vec::from_bytes(bytes)?;
+        Ok(Self {
+            registry_id,
+            entries,
+        })
+    }
+}
+
+impl Display for RegistryData {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let entries = self.entries.iter().map(|x| x.entry_id.to_string()).join(", ");
+        write!(
+            f,
+            "{} | RegistryData | Registry: {}, Entries: [{}]",
+            direction_str(false),
+            self.registry_id,
+            entries
+        )
+    }
+}
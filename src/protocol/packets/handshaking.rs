@@ -1,13 +1,14 @@
+use crate::protocol::types::error::DatatypeError;
 use super::MinecraftPacket;
 use crate::parser::PacketParser;
 use crate::protocol::types::{Datatype, State, VarInt};
 use num_traits::FromPrimitive;
 use owo_colors::OwoColorize;
-use std::collections::VecDeque;
+use serde::Serialize;
 use std::fmt::{Display, Formatter};
 use crate::protocol::packets::format::direction_str;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Handshake {
     pub protocol_version: VarInt,
     pub server_address: String,
@@ -16,7 +17,7 @@ pub struct Handshake {
 }
 
 impl MinecraftPacket for Handshake {
-    fn from_bytes(bytes: &mut VecDeque<u8>) -> color_eyre::Result<Self>
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
     where
         Self: Sized,
     {
@@ -0,0 +1,102 @@
+use crate::protocol::packets::format::direction_str;
+use crate::protocol::packets::MinecraftPacket;
+use crate::protocol::types::error::DatatypeError;
+use crate::protocol::types::*;
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+
+// Sent the same way in configuration and play, so the registry lists them under both
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClientboundKeepAlive {
+    pub keep_alive_id: i64,
+}
+
+impl MinecraftPacket for ClientboundKeepAlive {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let keep_alive_id = i64::from_bytes(bytes)?;
+        Ok(Self { keep_alive_id })
+    }
+}
+
+impl Display for ClientboundKeepAlive {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} | ClientboundKeepAlive | ID: {}",
+            direction_str(false),
+            self.keep_alive_id
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerboundKeepAlive {
+    pub keep_alive_id: i64,
+}
+
+impl MinecraftPacket for ServerboundKeepAlive {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let keep_alive_id = i64::from_bytes(bytes)?;
+        Ok(Self { keep_alive_id })
+    }
+}
+
+impl Display for ServerboundKeepAlive {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} | ServerboundKeepAlive | ID: {}",
+            direction_str(true),
+            self.keep_alive_id
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Ping {
+    pub id: i32,
+}
+
+impl MinecraftPacket for Ping {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let id = i32::from_bytes(bytes)?;
+        Ok(Self { id })
+    }
+}
+
+impl Display for Ping {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} | Ping | ID: {}", direction_str(false), self.id)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Pong {
+    pub id: i32,
+}
+
+impl MinecraftPacket for Pong {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let id = i32::from_bytes(bytes)?;
+        Ok(Self { id })
+    }
+}
+
+impl Display for Pong {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} | Pong | ID: {}", direction_str(true), self.id)
+    }
+}
@@ -1,18 +1,19 @@
+use crate::protocol::types::error::DatatypeError;
 use crate::protocol::packets::format::direction_str;
 use crate::protocol::packets::MinecraftPacket;
 use crate::protocol::types::{Datatype, PlayerProperty, VarInt};
-use std::collections::VecDeque;
+use serde::Serialize;
 use std::fmt::{Display, Formatter};
 use uuid::Uuid;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct LoginStart {
     pub name: String,
     pub uuid: Uuid,
 }
 
 impl MinecraftPacket for LoginStart {
-    fn from_bytes(bytes: &mut VecDeque<u8>) -> color_eyre::Result<Self>
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
     where
         Self: Sized,
     {
@@ -35,13 +36,13 @@ impl Display for LoginStart {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct SetCompression {
     pub threshold: VarInt,
 }
 
 impl MinecraftPacket for SetCompression {
-    fn from_bytes(bytes: &mut VecDeque<u8>) -> color_eyre::Result<Self>
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
     where
         Self: Sized,
     {
@@ -61,15 +62,15 @@ impl Display for SetCompression {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct LoginSuccess {
-    uuid: Uuid,
-    username: String,
-    property: Vec<PlayerProperty>,
+    pub uuid: Uuid,
+    pub username: String,
+    pub property: Vec<PlayerProperty>,
 }
 
 impl MinecraftPacket for LoginSuccess {
-    fn from_bytes(bytes: &mut VecDeque<u8>) -> color_eyre::Result<Self>
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
     where
         Self: Sized,
     {
@@ -101,11 +102,11 @@ impl Display for LoginSuccess {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct LoginAcknowledged;
 
 impl MinecraftPacket for LoginAcknowledged {
-    fn from_bytes(bytes: &mut VecDeque<u8>) -> color_eyre::Result<Self>
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
     where
         Self: Sized,
     {
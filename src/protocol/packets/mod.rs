@@ -4,18 +4,29 @@ mod format;
 mod login;
 mod unknown;
 mod configuration;
+mod keep_alive;
+mod play;
 
-use std::any::Any;
-use std::collections::VecDeque;
+use crate::protocol::types::error::DatatypeError;
 use std::fmt::{Debug, Display};
 pub use handshaking::*;
 pub use status::*;
 pub use login::*;
 pub use configuration::*;
+pub use keep_alive::*;
 pub use unknown::*;
+pub use play::*;
 
-pub trait MinecraftPacket: Debug + Display + Any {
-    fn from_bytes(bytes: &mut VecDeque<u8>) -> color_eyre::Result<Self>
+pub trait MinecraftPacket: Debug + Display + erased_serde::Serialize {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
     where
         Self: Sized;
-}
\ No newline at end of file
+
+    /// Name of the packet type, e.g. `LoginSuccess`
+    fn name(&self) -> &'static str {
+        let path = std::any::type_name::<Self>();
+        path.rsplit("::").next().unwrap_or(path)
+    }
+}
+
+erased_serde::serialize_trait_object!(MinecraftPacket);
\ No newline at end of file
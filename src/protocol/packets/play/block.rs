@@ -0,0 +1,133 @@
+use crate::protocol::types::error::DatatypeError;
+use crate::protocol::packets::format::direction_str;
+use crate::protocol::packets::MinecraftPacket;
+use crate::protocol::types::*;
+use itertools::Itertools;
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BlockUpdate {
+    pub location: Position,
+    pub block_id: VarInt,
+}
+
+impl MinecraftPacket for BlockUpdate {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let location = Position::from_bytes(bytes)?;
+        let block_id = VarInt::from_bytes(bytes)?;
+        Ok(Self { location, block_id })
+    }
+}
+
+impl Display for BlockUpdate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} | BlockUpdate | Location: {}, Block state: {}",
+            direction_str(false),
+            self.location,
+            self.block_id
+        )
+    }
+}
+
+/// Multi Block Change on the wiki
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateSectionBlocks {
+    /// Section coordinates, in units of 16 blocks
+    pub section_x: i32,
+    pub section_y: i32,
+    pub section_z: i32,
+    pub blocks: Vec<VarLong>,
+}
+
+impl UpdateSectionBlocks {
+    /// Absolute block positions and their new block state
+    pub fn changes(&self) -> impl Iterator<Item = (Position, i32)> + '_ {
+        self.blocks.iter().map(|entry| {
+            let entry = entry.value;
+            let position = Position {
+                x: self.section_x * 16 + ((entry >> 8) & 0xF) as i32,
+                y: self.section_y * 16 + (entry & 0xF) as i32,
+                z: self.section_z * 16 + ((entry >> 4) & 0xF) as i32,
+            };
+            (position, (entry >> 12) as i32)
+        })
+    }
+}
+
+impl MinecraftPacket for UpdateSectionBlocks {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        // x (22 bits), z (22 bits), y (20 bits)
+        let section = i64::from_bytes(bytes)?;
+        let section_x = (section >> 42) as i32;
+        let section_y = (section << 44 >> 44) as i32;
+        let section_z = (section << 22 >> 42) as i32;
+        let blocks = Vec::from_bytes(bytes)?;
+
+        Ok(Self {
+            section_x,
+            section_y,
+            section_z,
+            blocks,
+        })
+    }
+}
+
+impl Display for UpdateSectionBlocks {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let changes = self
+            .changes()
+            .map(|(position, state)| format!("{position}: {state}"))
+            .join(", ");
+        write!(
+            f,
+            "{} | UpdateSectionBlocks | Section: ({}, {}, {}), Blocks: [{}]",
+            direction_str(false),
+            self.section_x,
+            self.section_y,
+            self.section_z,
+            changes
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn update_section_blocks() {
+        // Section (-1, 4, 2)
+        let section: i64 = (-1i64 << 42) | (2 << 20) | 4;
+        let mut data = section.to_be_bytes().to_vec();
+        // State 9 at local (1, 2, 3)
+        data.extend([0x01, 0xB2, 0xA2, 0x02]);
+        let mut bytes = &data[..];
+
+        let packet = UpdateSectionBlocks::from_bytes(&mut bytes).unwrap();
+        assert!(bytes.is_empty());
+        assert_eq!(
+            (packet.section_x, packet.section_y, packet.section_z),
+            (-1, 4, 2)
+        );
+        assert_eq!(
+            packet.changes().collect_vec(),
+            [(
+                Position {
+                    x: -15,
+                    y: 66,
+                    z: 35
+                },
+                9
+            )]
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use crate::protocol::types::error::DatatypeError;
+use crate::protocol::packets::format::direction_str;
+use crate::protocol::packets::MinecraftPacket;
+use crate::protocol::types::*;
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChunkDataAndUpdateLight {
+    pub chunk_x: i32,
+    pub chunk_z: i32,
+    pub data: ChunkData,
+    pub light: LightData,
+}
+
+impl MinecraftPacket for ChunkDataAndUpdateLight {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let chunk_x = i32::from_bytes(bytes)?;
+        let chunk_z = i32::from_bytes(bytes)?;
+        let data = ChunkData::from_bytes(bytes)?;
+        let light = LightData::from_bytes(bytes)?;
+
+        Ok(Self {
+            chunk_x,
+            chunk_z,
+            data,
+            light,
+        })
+    }
+}
+
+impl Display for ChunkDataAndUpdateLight {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} | ChunkDataAndUpdateLight | Chunk: ({}, {}), Sections: {}, Non-air blocks: {}, \
+            Block entities: {}, Sky light sections: {}, Block light sections: {}",
+            direction_str(false),
+            self.chunk_x,
+            self.chunk_z,
+            self.data.sections.len(),
+            self.data.non_air_blocks(),
+            self.data.block_entities.len(),
+            self.light.sky_light_arrays.len(),
+            self.light.block_light_arrays.len()
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateLight {
+    pub chunk_x: VarInt,
+    pub chunk_z: VarInt,
+    pub light: LightData,
+}
+
+impl MinecraftPacket for UpdateLight {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let chunk_x = VarInt::from_bytes(bytes)?;
+        let chunk_z = VarInt::from_bytes(bytes)?;
+        let light = LightData::from_bytes(bytes)?;
+
+        Ok(Self {
+            chunk_x,
+            chunk_z,
+            light,
+        })
+    }
+}
+
+impl Display for UpdateLight {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} | UpdateLight | Chunk: ({}, {}), Sky light sections: {}, Block light sections: {}",
+            direction_str(false),
+            self.chunk_x,
+            self.chunk_z,
+            self.light.sky_light_arrays.len(),
+            self.light.block_light_arrays.len()
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UnloadChunk {
+    pub chunk_x: i32,
+    pub chunk_z: i32,
+}
+
+impl MinecraftPacket for UnloadChunk {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        // Z comes first on the wire
+        let chunk_z = i32::from_bytes(bytes)?;
+        let chunk_x = i32::from_bytes(bytes)?;
+        Ok(Self { chunk_x, chunk_z })
+    }
+}
+
+impl Display for UnloadChunk {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} | UnloadChunk | Chunk: ({}, {})",
+            direction_str(false),
+            self.chunk_x,
+            self.chunk_z
+        )
+    }
+}
@@ -0,0 +1,192 @@
+use crate::protocol::types::error::DatatypeError;
+use crate::protocol::packets::format::direction_str;
+use crate::protocol::packets::MinecraftPacket;
+use crate::protocol::types::*;
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StartConfiguration;
+
+impl MinecraftPacket for StartConfiguration {
+    fn from_bytes(_bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        Ok(Self)
+    }
+}
+
+impl Display for StartConfiguration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} | StartConfiguration", direction_str(false))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AcknowledgeConfiguration;
+
+impl MinecraftPacket for AcknowledgeConfiguration {
+    fn from_bytes(_bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        Ok(Self)
+    }
+}
+
+impl Display for AcknowledgeConfiguration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} | AcknowledgeConfiguration", direction_str(true))
+    }
+}
+
+/// Dimension and game mode info shared by Login (play) and Respawn
+#[derive(Debug, Clone, Serialize)]
+pub struct SpawnInfo {
+    pub dimension_type: VarInt,
+    pub dimension_name: Identifier,
+    pub hashed_seed: i64,
+    pub game_mode: u8,
+    pub previous_game_mode: i8,
+    pub is_debug: bool,
+    pub is_flat: bool,
+    pub death_location: Option<GlobalPosition>,
+    pub portal_cooldown: VarInt,
+    pub sea_level: VarInt,
+}
+
+impl Datatype<'_> for SpawnInfo {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let dimension_type = VarInt::from_bytes(bytes)?;
+        let dimension_name = Identifier::from_bytes(bytes)?;
+        let hashed_seed = i64::from_bytes(bytes)?;
+        let game_mode = u8::from_bytes(bytes)?;
+        let previous_game_mode = i8::from_bytes(bytes)?;
+        let is_debug = bool::from_bytes(bytes)?;
+        let is_flat = bool::from_bytes(bytes)?;
+        let death_location = Option::from_bytes(bytes)?;
+        let portal_cooldown = VarInt::from_bytes(bytes)?;
+        let sea_level = VarInt::from_bytes(bytes)?;
+
+        Ok(Self {
+            dimension_type,
+            dimension_name,
+            hashed_seed,
+            game_mode,
+            previous_game_mode,
+            is_debug,
+            is_flat,
+            death_location,
+            portal_cooldown,
+            sea_level,
+        })
+    }
+}
+
+impl Display for SpawnInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Dimension: {}, Dimension type: {}, Game mode: {}, Debug: {}, Flat: {}",
+            self.dimension_name, self.dimension_type, self.game_mode, self.is_debug, self.is_flat
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LoginPlay {
+    pub entity_id: i32,
+    pub is_hardcore: bool,
+    pub dimension_names: Vec<Identifier>,
+    pub max_players: VarInt,
+    pub view_distance: VarInt,
+    pub simulation_distance: VarInt,
+    pub reduced_debug_info: bool,
+    pub enable_respawn_screen: bool,
+    pub do_limited_crafting: bool,
+    pub spawn_info: SpawnInfo,
+    pub enforces_secure_chat: bool,
+}
+
+impl MinecraftPacket for LoginPlay {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let entity_id = i32::from_bytes(bytes)?;
+        let is_hardcore = bool::from_bytes(bytes)?;
+        let dimension_names = Vec::from_bytes(bytes)?;
+        let max_players = VarInt::from_bytes(bytes)?;
+        let view_distance = VarInt::from_bytes(bytes)?;
+        let simulation_distance = VarInt::from_bytes(bytes)?;
+        let reduced_debug_info = bool::from_bytes(bytes)?;
+        let enable_respawn_screen = bool::from_bytes(bytes)?;
+        let do_limited_crafting = bool::from_bytes(bytes)?;
+        let spawn_info = SpawnInfo::from_bytes(bytes)?;
+        let enforces_secure_chat = bool::from_bytes(bytes)?;
+
+        Ok(Self {
+            entity_id,
+            is_hardcore,
+            dimension_names,
+            max_players,
+            view_distance,
+            simulation_distance,
+            reduced_debug_info,
+            enable_respawn_screen,
+            do_limited_crafting,
+            spawn_info,
+            enforces_secure_chat,
+        })
+    }
+}
+
+impl Display for LoginPlay {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} | LoginPlay | Entity ID: {}, Hardcore: {}, View distance: {}, {}",
+            direction_str(false),
+            self.entity_id,
+            self.is_hardcore,
+            self.view_distance,
+            self.spawn_info
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Respawn {
+    pub spawn_info: SpawnInfo,
+    pub data_kept: u8,
+}
+
+impl MinecraftPacket for Respawn {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let spawn_info = SpawnInfo::from_bytes(bytes)?;
+        let data_kept = u8::from_bytes(bytes)?;
+        Ok(Self {
+            spawn_info,
+            data_kept,
+        })
+    }
+}
+
+impl Display for Respawn {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} | Respawn | {}, Data kept: {:#04x}",
+            direction_str(false),
+            self.spawn_info,
+            self.data_kept
+        )
+    }
+}
@@ -0,0 +1,141 @@
+use crate::protocol::types::error::DatatypeError;
+use crate::protocol::packets::format::direction_str;
+use crate::protocol::packets::MinecraftPacket;
+use crate::protocol::types::*;
+use itertools::Itertools;
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SpawnEntity {
+    pub entity_id: VarInt,
+    pub entity_uuid: Uuid,
+    pub entity_type: EntityType,
+    pub position: Vec3,
+    pub pitch: Angle,
+    pub yaw: Angle,
+    pub head_yaw: Angle,
+    pub data: VarInt,
+    pub velocity_x: i16,
+    pub velocity_y: i16,
+    pub velocity_z: i16,
+}
+
+impl MinecraftPacket for SpawnEntity {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let entity_id = VarInt::from_bytes(bytes)?;
+        let entity_uuid = <Uuid as Datatype>::from_bytes(bytes)?;
+        let entity_type = EntityType::from_bytes(bytes)?;
+        let position = Vec3::from_bytes(bytes)?;
+        let pitch = Angle::from_bytes(bytes)?;
+        let yaw = Angle::from_bytes(bytes)?;
+        let head_yaw = Angle::from_bytes(bytes)?;
+        let data = VarInt::from_bytes(bytes)?;
+        let velocity_x = i16::from_bytes(bytes)?;
+        let velocity_y = i16::from_bytes(bytes)?;
+        let velocity_z = i16::from_bytes(bytes)?;
+
+        Ok(Self {
+            entity_id,
+            entity_uuid,
+            entity_type,
+            position,
+            pitch,
+            yaw,
+            head_yaw,
+            data,
+            velocity_x,
+            velocity_y,
+            velocity_z,
+        })
+    }
+}
+
+impl Display for SpawnEntity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} | SpawnEntity | Entity ID: {}, UUID: {}, Type: {}, Position: {}, Pitch: {}, Yaw: {}, \
+            Head yaw: {}, Data: {}",
+            direction_str(false),
+            self.entity_id,
+            self.entity_uuid,
+            self.entity_type,
+            self.position,
+            self.pitch,
+            self.yaw,
+            self.head_yaw,
+            self.data
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RemoveEntities {
+    pub entity_ids: Vec<VarInt>,
+}
+
+impl MinecraftPacket for RemoveEntities {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let entity_ids = Vec::from_bytes(bytes)?;
+        Ok(Self { entity_ids })
+    }
+}
+
+impl Display for RemoveEntities {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} | RemoveEntities | Entity IDs: [{}]",
+            direction_str(false),
+            self.entity_ids.iter().join(", ")
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SetEntityMetadata {
+    pub entity_id: VarInt,
+    pub metadata: EntityMetadata,
+    /// Filled in by the listener from an earlier SpawnEntity, if it saw one
+    pub entity_type: Option<EntityType>,
+}
+
+impl MinecraftPacket for SetEntityMetadata {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let entity_id = VarInt::from_bytes(bytes)?;
+        let metadata = EntityMetadata::from_bytes(bytes)?;
+        Ok(Self {
+            entity_id,
+            metadata,
+            entity_type: None,
+        })
+    }
+}
+
+impl Display for SetEntityMetadata {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let entity_type = match self.entity_type {
+            Some(entity_type) => entity_type.to_string(),
+            None => "Unknown".to_string(),
+        };
+        write!(
+            f,
+            "{} | SetEntityMetadata | Entity ID: {}, Type: {}, Metadata: [{}]",
+            direction_str(false),
+            self.entity_id,
+            entity_type,
+            self.metadata.describe(self.entity_type)
+        )
+    }
+}
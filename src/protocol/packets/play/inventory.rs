@@ -0,0 +1,342 @@
+use crate::protocol::types::error::DatatypeError;
+use crate::protocol::packets::format::direction_str;
+use crate::protocol::packets::MinecraftPacket;
+use crate::protocol::types::*;
+use itertools::Itertools;
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SetContainerContent {
+    pub window_id: VarInt,
+    pub state_id: VarInt,
+    pub slot_data: Vec<Slot>,
+    pub carried_item: Slot,
+}
+
+impl MinecraftPacket for SetContainerContent {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let window_id = VarInt::from_bytes(bytes)?;
+        let state_id = VarInt::from_bytes(bytes)?;
+        let slot_data = Vec::from_bytes(bytes)?;
+        let carried_item = Slot::from_bytes(bytes)?;
+
+        Ok(Self {
+            window_id,
+            state_id,
+            slot_data,
+            carried_item,
+        })
+    }
+}
+
+impl Display for SetContainerContent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let slots = self
+            .slot_data
+            .iter()
+            .enumerate()
+            .filter(|(_, slot)| !slot.is_empty())
+            .map(|(i, slot)| format!("{i}: {slot}"))
+            .join(", ");
+        write!(
+            f,
+            "{} | SetContainerContent | Window ID: {}, State ID: {}, Slots: [{}], Carried item: {}",
+            direction_str(false),
+            self.window_id,
+            self.state_id,
+            slots,
+            self.carried_item
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SetContainerSlot {
+    pub window_id: VarInt,
+    pub state_id: VarInt,
+    pub slot: i16,
+    pub slot_data: Slot,
+}
+
+impl MinecraftPacket for SetContainerSlot {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let window_id = VarInt::from_bytes(bytes)?;
+        let state_id = VarInt::from_bytes(bytes)?;
+        let slot = i16::from_bytes(bytes)?;
+        let slot_data = Slot::from_bytes(bytes)?;
+
+        Ok(Self {
+            window_id,
+            state_id,
+            slot,
+            slot_data,
+        })
+    }
+}
+
+impl Display for SetContainerSlot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} | SetContainerSlot | Window ID: {}, State ID: {}, Slot: {}, Slot data: {}",
+            direction_str(false),
+            self.window_id,
+            self.state_id,
+            self.slot,
+            self.slot_data
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SetCursorItem {
+    pub carried_item: Slot,
+}
+
+impl MinecraftPacket for SetCursorItem {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let carried_item = Slot::from_bytes(bytes)?;
+        Ok(Self { carried_item })
+    }
+}
+
+impl Display for SetCursorItem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} | SetCursorItem | Carried item: {}",
+            direction_str(false),
+            self.carried_item
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SetPlayerInventorySlot {
+    pub slot: VarInt,
+    pub slot_data: Slot,
+}
+
+impl MinecraftPacket for SetPlayerInventorySlot {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let slot = VarInt::from_bytes(bytes)?;
+        let slot_data = Slot::from_bytes(bytes)?;
+        Ok(Self { slot, slot_data })
+    }
+}
+
+impl Display for SetPlayerInventorySlot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} | SetPlayerInventorySlot | Slot: {}, Slot data: {}",
+            direction_str(false),
+            self.slot,
+            self.slot_data
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EquipmentEntry {
+    pub slot: u8,
+    pub item: Slot,
+}
+
+impl EquipmentEntry {
+    pub fn slot_name(&self) -> &'static str {
+        match self.slot {
+            0 => "Main hand",
+            1 => "Off hand",
+            2 => "Boots",
+            3 => "Leggings",
+            4 => "Chestplate",
+            5 => "Helmet",
+            6 => "Body",
+            _ => "Unknown",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SetEquipment {
+    pub entity_id: VarInt,
+    pub equipment: Vec<EquipmentEntry>,
+}
+
+impl SetEquipment {
+    /// The top bit of each slot byte says whether another entry follows
+    const HAS_NEXT: u8 = 0x80;
+}
+
+impl MinecraftPacket for SetEquipment {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let entity_id = VarInt::from_bytes(bytes)?;
+        let mut equipment = vec![];
+        loop {
+            let slot = u8::from_bytes(bytes)?;
+            let item = Slot::from_bytes(bytes)?;
+            equipment.push(EquipmentEntry {
+                slot: slot & !Self::HAS_NEXT,
+                item,
+            });
+            if slot & Self::HAS_NEXT == 0 {
+                break;
+            }
+        }
+
+        Ok(Self {
+            entity_id,
+            equipment,
+        })
+    }
+}
+
+impl Display for SetEquipment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let equipment = self
+            .equipment
+            .iter()
+            .map(|x| format!("{}: {}", x.slot_name(), x.item))
+            .join(", ");
+        write!(
+            f,
+            "{} | SetEquipment | Entity ID: {}, Equipment: [{}]",
+            direction_str(false),
+            self.entity_id,
+            equipment
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangedSlot {
+    pub slot: i16,
+    pub item: Slot,
+}
+
+impl Datatype<'_> for ChangedSlot {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let slot = i16::from_bytes(bytes)?;
+        let item = Slot::from_bytes(bytes)?;
+        Ok(Self { slot, item })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClickContainer {
+    pub window_id: VarInt,
+    pub state_id: VarInt,
+    pub slot: i16,
+    pub button: i8,
+    pub mode: VarInt,
+    pub changed_slots: Vec<ChangedSlot>,
+    pub carried_item: Slot,
+}
+
+impl MinecraftPacket for ClickContainer {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let window_id = VarInt::from_bytes(bytes)?;
+        let state_id = VarInt::from_bytes(bytes)?;
+        let slot = i16::from_bytes(bytes)?;
+        let button = i8::from_bytes(bytes)?;
+        let mode = VarInt::from_bytes(bytes)?;
+        let changed_slots = Vec::from_bytes(bytes)?;
+        let carried_item = Slot::from_bytes(bytes)?;
+
+        Ok(Self {
+            window_id,
+            state_id,
+            slot,
+            button,
+            mode,
+            changed_slots,
+            carried_item,
+        })
+    }
+}
+
+impl Display for ClickContainer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mode = match self.mode.value {
+            0 => "Click",
+            1 => "Shift click",
+            2 => "Number key",
+            3 => "Middle click",
+            4 => "Drop",
+            5 => "Drag",
+            6 => "Double click",
+            _ => "Unknown",
+        };
+        let changed_slots = self
+            .changed_slots
+            .iter()
+            .map(|x| format!("{}: {}", x.slot, x.item))
+            .join(", ");
+
+        write!(
+            f,
+            "{} | ClickContainer | Window ID: {}, State ID: {}, Slot: {}, Button: {}, Mode: {}, \
+            Changed slots: [{}], Carried item: {}",
+            direction_str(true),
+            self.window_id,
+            self.state_id,
+            self.slot,
+            self.button,
+            mode,
+            changed_slots,
+            self.carried_item
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SetCreativeModeSlot {
+    pub slot: i16,
+    pub clicked_item: Slot,
+}
+
+impl MinecraftPacket for SetCreativeModeSlot {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let slot = i16::from_bytes(bytes)?;
+        let clicked_item = Slot::from_bytes(bytes)?;
+        Ok(Self { slot, clicked_item })
+    }
+}
+
+impl Display for SetCreativeModeSlot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} | SetCreativeModeSlot | Slot: {}, Clicked item: {}",
+            direction_str(true),
+            self.slot,
+            self.clicked_item
+        )
+    }
+}
@@ -0,0 +1,11 @@
+mod block;
+mod chunk;
+mod connection;
+mod entity;
+mod inventory;
+
+pub use block::*;
+pub use chunk::*;
+pub use connection::*;
+pub use entity::*;
+pub use inventory::*;
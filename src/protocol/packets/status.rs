@@ -1,15 +1,16 @@
-use std::collections::VecDeque;
+use crate::protocol::types::error::DatatypeError;
 use std::fmt::{Display, Formatter};
 use owo_colors::OwoColorize;
+use serde::Serialize;
 use crate::protocol::packets::format::direction_str;
 use crate::protocol::packets::MinecraftPacket;
 use crate::protocol::types::Datatype;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct StatusRequest;
 
 impl MinecraftPacket for StatusRequest {
-    fn from_bytes(bytes: &mut VecDeque<u8>) -> color_eyre::Result<Self>
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
     where
         Self: Sized
     {
@@ -23,13 +24,13 @@ impl Display for StatusRequest {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct StatusResponse {
     pub json_response: String
 }
 
 impl MinecraftPacket for StatusResponse {
-    fn from_bytes(bytes: &mut VecDeque<u8>) -> color_eyre::Result<Self>
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
     where
         Self: Sized
     {
@@ -47,13 +48,13 @@ impl Display for StatusResponse {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct PingRequest {
     pub timestamp: i64
 }
 
 impl MinecraftPacket for PingRequest {
-    fn from_bytes(bytes: &mut VecDeque<u8>) -> color_eyre::Result<Self>
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
     where
         Self: Sized
     {
@@ -70,13 +71,13 @@ impl Display for PingRequest {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct PongResponse {
     pub timestamp: i64
 }
 
 impl MinecraftPacket for PongResponse {
-    fn from_bytes(bytes: &mut VecDeque<u8>) -> color_eyre::Result<Self>
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
     where
         Self: Sized
     {
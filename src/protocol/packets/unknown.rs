@@ -1,25 +1,33 @@
 use crate::protocol::packets::format::direction_str;
 use crate::protocol::packets::MinecraftPacket;
-use itertools::Itertools;
-use std::collections::VecDeque;
+use crate::protocol::types::error::DatatypeError;
+use bytes::Bytes;
+use serde::{Serialize, Serializer};
 use std::fmt::{Display, Formatter};
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct Unknown {
     pub packet_id: i32,
     pub server_bounded: bool,
-    pub payload: Vec<u8>,
+    /// Shares the frame it was decoded from, see [`crate::protocol::MinecraftListener::parse_frame`]
+    #[serde(serialize_with = "serialize_payload")]
+    pub payload: Bytes,
+}
+
+/// Same as a `Vec<u8>`, a list of numbers
+fn serialize_payload<S: Serializer>(payload: &Bytes, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(payload.iter())
 }
 
 impl MinecraftPacket for Unknown {
-    fn from_bytes(bytes: &mut VecDeque<u8>) -> color_eyre::Result<Self>
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
     where
         Self: Sized,
     {
         Ok(Self {
             packet_id: 0,
             server_bounded: false,
-            payload: bytes.iter().copied().collect_vec(),
+            payload: Bytes::copy_from_slice(std::mem::take(bytes)),
         })
     }
 }
@@ -31,7 +39,7 @@ impl Display for Unknown {
             "{} | Unknown Packet | Packet ID: {}, Payload: {:?}",
             direction_str(self.server_bounded),
             self.packet_id,
-            self.payload
+            &self.payload[..]
         )
     }
 }
@@ -0,0 +1,538 @@
+use crate::protocol::packets::*;
+use crate::protocol::types::error::DatatypeError;
+use crate::protocol::types::State;
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+
+const SERVERBOUND: bool = true;
+const CLIENTBOUND: bool = false;
+
+/// Everything known about one packet: where it's valid, how to parse it and its wire layout
+#[derive(Debug)]
+pub struct PacketInfo {
+    pub state: State,
+    pub server_bounded: bool,
+    pub id: i32,
+    pub name: &'static str,
+    pub parse: fn(&mut &[u8]) -> Result<Packet, DatatypeError>,
+    pub fields: &'static [Field],
+    pub transition: Option<Transition>,
+}
+
+/// Connection state change caused by a packet
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Transition {
+    /// To the handshake's `next_state`
+    Handshake,
+    To(State),
+    /// Compression on if the `threshold` isn't negative, off otherwise
+    Compression,
+}
+
+#[derive(Debug)]
+pub struct Field {
+    pub name: &'static str,
+    pub kind: FieldKind,
+}
+
+/// Wire types of packet fields
+#[derive(Debug)]
+pub enum FieldKind {
+    Bool,
+    Byte,
+    UnsignedByte,
+    Short,
+    UnsignedShort,
+    Int,
+    Long,
+    Float,
+    Double,
+    VarInt,
+    VarLong,
+    String,
+    Identifier,
+    Uuid,
+    Position,
+    Angle,
+    /// Prefixed with its length as a VarInt
+    Array(&'static FieldKind),
+    /// Prefixed with a bool telling if it's there
+    Optional(&'static FieldKind),
+    Struct(&'static [Field]),
+    /// Raw bytes up to the end of the packet
+    Bytes,
+    /// Structure not described here (slots, NBT, chunk data, ...), runs to the end of the packet
+    Rest(&'static str),
+}
+
+const fn field(name: &'static str, kind: FieldKind) -> Field {
+    Field { name, kind }
+}
+
+// Entries are `<id> => <packet> <fields>`, or `<id> => <variant>: <packet> <fields>` for packets
+// sent in several states. Those have no `From<packet> for Packet`, as it couldn't tell which.
+macro_rules! packets {
+    ($(
+        $group:ident: $state:ident $direction:ident {
+            $(
+                $id:literal => $variant:ident $(: $packet:ident)? $fields:expr
+                $(=> $transition:expr)?
+            ),* $(,)?
+        }
+    )*) => {
+        $(
+            /// Packets known in one state and direction
+            // Boxing the big ones would get in the way of matching on them
+            #[allow(clippy::large_enum_variant)]
+            #[derive(Debug, Serialize)]
+            #[serde(untagged)]
+            pub enum $group {
+                $($variant(packets!(@type $variant $($packet)?)),)*
+            }
+
+            impl $group {
+                pub fn inner(&self) -> &dyn MinecraftPacket {
+                    match self {
+                        $($group::$variant(packet) => packet,)*
+                    }
+                }
+            }
+
+            $(packets!(@from $group $variant $($packet)?);)*
+        )*
+
+        /// Any decoded packet, e.g. `Packet::PlayClientbound(PlayClientbound::SpawnEntity(x))`
+        #[derive(Debug, Serialize)]
+        #[serde(untagged)]
+        pub enum Packet {
+            $($group($group),)*
+            Unknown(Unknown),
+        }
+
+        impl Packet {
+            pub fn inner(&self) -> &dyn MinecraftPacket {
+                match self {
+                    $(Packet::$group(packet) => packet.inner(),)*
+                    Packet::Unknown(packet) => packet,
+                }
+            }
+        }
+
+        pub const PACKETS: &[PacketInfo] = &[$($(
+            PacketInfo {
+                state: State::$state,
+                server_bounded: $direction,
+                id: $id,
+                name: packets!(@name $variant $($packet)?),
+                parse: |bytes| {
+                    let packet = <packets!(@type $variant $($packet)?)>::from_bytes(bytes)?;
+                    Ok(Packet::$group($group::$variant(packet)))
+                },
+                fields: $fields,
+                transition: packets!(@transition $($transition)?),
+            },
+        )*)*];
+    };
+    (@type $variant:ident) => {
+        $variant
+    };
+    (@name $variant:ident) => {
+        stringify!($variant)
+    };
+    (@name $variant:ident $packet:ident) => {
+        stringify!($packet)
+    };
+    (@type $variant:ident $packet:ident) => {
+        $packet
+    };
+    (@from $group:ident $packet:ident) => {
+        impl From<$packet> for Packet {
+            fn from(packet: $packet) -> Self {
+                Packet::$group($group::$packet(packet))
+            }
+        }
+    };
+    (@from $group:ident $variant:ident $packet:ident) => {};
+    (@transition) => {
+        None
+    };
+    (@transition $transition:expr) => {
+        Some($transition)
+    };
+}
+
+const PLUGIN_MESSAGE: &[Field] = &[
+    field("channel", FieldKind::Identifier),
+    field("data", FieldKind::Bytes),
+];
+
+const KEEP_ALIVE: &[Field] = &[field("keep_alive_id", FieldKind::Long)];
+
+const PING: &[Field] = &[field("id", FieldKind::Int)];
+
+const KNOWN_PACKS: &[Field] = &[field(
+    "known_packs",
+    FieldKind::Array(&FieldKind::Struct(&[
+        field("namespace", FieldKind::String),
+        field("id", FieldKind::String),
+        field("version", FieldKind::String),
+    ])),
+)];
+
+const SPAWN_INFO: FieldKind = FieldKind::Struct(&[
+    field("dimension_type", FieldKind::VarInt),
+    field("dimension_name", FieldKind::Identifier),
+    field("hashed_seed", FieldKind::Long),
+    field("game_mode", FieldKind::UnsignedByte),
+    field("previous_game_mode", FieldKind::Byte),
+    field("is_debug", FieldKind::Bool),
+    field("is_flat", FieldKind::Bool),
+    field(
+        "death_location",
+        FieldKind::Optional(&FieldKind::Struct(&[
+            field("dimension", FieldKind::Identifier),
+            field("location", FieldKind::Position),
+        ])),
+    ),
+    field("portal_cooldown", FieldKind::VarInt),
+    field("sea_level", FieldKind::VarInt),
+]);
+
+// Every packet the listener decodes, grouped by state and direction. Anything else is `Packet::Unknown`.
+packets! {
+    HandshakingServerbound: Handshaking SERVERBOUND {
+        0x00 => Handshake &[
+            field("protocol_version", FieldKind::VarInt),
+            field("server_address", FieldKind::String),
+            field("server_port", FieldKind::UnsignedShort),
+            field("next_state", FieldKind::VarInt),
+        ] => Transition::Handshake,
+    }
+    StatusServerbound: Status SERVERBOUND {
+        0x00 => StatusRequest &[],
+        0x01 => PingRequest &[field("timestamp", FieldKind::Long)],
+    }
+    StatusClientbound: Status CLIENTBOUND {
+        0x00 => StatusResponse &[field("json_response", FieldKind::String)],
+        0x01 => PongResponse &[field("timestamp", FieldKind::Long)],
+    }
+    LoginServerbound: Login SERVERBOUND {
+        0x00 => LoginStart &[
+            field("name", FieldKind::String),
+            field("uuid", FieldKind::Uuid),
+        ],
+        0x03 => LoginAcknowledged &[] => Transition::To(State::Configuration),
+    }
+    LoginClientbound: Login CLIENTBOUND {
+        0x02 => LoginSuccess &[
+            field("uuid", FieldKind::Uuid),
+            field("username", FieldKind::String),
+            field(
+                "property",
+                FieldKind::Array(&FieldKind::Struct(&[
+                    field("name", FieldKind::String),
+                    field("value", FieldKind::String),
+                    field("signature", FieldKind::Optional(&FieldKind::String)),
+                ])),
+            ),
+        ],
+        0x03 => SetCompression &[field("threshold", FieldKind::VarInt)] => Transition::Compression,
+    }
+    ConfigurationServerbound: Configuration SERVERBOUND {
+        0x00 => ClientInformation &[
+            field("locale", FieldKind::String),
+            field("view_distance", FieldKind::Byte),
+            field("chat_mode", FieldKind::VarInt),
+            field("chat_colors", FieldKind::Bool),
+            field("displayed_skin_parts", FieldKind::UnsignedByte),
+            field("main_hand", FieldKind::VarInt),
+            field("enable_text_filtering", FieldKind::Bool),
+            field("allow_server_listings", FieldKind::Bool),
+            field("particle_status", FieldKind::VarInt),
+        ],
+        0x02 => ServerboundPluginMessage PLUGIN_MESSAGE,
+        0x03 => AcknowledgeFinishConfiguration &[] => Transition::To(State::Play),
+        0x04 => KeepAlive: ServerboundKeepAlive KEEP_ALIVE,
+        0x05 => Pong: Pong PING,
+        0x07 => ServerboundKnownPacks KNOWN_PACKS,
+    }
+    ConfigurationClientbound: Configuration CLIENTBOUND {
+        0x01 => ClientboundPluginMessage PLUGIN_MESSAGE,
+        0x03 => FinishConfiguration &[],
+        0x04 => KeepAlive: ClientboundKeepAlive KEEP_ALIVE,
+        0x05 => Ping: Ping PING,
+        0x07 => RegistryData &[
+            field("registry_id", FieldKind::Identifier),
+            field(
+                "entries",
+                FieldKind::Array(&FieldKind::Struct(&[
+                    field("entry_id", FieldKind::Identifier),
+                    field("data", FieldKind::Rest("Optional NBT")),
+                ])),
+            ),
+        ],
+        0x0C => FeatureFlags &[field("feature_flags", FieldKind::Array(&FieldKind::Identifier))],
+        0x0E => ClientboundKnownPacks KNOWN_PACKS,
+    }
+    PlayServerbound: Play SERVERBOUND {
+        0x0E => AcknowledgeConfiguration &[] => Transition::To(State::Configuration),
+        0x10 => ClickContainer &[
+            field("window_id", FieldKind::VarInt),
+            field("state_id", FieldKind::VarInt),
+            field("slot", FieldKind::Short),
+            field("button", FieldKind::Byte),
+            field("mode", FieldKind::VarInt),
+            field("changed_slots", FieldKind::Rest("Changed slots and carried item")),
+        ],
+        0x1A => KeepAlive: ServerboundKeepAlive KEEP_ALIVE,
+        0x2B => Pong: Pong PING,
+        0x36 => SetCreativeModeSlot &[
+            field("slot", FieldKind::Short),
+            field("clicked_item", FieldKind::Rest("Slot")),
+        ],
+    }
+    PlayClientbound: Play CLIENTBOUND {
+        0x01 => SpawnEntity &[
+            field("entity_id", FieldKind::VarInt),
+            field("entity_uuid", FieldKind::Uuid),
+            field("entity_type", FieldKind::VarInt),
+            field("x", FieldKind::Double),
+            field("y", FieldKind::Double),
+            field("z", FieldKind::Double),
+            field("pitch", FieldKind::Angle),
+            field("yaw", FieldKind::Angle),
+            field("head_yaw", FieldKind::Angle),
+            field("data", FieldKind::VarInt),
+            field("velocity_x", FieldKind::Short),
+            field("velocity_y", FieldKind::Short),
+            field("velocity_z", FieldKind::Short),
+        ],
+        0x09 => BlockUpdate &[
+            field("location", FieldKind::Position),
+            field("block_id", FieldKind::VarInt),
+        ],
+        0x13 => SetContainerContent &[
+            field("window_id", FieldKind::VarInt),
+            field("state_id", FieldKind::VarInt),
+            field("slot_data", FieldKind::Rest("Slots and carried item")),
+        ],
+        0x15 => SetContainerSlot &[
+            field("window_id", FieldKind::VarInt),
+            field("state_id", FieldKind::VarInt),
+            field("slot", FieldKind::Short),
+            field("slot_data", FieldKind::Rest("Slot")),
+        ],
+        0x22 => UnloadChunk &[
+            field("chunk_z", FieldKind::Int),
+            field("chunk_x", FieldKind::Int),
+        ],
+        0x27 => KeepAlive: ClientboundKeepAlive KEEP_ALIVE,
+        0x28 => ChunkDataAndUpdateLight &[
+            field("chunk_x", FieldKind::Int),
+            field("chunk_z", FieldKind::Int),
+            field("data", FieldKind::Rest("Chunk and light data")),
+        ],
+        0x2B => UpdateLight &[
+            field("chunk_x", FieldKind::VarInt),
+            field("chunk_z", FieldKind::VarInt),
+            field("light", FieldKind::Rest("Light data")),
+        ],
+        0x2C => LoginPlay &[
+            field("entity_id", FieldKind::Int),
+            field("is_hardcore", FieldKind::Bool),
+            field("dimension_names", FieldKind::Array(&FieldKind::Identifier)),
+            field("max_players", FieldKind::VarInt),
+            field("view_distance", FieldKind::VarInt),
+            field("simulation_distance", FieldKind::VarInt),
+            field("reduced_debug_info", FieldKind::Bool),
+            field("enable_respawn_screen", FieldKind::Bool),
+            field("do_limited_crafting", FieldKind::Bool),
+            field("spawn_info", SPAWN_INFO),
+            field("enforces_secure_chat", FieldKind::Bool),
+        ],
+        0x37 => Ping: Ping PING,
+        0x47 => RemoveEntities &[field("entity_ids", FieldKind::Array(&FieldKind::VarInt))],
+        0x4C => Respawn &[
+            field("spawn_info", SPAWN_INFO),
+            field("data_kept", FieldKind::UnsignedByte),
+        ],
+        0x4E => UpdateSectionBlocks &[
+            field("section", FieldKind::Long),
+            field("blocks", FieldKind::Array(&FieldKind::VarLong)),
+        ],
+        0x5A => SetCursorItem &[field("carried_item", FieldKind::Rest("Slot"))],
+        0x5D => SetEntityMetadata &[
+            field("entity_id", FieldKind::VarInt),
+            field("metadata", FieldKind::Rest("Entity metadata")),
+        ],
+        0x60 => SetEquipment &[
+            field("entity_id", FieldKind::VarInt),
+            field("equipment", FieldKind::Rest("Equipment")),
+        ],
+        0x66 => SetPlayerInventorySlot &[
+            field("slot", FieldKind::VarInt),
+            field("slot_data", FieldKind::Rest("Slot")),
+        ],
+        0x70 => StartConfiguration &[],
+    }
+}
+
+impl Packet {
+    /// Name of the packet type, e.g. `LoginSuccess`
+    pub fn name(&self) -> &'static str {
+        self.inner().name()
+    }
+}
+
+impl Display for Packet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self.inner(), f)
+    }
+}
+
+impl From<Unknown> for Packet {
+    fn from(packet: Unknown) -> Self {
+        Packet::Unknown(packet)
+    }
+}
+
+pub fn find(state: State, server_bounded: bool, id: i32) -> Option<&'static PacketInfo> {
+    PACKETS
+        .iter()
+        .find(|x| x.state == state && x.server_bounded == server_bounded && x.id == id)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::protocol::types::{Datatype, VarInt};
+
+    fn contains_rest(kind: &FieldKind) -> bool {
+        match kind {
+            FieldKind::Rest(_) => true,
+            FieldKind::Array(kind) | FieldKind::Optional(kind) => contains_rest(kind),
+            FieldKind::Struct(fields) => fields.iter().any(|x| contains_rest(&x.kind)),
+            _ => false,
+        }
+    }
+
+    /// Smallest valid encoding of a field: empty arrays, absent optionals, zeroed numbers
+    fn sample(kind: &FieldKind, bytes: &mut Vec<u8>) {
+        match kind {
+            FieldKind::Bool | FieldKind::Byte | FieldKind::UnsignedByte | FieldKind::Angle => {
+                bytes.push(0)
+            }
+            FieldKind::Short | FieldKind::UnsignedShort => bytes.extend([0; 2]),
+            FieldKind::Int | FieldKind::Float => bytes.extend([0; 4]),
+            FieldKind::Long | FieldKind::Double | FieldKind::Position => bytes.extend([0; 8]),
+            FieldKind::VarInt | FieldKind::VarLong | FieldKind::Array(_) => bytes.push(0),
+            FieldKind::Optional(_) => bytes.push(0),
+            FieldKind::String | FieldKind::Identifier => {
+                let text = b"minecraft:sample";
+                bytes.extend(
+                    VarInt {
+                        value: text.len() as i32,
+                    }
+                    .to_bytes(),
+                );
+                bytes.extend(text);
+            }
+            FieldKind::Uuid => bytes.extend([0; 16]),
+            FieldKind::Struct(fields) => fields.iter().for_each(|x| sample(&x.kind, bytes)),
+            FieldKind::Bytes | FieldKind::Rest(_) => {}
+        }
+    }
+
+    #[test]
+    fn unique() {
+        for (i, a) in PACKETS.iter().enumerate() {
+            for b in &PACKETS[i + 1..] {
+                assert!(
+                    (a.state, a.server_bounded, a.id) != (b.state, b.server_bounded, b.id),
+                    "{} and {} share an ID",
+                    a.name,
+                    b.name
+                );
+            }
+        }
+    }
+
+    /// Decodes a frame by its ID the way the listener does
+    fn decode(state: State, server_bounded: bool, frame: &[u8]) -> Packet {
+        let mut bytes = frame;
+        let id = VarInt::from_bytes(&mut bytes).unwrap().value;
+        let info = find(state, server_bounded, id).unwrap_or_else(|| panic!("{id:#04x} unknown"));
+        let packet = (info.parse)(&mut bytes).unwrap();
+        assert!(bytes.is_empty(), "{} left {} bytes", info.name, bytes.len());
+        packet
+    }
+
+    fn string(bytes: &mut Vec<u8>, text: &str) {
+        bytes.extend(
+            VarInt {
+                value: text.len() as i32,
+            }
+            .to_bytes(),
+        );
+        bytes.extend(text.as_bytes());
+    }
+
+    /// Frames as a 1.21.4 server sends them after joining
+    #[test]
+    fn play_ids() {
+        let mut login = vec![0x2C];
+        login.extend(42i32.to_be_bytes());
+        login.push(0); // not hardcore
+        login.push(3);
+        for dimension in ["overworld", "the_nether", "the_end"] {
+            string(&mut login, &format!("minecraft:{dimension}"));
+        }
+        login.extend([20, 10, 10, 0, 1, 0]); // max players, view and simulation distance, flags
+        login.push(0); // dimension type
+        string(&mut login, "minecraft:overworld");
+        login.extend((-4172144997902289642i64).to_be_bytes());
+        login.extend([0x00, 0xFF, 0, 0, 0, 0, 63, 0]); // survival, no previous, sea level 63
+        let Packet::PlayClientbound(PlayClientbound::LoginPlay(login)) =
+            decode(State::Play, CLIENTBOUND, &login)
+        else {
+            panic!("0x2C isn't LoginPlay");
+        };
+        assert_eq!(login.entity_id, 42);
+        assert_eq!(login.dimension_names.len(), 3);
+        assert_eq!(login.spawn_info.sea_level.value, 63);
+
+        let mut chunk = vec![0x28];
+        chunk.extend(3i32.to_be_bytes());
+        chunk.extend((-5i32).to_be_bytes());
+        chunk.extend([0x0A, 0x00]); // no heightmaps
+        chunk.extend([8, 0x10, 0x00, 0x00, 0x01, 0x00, 0x00, 0x01, 0x00]); // one stone section
+        chunk.push(0); // no block entities
+        chunk.extend([0, 0, 0, 0, 0, 0]); // no light
+        let Packet::PlayClientbound(PlayClientbound::ChunkDataAndUpdateLight(chunk)) =
+            decode(State::Play, CLIENTBOUND, &chunk)
+        else {
+            panic!("0x28 isn't ChunkDataAndUpdateLight");
+        };
+        assert_eq!((chunk.chunk_x, chunk.chunk_z), (3, -5));
+        assert_eq!(chunk.data.block_state(0, 0, 0), Some(1));
+    }
+
+    /// The layouts have to match what the parsers actually read
+    #[test]
+    fn layouts() {
+        for info in PACKETS {
+            if info.fields.iter().any(|x| contains_rest(&x.kind)) {
+                continue;
+            }
+            let mut sampled = vec![];
+            info.fields
+                .iter()
+                .for_each(|x| sample(&x.kind, &mut sampled));
+            let mut bytes = &sampled[..];
+            let packet = (info.parse)(&mut bytes)
+                .unwrap_or_else(|e| panic!("{} doesn't parse: {e}", info.name));
+            assert_eq!(packet.name(), info.name);
+            assert!(bytes.is_empty(), "{} left {} bytes", info.name, bytes.len());
+        }
+    }
+}
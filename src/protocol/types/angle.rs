@@ -0,0 +1,45 @@
+use super::error::DatatypeError;
+use crate::protocol::types::Datatype;
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+
+/// Rotation angle in steps of 1/256 of a full turn
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub struct Angle {
+    pub value: u8,
+}
+
+impl Angle {
+    pub fn degrees(&self) -> f32 {
+        self.value as f32 * 360.0 / 256.0
+    }
+}
+
+impl Datatype<'_> for Angle {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let value = u8::from_bytes(bytes)?;
+        Ok(Self { value })
+    }
+}
+
+impl Display for Angle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}°", self.degrees())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn degrees() {
+        let mut bytes: &[u8] = &[64, 128, 0];
+        assert_eq!(Angle::from_bytes(&mut bytes).unwrap().degrees(), 90.0);
+        assert_eq!(Angle::from_bytes(&mut bytes).unwrap().degrees(), 180.0);
+        assert_eq!(Angle::from_bytes(&mut bytes).unwrap().degrees(), 0.0);
+    }
+}
@@ -0,0 +1,92 @@
+use super::error::DatatypeError;
+use crate::protocol::types::{Datatype, MinecraftArray, VarInt};
+use serde::Serialize;
+
+/// Length-prefixed bit set backed by Longs
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct BitSet {
+    pub data: Vec<i64>,
+}
+
+impl BitSet {
+    pub fn get(&self, index: usize) -> bool {
+        self.data
+            .get(index / 64)
+            .is_some_and(|long| long & (1 << (index % 64)) != 0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len() * 64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len()).filter(|&i| self.get(i))
+    }
+}
+
+impl Datatype<'_> for BitSet {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let len = VarInt::from_bytes(bytes)?.value as usize;
+        let data = Vec::from_array_bytes(len, bytes)?;
+        Ok(Self { data })
+    }
+}
+
+/// Bit set with a length of `BITS` known ahead of time, sent as ceil(BITS / 8) bytes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FixedBitSet<const BITS: usize> {
+    pub data: Vec<u8>,
+}
+
+impl<const BITS: usize> FixedBitSet<BITS> {
+    pub fn get(&self, index: usize) -> bool {
+        index < BITS && self.data[index / 8] & (1 << (index % 8)) != 0
+    }
+
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..BITS).filter(|&i| self.get(i))
+    }
+}
+
+impl<const BITS: usize> Datatype<'_> for FixedBitSet<BITS> {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let data = Vec::from_array_bytes(BITS.div_ceil(8), bytes)?;
+        Ok(Self { data })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bit_set() {
+        let mut bytes: &[u8] = &[
+            0x02, // 2 longs
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, // bits 0 and 2
+            0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // bit 127
+        ];
+        let bit_set = BitSet::from_bytes(&mut bytes).unwrap();
+        assert_eq!(bit_set.len(), 128);
+        assert_eq!(bit_set.ones().collect::<Vec<_>>(), vec![0, 2, 127]);
+        assert!(!bit_set.get(200));
+    }
+
+    #[test]
+    fn fixed_bit_set() {
+        let mut bytes: &[u8] = &[0b0000_0011, 0b0000_0100, 0xFF];
+        let bit_set = FixedBitSet::<12>::from_bytes(&mut bytes).unwrap();
+        assert_eq!(bit_set.ones().collect::<Vec<_>>(), vec![0, 1, 10]);
+        assert_eq!(bytes.len(), 1);
+    }
+}
@@ -0,0 +1,314 @@
+use super::error::{DatatypeError, WrapDatatypeErr};
+use crate::protocol::types::{Datatype, MinecraftArray, Nbt, VarInt};
+use serde::Serialize;
+
+/// Block and biome data of a chunk column, as sent in Chunk Data and Update Light
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChunkData {
+    pub heightmaps: Nbt,
+    /// Ordered from the bottom of the world up
+    pub sections: Vec<ChunkSection>,
+    pub block_entities: Vec<BlockEntity>,
+}
+
+impl ChunkData {
+    /// Looks up a block state by chunk-local x/z (0-15) and y counted from the bottom of the world
+    pub fn block_state(&self, x: usize, y: usize, z: usize) -> Option<i32> {
+        self.sections.get(y / 16)?.block_state(x, y % 16, z)
+    }
+
+    /// Biomes are stored per 4x4x4 cell, so coordinates are in blocks like [`Self::block_state`]
+    pub fn biome(&self, x: usize, y: usize, z: usize) -> Option<i32> {
+        self.sections.get(y / 16)?.biome(x / 4, (y % 16) / 4, z / 4)
+    }
+
+    pub fn non_air_blocks(&self) -> i64 {
+        self.sections.iter().map(|x| x.block_count as i64).sum()
+    }
+}
+
+impl Datatype<'_> for ChunkData {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let heightmaps = Nbt::from_bytes(bytes)?;
+
+        // The section count depends on the dimension height, so read until the buffer runs out
+        let size = VarInt::from_bytes(bytes)?.value as usize;
+        let mut section_bytes = <&[u8]>::from_array_bytes(size, bytes)?;
+        let mut sections = vec![];
+        while !section_bytes.is_empty() {
+            let section = ChunkSection::from_bytes(&mut section_bytes)
+                .wrap_err(format!("Can't parse chunk section {}", sections.len()))?;
+            sections.push(section);
+        }
+
+        let block_entities = Vec::from_bytes(bytes)?;
+
+        Ok(Self {
+            heightmaps,
+            sections,
+            block_entities,
+        })
+    }
+}
+
+/// 16x16x16 blocks
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChunkSection {
+    /// Number of non-air blocks
+    pub block_count: i16,
+    pub block_states: BlockStateContainer,
+    pub biomes: BiomeContainer,
+}
+
+impl ChunkSection {
+    pub fn block_state(&self, x: usize, y: usize, z: usize) -> Option<i32> {
+        if x >= 16 || y >= 16 || z >= 16 {
+            return None;
+        }
+        self.block_states.get((y * 16 + z) * 16 + x)
+    }
+
+    pub fn biome(&self, x: usize, y: usize, z: usize) -> Option<i32> {
+        if x >= 4 || y >= 4 || z >= 4 {
+            return None;
+        }
+        self.biomes.get((y * 4 + z) * 4 + x)
+    }
+}
+
+impl Datatype<'_> for ChunkSection {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let block_count = i16::from_bytes(bytes)?;
+        let block_states = BlockStateContainer::from_bytes(bytes)?;
+        let biomes = BiomeContainer::from_bytes(bytes)?;
+        Ok(Self {
+            block_count,
+            block_states,
+            biomes,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum Palette {
+    /// Every entry has this value and the data array is empty
+    SingleValue(VarInt),
+    /// Entries are indexes into this list
+    Indirect(Vec<VarInt>),
+    /// Entries are registry IDs
+    Direct,
+}
+
+/// Compacted array of `ENTRIES` registry IDs.
+/// Up to `MAX_INDIRECT_BITS` bits per entry a palette is sent, above that IDs are stored directly,
+/// using at most [`Self::MAX_BITS`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PalettedContainer<const ENTRIES: usize, const MAX_INDIRECT_BITS: u8> {
+    pub bits_per_entry: u8,
+    pub palette: Palette,
+    pub data: Vec<i64>,
+}
+
+pub type BlockStateContainer = PalettedContainer<4096, 8>;
+pub type BiomeContainer = PalettedContainer<64, 3>;
+
+impl<const ENTRIES: usize, const MAX_INDIRECT_BITS: u8>
+    PalettedContainer<ENTRIES, MAX_INDIRECT_BITS>
+{
+    /// Registry IDs are ints, wider entries can only come from a malformed packet
+    pub const MAX_BITS: u8 = 32;
+
+    fn raw_entry(&self, index: usize) -> Option<u64> {
+        let bits = self.bits_per_entry as usize;
+        // Entries never straddle two longs, any leftover high bits are padding
+        let entries_per_long = 64 / bits;
+        let long = *self.data.get(index / entries_per_long)? as u64;
+        let offset = (index % entries_per_long) * bits;
+        Some((long >> offset) & ((1u64 << bits) - 1))
+    }
+
+    pub fn get(&self, index: usize) -> Option<i32> {
+        if index >= ENTRIES {
+            return None;
+        }
+        match &self.palette {
+            Palette::SingleValue(value) => Some(value.value),
+            Palette::Indirect(palette) => {
+                let raw = self.raw_entry(index)?;
+                palette.get(raw as usize).map(|x| x.value)
+            }
+            Palette::Direct => self.raw_entry(index).map(|x| x as i32),
+        }
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = Option<i32>> + '_ {
+        (0..ENTRIES).map(|i| self.get(i))
+    }
+}
+
+impl<const ENTRIES: usize, const MAX_INDIRECT_BITS: u8> Datatype<'_>
+    for PalettedContainer<ENTRIES, MAX_INDIRECT_BITS>
+{
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let bits_per_entry = u8::from_bytes(bytes)?;
+        if bits_per_entry > Self::MAX_BITS {
+            return Err(DatatypeError::Invalid)
+                .wrap_err(format!("Can't use {bits_per_entry} bits per entry"));
+        }
+        let palette = if bits_per_entry == 0 {
+            Palette::SingleValue(VarInt::from_bytes(bytes)?)
+        } else if bits_per_entry <= MAX_INDIRECT_BITS {
+            Palette::Indirect(Vec::from_bytes(bytes)?)
+        } else {
+            Palette::Direct
+        };
+        let data = Vec::from_bytes(bytes)?;
+
+        Ok(Self {
+            bits_per_entry,
+            palette,
+            data,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BlockEntity {
+    /// Chunk-local x in the high nibble, z in the low nibble
+    pub packed_xz: u8,
+    pub y: i16,
+    pub block_entity_type: VarInt,
+    pub data: Nbt,
+}
+
+impl BlockEntity {
+    pub fn x(&self) -> u8 {
+        self.packed_xz >> 4
+    }
+
+    pub fn z(&self) -> u8 {
+        self.packed_xz & 0x0F
+    }
+}
+
+impl Datatype<'_> for BlockEntity {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let packed_xz = u8::from_bytes(bytes)?;
+        let y = i16::from_bytes(bytes)?;
+        let block_entity_type = VarInt::from_bytes(bytes)?;
+        let data = Nbt::from_bytes(bytes)?;
+        Ok(Self {
+            packed_xz,
+            y,
+            block_entity_type,
+            data,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Section of solid stone (state 1) over a single plains biome
+    fn stone_section() -> Vec<u8> {
+        vec![
+            0x10, 0x00, // 4096 blocks
+            0x00, 0x01, 0x00, // single value: 1, no data
+            0x00, 0x01, 0x00, // single value biome 1, no data
+        ]
+    }
+
+    /// Section with air except the block at (1, 0, 0), and two biomes
+    fn mixed_section() -> Vec<u8> {
+        let mut bytes = vec![
+            0x00, 0x01, // 1 block
+            0x04, // 4 bits per entry
+            0x02, 0x00, 0x09, // palette [0, 9]
+            0x80, 0x02, // 256 longs
+        ];
+        // First long holds entries 0-15, entry 1 points at palette index 1
+        bytes.extend(0x10i64.to_be_bytes());
+        bytes.extend(std::iter::repeat_n(0u8, 255 * 8));
+        bytes.extend([
+            0x01, // 1 bit per entry
+            0x02, 0x03, 0x07, // palette [3, 7]
+            0x01, // 1 long
+        ]);
+        // The top 16 entries (y = 3) use biome 7
+        bytes.extend((0xFFFFi64 << 48).to_be_bytes());
+        bytes
+    }
+
+    #[test]
+    fn chunk_data() {
+        let mut section_data = stone_section();
+        section_data.extend(mixed_section());
+
+        let mut data = vec![0x0A, 0x00]; // empty heightmaps
+        data.extend([0x9D, 0x10]); // size 2077
+        assert_eq!(section_data.len(), 2077);
+        data.extend(section_data);
+        data.extend([
+            0x01, // 1 block entity
+            0x3A, 0x00, 0x40, 0x07, 0x0A, 0x00, // x 3, z 10, y 64, type 7, empty NBT
+        ]);
+        let mut bytes = &data[..];
+
+        let chunk = ChunkData::from_bytes(&mut bytes).unwrap();
+        assert!(bytes.is_empty());
+        assert_eq!(chunk.sections.len(), 2);
+        assert_eq!(chunk.non_air_blocks(), 4097);
+        assert_eq!(chunk.block_state(5, 5, 5), Some(1));
+        assert_eq!(chunk.block_state(0, 16, 0), Some(0));
+        assert_eq!(chunk.block_state(1, 16, 0), Some(9));
+        assert_eq!(chunk.block_state(0, 32, 0), None);
+        assert_eq!(chunk.biome(0, 16, 0), Some(3));
+        assert_eq!(chunk.biome(15, 31, 15), Some(7));
+        assert_eq!(chunk.block_entities[0].x(), 3);
+        assert_eq!(chunk.block_entities[0].z(), 10);
+    }
+
+    #[test]
+    fn direct_palette() {
+        let mut data = vec![0x0F, 0x80, 0x08]; // 15 bits, 1024 longs
+        let mut first = 0i64;
+        first |= 1234; // entry 0
+        first |= 4321 << 15; // entry 1
+        data.extend(first.to_be_bytes());
+        data.extend(std::iter::repeat_n(0u8, 1023 * 8));
+        let mut bytes = &data[..];
+
+        let container = BlockStateContainer::from_bytes(&mut bytes).unwrap();
+        assert_eq!(container.palette, Palette::Direct);
+        assert_eq!(container.get(0), Some(1234));
+        assert_eq!(container.get(1), Some(4321));
+        assert_eq!(container.get(4), Some(0));
+        assert_eq!(container.get(4096), None);
+    }
+
+    #[test]
+    fn bits_per_entry() {
+        let data = [0x20, 0x01, 0x00, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x06];
+        let container = BiomeContainer::from_bytes(&mut &data[..]).unwrap();
+        assert_eq!(container.get(0), Some(6));
+        assert_eq!(container.get(1), Some(5));
+
+        for bits in [33, 64, 65, 255] {
+            let data = [bits, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+            assert!(BiomeContainer::from_bytes(&mut &data[..]).is_err());
+        }
+    }
+}
@@ -0,0 +1,405 @@
+use crate::protocol::types::error::{DatatypeError, WrapDatatypeErr};
+use crate::protocol::types::{
+    Datatype, EntityType, GlobalPosition, IDSet, IdOr, Identifier, Nbt, Particle, Position, Slot,
+    TextComponent, VarInt, VarLong,
+};
+use itertools::Itertools;
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+use uuid::Uuid;
+
+/// Index/value pairs describing an entity, terminated by index 0xFF
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EntityMetadata {
+    pub entries: Vec<MetadataEntry>,
+}
+
+impl EntityMetadata {
+    const END_INDEX: u8 = 0xFF;
+
+    pub fn get(&self, index: u8) -> Option<&MetadataValue> {
+        self.entries
+            .iter()
+            .find(|x| x.index == index)
+            .map(|x| &x.value)
+    }
+
+    /// Renders the entries, naming each field when the entity type is known
+    pub fn describe(&self, entity_type: Option<EntityType>) -> String {
+        self.entries
+            .iter()
+            .map(|entry| {
+                match entity_type.and_then(|x| x.metadata_field(entry.index)) {
+                    Some(name) => format!("{name}({})={}", entry.index, entry.value),
+                    None => format!("{}={}", entry.index, entry.value),
+                }
+            })
+            .join(", ")
+    }
+}
+
+impl Datatype<'_> for EntityMetadata {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let mut entries = vec![];
+        loop {
+            let index = u8::from_bytes(bytes)?;
+            if index == Self::END_INDEX {
+                break;
+            }
+            let value = MetadataValue::from_bytes(bytes)
+                .wrap_err(format!("Can't parse metadata index {index}"))?;
+            entries.push(MetadataEntry { index, value });
+        }
+
+        Ok(Self { entries })
+    }
+}
+
+impl Display for EntityMetadata {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.describe(None))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MetadataEntry {
+    pub index: u8,
+    pub value: MetadataValue,
+}
+
+/// Metadata value, tagged with its serializer ID on the wire
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum MetadataValue {
+    Byte(i8),
+    VarInt(VarInt),
+    VarLong(VarLong),
+    Float(f32),
+    String(String),
+    TextComponent(TextComponent),
+    OptionalTextComponent(Option<TextComponent>),
+    Slot(Slot),
+    Boolean(bool),
+    Rotations { x: f32, y: f32, z: f32 },
+    Position(Position),
+    OptionalPosition(Option<Position>),
+    Direction(Direction),
+    OptionalUuid(Option<Uuid>),
+    BlockState(VarInt),
+    /// 0 (air) means absent
+    OptionalBlockState(Option<VarInt>),
+    Nbt(Nbt),
+    Particle(Particle),
+    Particles(Vec<Particle>),
+    VillagerData {
+        villager_type: VarInt,
+        profession: VarInt,
+        level: VarInt,
+    },
+    /// Sent as value + 1, with 0 meaning absent
+    OptionalVarInt(Option<i32>),
+    Pose(Pose),
+    CatVariant(VarInt),
+    WolfVariant(IdOr<WolfVariant>),
+    FrogVariant(VarInt),
+    OptionalGlobalPosition(Option<GlobalPosition>),
+    PaintingVariant(IdOr<PaintingVariant>),
+    SnifferState(VarInt),
+    ArmadilloState(VarInt),
+    Vector3 { x: f32, y: f32, z: f32 },
+    Quaternion { x: f32, y: f32, z: f32, w: f32 },
+}
+
+impl Datatype<'_> for MetadataValue {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let serializer = VarInt::from_bytes(bytes)?.value;
+        Ok(match serializer {
+            0 => Self::Byte(i8::from_bytes(bytes)?),
+            1 => Self::VarInt(VarInt::from_bytes(bytes)?),
+            2 => Self::VarLong(VarLong::from_bytes(bytes)?),
+            3 => Self::Float(f32::from_bytes(bytes)?),
+            4 => Self::String(String::from_bytes(bytes)?),
+            5 => Self::TextComponent(TextComponent::from_bytes(bytes)?),
+            6 => Self::OptionalTextComponent(Option::from_bytes(bytes)?),
+            7 => Self::Slot(Slot::from_bytes(bytes)?),
+            8 => Self::Boolean(bool::from_bytes(bytes)?),
+            9 => Self::Rotations {
+                x: f32::from_bytes(bytes)?,
+                y: f32::from_bytes(bytes)?,
+                z: f32::from_bytes(bytes)?,
+            },
+            10 => Self::Position(Position::from_bytes(bytes)?),
+            11 => Self::OptionalPosition(Option::from_bytes(bytes)?),
+            12 => Self::Direction(Direction::from_bytes(bytes)?),
+            13 => Self::OptionalUuid(Option::from_bytes(bytes)?),
+            14 => Self::BlockState(VarInt::from_bytes(bytes)?),
+            15 => {
+                let state = VarInt::from_bytes(bytes)?;
+                Self::OptionalBlockState((state.value != 0).then_some(state))
+            }
+            16 => Self::Nbt(Nbt::from_bytes(bytes)?),
+            17 => Self::Particle(Particle::from_bytes(bytes)?),
+            18 => Self::Particles(Vec::from_bytes(bytes)?),
+            19 => Self::VillagerData {
+                villager_type: VarInt::from_bytes(bytes)?,
+                profession: VarInt::from_bytes(bytes)?,
+                level: VarInt::from_bytes(bytes)?,
+            },
+            20 => {
+                let value = VarInt::from_bytes(bytes)?.value;
+                Self::OptionalVarInt((value != 0).then_some(value - 1))
+            }
+            21 => Self::Pose(Pose::from_bytes(bytes)?),
+            22 => Self::CatVariant(VarInt::from_bytes(bytes)?),
+            23 => Self::WolfVariant(IdOr::from_bytes(bytes)?),
+            24 => Self::FrogVariant(VarInt::from_bytes(bytes)?),
+            25 => Self::OptionalGlobalPosition(Option::from_bytes(bytes)?),
+            26 => Self::PaintingVariant(IdOr::from_bytes(bytes)?),
+            27 => Self::SnifferState(VarInt::from_bytes(bytes)?),
+            28 => Self::ArmadilloState(VarInt::from_bytes(bytes)?),
+            29 => Self::Vector3 {
+                x: f32::from_bytes(bytes)?,
+                y: f32::from_bytes(bytes)?,
+                z: f32::from_bytes(bytes)?,
+            },
+            30 => Self::Quaternion {
+                x: f32::from_bytes(bytes)?,
+                y: f32::from_bytes(bytes)?,
+                z: f32::from_bytes(bytes)?,
+                w: f32::from_bytes(bytes)?,
+            },
+            _ => return Err(DatatypeError::Invalid),
+        })
+    }
+}
+
+impl Display for MetadataValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MetadataValue::Byte(v) => write!(f, "{v:#04x}"),
+            MetadataValue::VarInt(v)
+            | MetadataValue::BlockState(v)
+            | MetadataValue::CatVariant(v)
+            | MetadataValue::FrogVariant(v)
+            | MetadataValue::SnifferState(v)
+            | MetadataValue::ArmadilloState(v) => write!(f, "{v}"),
+            MetadataValue::VarLong(v) => write!(f, "{}", v.value),
+            MetadataValue::Float(v) => write!(f, "{v}"),
+            MetadataValue::String(v) => write!(f, "{v:?}"),
+            MetadataValue::TextComponent(v) => write!(f, "{v}"),
+            MetadataValue::Slot(v) => write!(f, "{v}"),
+            MetadataValue::Boolean(v) => write!(f, "{v}"),
+            MetadataValue::Position(v) => write!(f, "{v}"),
+            MetadataValue::Direction(v) => write!(f, "{v:?}"),
+            MetadataValue::Pose(v) => write!(f, "{v:?}"),
+            MetadataValue::Particle(v) => write!(f, "{v}"),
+            MetadataValue::Particles(v) => write!(f, "[{}]", v.iter().join(", ")),
+            MetadataValue::Nbt(v) => write!(f, "{v}"),
+            MetadataValue::OptionalTextComponent(v) => fmt_option(f, v),
+            MetadataValue::OptionalPosition(v) => fmt_option(f, v),
+            MetadataValue::OptionalUuid(v) => fmt_option(f, v),
+            MetadataValue::OptionalBlockState(v) => fmt_option(f, v),
+            MetadataValue::OptionalVarInt(v) => fmt_option(f, v),
+            MetadataValue::OptionalGlobalPosition(v) => fmt_option(f, v),
+            MetadataValue::WolfVariant(v) => write!(f, "{v}"),
+            MetadataValue::PaintingVariant(v) => write!(f, "{v}"),
+            MetadataValue::Rotations { x, y, z } | MetadataValue::Vector3 { x, y, z } => {
+                write!(f, "({x}, {y}, {z})")
+            }
+            MetadataValue::Quaternion { x, y, z, w } => write!(f, "({x}, {y}, {z}, {w})"),
+            MetadataValue::VillagerData {
+                villager_type,
+                profession,
+                level,
+            } => write!(
+                f,
+                "(type {villager_type}, profession {profession}, level {level})"
+            ),
+        }
+    }
+}
+
+fn fmt_option<T: Display>(f: &mut Formatter<'_>, value: &Option<T>) -> std::fmt::Result {
+    match value {
+        Some(v) => write!(f, "{v}"),
+        None => write!(f, "None"),
+    }
+}
+
+#[derive(FromPrimitive, Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[repr(i32)]
+pub enum Direction {
+    Down = 0,
+    Up = 1,
+    North = 2,
+    South = 3,
+    West = 4,
+    East = 5,
+}
+
+impl Datatype<'_> for Direction {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let value = VarInt::from_bytes(bytes)?.value;
+        Self::from_i32(value).ok_or(DatatypeError::Invalid)
+    }
+}
+
+#[derive(FromPrimitive, Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[repr(i32)]
+pub enum Pose {
+    Standing = 0,
+    FallFlying = 1,
+    Sleeping = 2,
+    Swimming = 3,
+    SpinAttack = 4,
+    Crouching = 5,
+    LongJumping = 6,
+    Dying = 7,
+    Croaking = 8,
+    UsingTongue = 9,
+    Sitting = 10,
+    Roaring = 11,
+    Sniffing = 12,
+    Emerging = 13,
+    Digging = 14,
+    Sliding = 15,
+    Shooting = 16,
+    Inhaling = 17,
+}
+
+impl Datatype<'_> for Pose {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let value = VarInt::from_bytes(bytes)?.value;
+        Self::from_i32(value).ok_or(DatatypeError::Invalid)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WolfVariant {
+    pub wild_texture: Identifier,
+    pub tame_texture: Identifier,
+    pub angry_texture: Identifier,
+    pub biomes: IDSet,
+}
+
+impl Datatype<'_> for WolfVariant {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let wild_texture = Identifier::from_bytes(bytes)?;
+        let tame_texture = Identifier::from_bytes(bytes)?;
+        let angry_texture = Identifier::from_bytes(bytes)?;
+        let biomes = IDSet::from_bytes(bytes)?;
+        Ok(Self {
+            wild_texture,
+            tame_texture,
+            angry_texture,
+            biomes,
+        })
+    }
+}
+
+impl Display for WolfVariant {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.wild_texture)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PaintingVariant {
+    pub width: VarInt,
+    pub height: VarInt,
+    pub asset_id: Identifier,
+    pub title: Option<TextComponent>,
+    pub author: Option<TextComponent>,
+}
+
+impl Datatype<'_> for PaintingVariant {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let width = VarInt::from_bytes(bytes)?;
+        let height = VarInt::from_bytes(bytes)?;
+        let asset_id = Identifier::from_bytes(bytes)?;
+        let title = Option::from_bytes(bytes)?;
+        let author = Option::from_bytes(bytes)?;
+        Ok(Self {
+            width,
+            height,
+            asset_id,
+            title,
+            author,
+        })
+    }
+}
+
+impl Display for PaintingVariant {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({}x{})", self.asset_id, self.width, self.height)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn player_metadata() {
+        let mut bytes: &[u8] = &[
+            0x00, 0x00, 0x02, // shared_flags: Byte 0x02 (crouching)
+            0x06, 0x15, 0x05, // pose: Crouching
+            0x09, 0x03, 0x41, 0xA0, 0x00, 0x00, // health: 20.0
+            0x0F, 0x14, 0x00, // unknown index 15 on player: absent OptionalVarInt
+            0xFF,
+        ];
+        let metadata = EntityMetadata::from_bytes(&mut bytes).unwrap();
+        assert!(bytes.is_empty());
+        assert_eq!(metadata.get(6), Some(&MetadataValue::Pose(Pose::Crouching)));
+        assert_eq!(metadata.get(9), Some(&MetadataValue::Float(20.0)));
+        assert_eq!(
+            metadata.describe(Some(EntityType { id: 147 })),
+            "shared_flags(0)=0x02, pose(6)=Crouching, health(9)=20, player_absorption(15)=None"
+        );
+        assert_eq!(metadata.to_string(), "0=0x02, 6=Crouching, 9=20, 15=None");
+    }
+
+    #[test]
+    fn optional_values() {
+        let mut bytes: &[u8] = &[
+            0x01, 0x0F, 0x00, // optional block state: absent
+            0x02, 0x0F, 0x01, // optional block state: 1
+            0x03, 0x14, 0x06, // optional VarInt: 5
+            0x04, 0x0D, 0x00, // optional UUID: absent
+            0xFF,
+        ];
+        let metadata = EntityMetadata::from_bytes(&mut bytes).unwrap();
+        assert_eq!(metadata.get(1), Some(&MetadataValue::OptionalBlockState(None)));
+        assert_eq!(
+            metadata.get(2),
+            Some(&MetadataValue::OptionalBlockState(Some(VarInt { value: 1 })))
+        );
+        assert_eq!(metadata.get(3), Some(&MetadataValue::OptionalVarInt(Some(5))));
+        assert_eq!(metadata.get(4), Some(&MetadataValue::OptionalUuid(None)));
+    }
+
+    #[test]
+    fn unknown_serializer() {
+        let mut bytes: &[u8] = &[0x00, 0x40, 0x00, 0xFF];
+        assert!(EntityMetadata::from_bytes(&mut bytes).is_err());
+    }
+}
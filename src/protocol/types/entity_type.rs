@@ -0,0 +1,318 @@
+use super::error::DatatypeError;
+use crate::protocol::types::{Datatype, VarInt};
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+
+/// Entity type registry ID, as of protocol 769 (1.21.4)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct EntityType {
+    pub id: i32,
+}
+
+const ENTITY: &[&str] = &[
+    "shared_flags",
+    "air_supply",
+    "custom_name",
+    "custom_name_visible",
+    "silent",
+    "no_gravity",
+    "pose",
+    "ticks_frozen",
+];
+const LIVING_ENTITY: &[&str] = &[
+    "living_entity_flags",
+    "health",
+    "effect_particles",
+    "effect_ambience",
+    "arrow_count",
+    "stinger_count",
+    "sleeping_pos",
+];
+const PLAYER: &[&str] = &[
+    "player_absorption",
+    "score",
+    "player_mode_customisation",
+    "player_main_hand",
+    "shoulder_left",
+    "shoulder_right",
+];
+const ARMOR_STAND: &[&str] = &[
+    "client_flags",
+    "head_pose",
+    "body_pose",
+    "left_arm_pose",
+    "right_arm_pose",
+    "left_leg_pose",
+    "right_leg_pose",
+];
+const MOB: &[&str] = &["mob_flags"];
+const AGEABLE_MOB: &[&str] = &["baby"];
+const TAMABLE_ANIMAL: &[&str] = &["tame_flags", "owner"];
+const ABSTRACT_VILLAGER: &[&str] = &["unhappy_counter"];
+const VILLAGER: &[&str] = &["villager_data"];
+const ZOMBIE: &[&str] = &["baby", "special_type", "drowned_conversion"];
+const ZOMBIE_VILLAGER: &[&str] = &["converting", "villager_data"];
+const CREEPER: &[&str] = &["swell_dir", "is_powered", "is_ignited"];
+const SKELETON: &[&str] = &["stray_conversion"];
+const ENDERMAN: &[&str] = &["carry_state", "creepy", "stared_at"];
+const SLIME: &[&str] = &["size"];
+const SPIDER: &[&str] = &["flags"];
+const WOLF: &[&str] = &["interested", "collar_color", "anger_time", "variant"];
+const CAT: &[&str] = &["variant", "is_lying", "relax_state_one", "collar_color"];
+const SHEEP: &[&str] = &["wool"];
+const PIG: &[&str] = &["saddle", "boost_time"];
+const VARIANT: &[&str] = &["variant"];
+const ITEM: &[&str] = &["item"];
+const ITEM_FRAME: &[&str] = &["item", "rotation"];
+const FALLING_BLOCK: &[&str] = &["start_pos"];
+const FIREWORK_ROCKET: &[&str] = &["item", "attached_to_target", "shot_at_angle"];
+const PRIMED_TNT: &[&str] = &["fuse", "block_state"];
+
+impl EntityType {
+    const NAMES: [&'static str; 149] = [
+        "acacia_boat",
+        "acacia_chest_boat",
+        "allay",
+        "area_effect_cloud",
+        "armadillo",
+        "armor_stand",
+        "arrow",
+        "axolotl",
+        "bamboo_chest_raft",
+        "bamboo_raft",
+        "bat",
+        "bee",
+        "birch_boat",
+        "birch_chest_boat",
+        "blaze",
+        "block_display",
+        "bogged",
+        "breeze",
+        "breeze_wind_charge",
+        "camel",
+        "cat",
+        "cave_spider",
+        "cherry_boat",
+        "cherry_chest_boat",
+        "chest_minecart",
+        "chicken",
+        "cod",
+        "command_block_minecart",
+        "cow",
+        "creaking",
+        "creeper",
+        "dark_oak_boat",
+        "dark_oak_chest_boat",
+        "dolphin",
+        "donkey",
+        "dragon_fireball",
+        "drowned",
+        "egg",
+        "elder_guardian",
+        "enderman",
+        "endermite",
+        "ender_dragon",
+        "ender_pearl",
+        "end_crystal",
+        "evoker",
+        "evoker_fangs",
+        "experience_bottle",
+        "experience_orb",
+        "eye_of_ender",
+        "falling_block",
+        "fireball",
+        "firework_rocket",
+        "fox",
+        "frog",
+        "furnace_minecart",
+        "ghast",
+        "giant",
+        "glow_item_frame",
+        "glow_squid",
+        "goat",
+        "guardian",
+        "hoglin",
+        "hopper_minecart",
+        "horse",
+        "husk",
+        "illusioner",
+        "interaction",
+        "iron_golem",
+        "item",
+        "item_display",
+        "item_frame",
+        "jungle_boat",
+        "jungle_chest_boat",
+        "leash_knot",
+        "lightning_bolt",
+        "llama",
+        "llama_spit",
+        "magma_cube",
+        "mangrove_boat",
+        "mangrove_chest_boat",
+        "marker",
+        "minecart",
+        "mooshroom",
+        "mule",
+        "oak_boat",
+        "oak_chest_boat",
+        "ocelot",
+        "ominous_item_spawner",
+        "painting",
+        "pale_oak_boat",
+        "pale_oak_chest_boat",
+        "panda",
+        "parrot",
+        "phantom",
+        "pig",
+        "piglin",
+        "piglin_brute",
+        "pillager",
+        "polar_bear",
+        "potion",
+        "pufferfish",
+        "rabbit",
+        "ravager",
+        "salmon",
+        "sheep",
+        "shulker",
+        "shulker_bullet",
+        "silverfish",
+        "skeleton",
+        "skeleton_horse",
+        "slime",
+        "small_fireball",
+        "sniffer",
+        "snowball",
+        "snow_golem",
+        "spawner_minecart",
+        "spectral_arrow",
+        "spider",
+        "spruce_boat",
+        "spruce_chest_boat",
+        "squid",
+        "stray",
+        "strider",
+        "tadpole",
+        "text_display",
+        "tnt",
+        "tnt_minecart",
+        "trader_llama",
+        "trident",
+        "tropical_fish",
+        "turtle",
+        "vex",
+        "villager",
+        "vindicator",
+        "wandering_trader",
+        "warden",
+        "wind_charge",
+        "witch",
+        "wither",
+        "wither_skeleton",
+        "wither_skull",
+        "wolf",
+        "zoglin",
+        "zombie",
+        "zombie_horse",
+        "zombie_villager",
+        "zombified_piglin",
+        "player",
+        "fishing_bobber",
+    ];
+
+    pub fn name(&self) -> Option<&'static str> {
+        usize::try_from(self.id)
+            .ok()
+            .and_then(|i| Self::NAMES.get(i))
+            .copied()
+    }
+
+    /// Metadata fields declared by each class in the entity's hierarchy, base class first.
+    /// Only the more common entities are mapped; the rest fall back to the base `Entity` fields.
+    fn metadata_layers(&self) -> &'static [&'static [&'static str]] {
+        match self.name().unwrap_or_default() {
+            "player" => &[ENTITY, LIVING_ENTITY, PLAYER],
+            "armor_stand" => &[ENTITY, LIVING_ENTITY, ARMOR_STAND],
+            "zombie" | "husk" | "drowned" | "zombified_piglin" => {
+                &[ENTITY, LIVING_ENTITY, MOB, ZOMBIE]
+            }
+            "zombie_villager" => &[ENTITY, LIVING_ENTITY, MOB, ZOMBIE, ZOMBIE_VILLAGER],
+            "creeper" => &[ENTITY, LIVING_ENTITY, MOB, CREEPER],
+            "skeleton" => &[ENTITY, LIVING_ENTITY, MOB, SKELETON],
+            "stray" | "wither_skeleton" | "bogged" => &[ENTITY, LIVING_ENTITY, MOB],
+            "enderman" => &[ENTITY, LIVING_ENTITY, MOB, ENDERMAN],
+            "slime" | "magma_cube" => &[ENTITY, LIVING_ENTITY, MOB, SLIME],
+            "spider" | "cave_spider" => &[ENTITY, LIVING_ENTITY, MOB, SPIDER],
+            "villager" => &[ENTITY, LIVING_ENTITY, MOB, AGEABLE_MOB, ABSTRACT_VILLAGER, VILLAGER],
+            "wandering_trader" => &[ENTITY, LIVING_ENTITY, MOB, AGEABLE_MOB, ABSTRACT_VILLAGER],
+            "wolf" => &[ENTITY, LIVING_ENTITY, MOB, AGEABLE_MOB, TAMABLE_ANIMAL, WOLF],
+            "cat" => &[ENTITY, LIVING_ENTITY, MOB, AGEABLE_MOB, TAMABLE_ANIMAL, CAT],
+            "sheep" => &[ENTITY, LIVING_ENTITY, MOB, AGEABLE_MOB, SHEEP],
+            "pig" => &[ENTITY, LIVING_ENTITY, MOB, AGEABLE_MOB, PIG],
+            "mooshroom" => &[ENTITY, LIVING_ENTITY, MOB, AGEABLE_MOB, VARIANT],
+            "cow" | "chicken" => &[ENTITY, LIVING_ENTITY, MOB, AGEABLE_MOB],
+            "item" => &[ENTITY, ITEM],
+            "item_frame" | "glow_item_frame" => &[ENTITY, ITEM_FRAME],
+            "painting" => &[ENTITY, VARIANT],
+            "falling_block" => &[ENTITY, FALLING_BLOCK],
+            "firework_rocket" => &[ENTITY, FIREWORK_ROCKET],
+            "tnt" => &[ENTITY, PRIMED_TNT],
+            _ => &[ENTITY],
+        }
+    }
+
+    pub fn metadata_field(&self, index: u8) -> Option<&'static str> {
+        self.metadata_layers()
+            .iter()
+            .flat_map(|layer| layer.iter())
+            .nth(index as usize)
+            .copied()
+    }
+}
+
+impl Datatype<'_> for EntityType {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let id = VarInt::from_bytes(bytes)?.value;
+        Ok(Self { id })
+    }
+}
+
+impl Display for EntityType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.name() {
+            Some(name) => write!(f, "minecraft:{name}"),
+            None => write!(f, "unknown({})", self.id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn names() {
+        assert_eq!(EntityType { id: 147 }.to_string(), "minecraft:player");
+        assert_eq!(EntityType { id: 500 }.to_string(), "unknown(500)");
+    }
+
+    #[test]
+    fn metadata_fields() {
+        let player = EntityType { id: 147 };
+        assert_eq!(player.metadata_field(0), Some("shared_flags"));
+        assert_eq!(player.metadata_field(9), Some("health"));
+        assert_eq!(player.metadata_field(17), Some("player_mode_customisation"));
+        assert_eq!(player.metadata_field(21), None);
+
+        let villager = EntityType { id: 132 };
+        assert_eq!(villager.metadata_field(18), Some("villager_data"));
+
+        let unmapped = EntityType { id: 0 };
+        assert_eq!(unmapped.metadata_field(6), Some("pose"));
+        assert_eq!(unmapped.metadata_field(8), None);
+    }
+}
@@ -1,6 +1,7 @@
+use std::str::Utf8Error;
 use thiserror::Error;
 
-#[derive(Debug, Copy, Clone, Error)]
+#[derive(Debug, Clone, Error)]
 pub enum DatatypeError {
     #[error("Input is too big for {0}")]
     TooBig(&'static str),
@@ -10,4 +11,39 @@ pub enum DatatypeError {
     UnknownState(i32),
     #[error("Invalid input")]
     Invalid,
+    #[error("Invalid UTF-8: {0}")]
+    Utf8(#[from] Utf8Error),
+    #[error("{context}: {source}")]
+    Context {
+        context: String,
+        source: Box<DatatypeError>,
+    },
+}
+
+impl DatatypeError {
+    /// Short name of the variant, the innermost one for [`Self::Context`]
+    pub fn name(&self) -> &'static str {
+        match self {
+            DatatypeError::TooBig(_) => "too_big",
+            DatatypeError::NotEnoughData => "not_enough_data",
+            DatatypeError::UnknownState(_) => "unknown_state",
+            DatatypeError::Invalid => "invalid",
+            DatatypeError::Utf8(_) => "utf8",
+            DatatypeError::Context { source, .. } => source.name(),
+        }
+    }
+}
+
+/// Says what was being parsed when a [`DatatypeError`] happened, like `WrapErr` for reports
+pub trait WrapDatatypeErr<T> {
+    fn wrap_err(self, context: String) -> Result<T, DatatypeError>;
+}
+
+impl<T> WrapDatatypeErr<T> for Result<T, DatatypeError> {
+    fn wrap_err(self, context: String) -> Result<T, DatatypeError> {
+        self.map_err(|source| DatatypeError::Context {
+            context,
+            source: Box::new(source),
+        })
+    }
 }
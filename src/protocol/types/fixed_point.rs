@@ -0,0 +1,45 @@
+use super::error::DatatypeError;
+use crate::protocol::types::Datatype;
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+
+/// Int with 5 fractional bits
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub struct FixedPoint {
+    pub raw: i32,
+}
+
+impl FixedPoint {
+    const FRACTIONAL_BITS: u32 = 5;
+
+    pub fn value(&self) -> f64 {
+        self.raw as f64 / (1 << Self::FRACTIONAL_BITS) as f64
+    }
+}
+
+impl Datatype<'_> for FixedPoint {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let raw = i32::from_bytes(bytes)?;
+        Ok(Self { raw })
+    }
+}
+
+impl Display for FixedPoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.value())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn value() {
+        let mut bytes: &[u8] = &[0xFF, 0xFF, 0xFF, 0xB0];
+        assert_eq!(FixedPoint::from_bytes(&mut bytes).unwrap().value(), -2.5);
+    }
+}
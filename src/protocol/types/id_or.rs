@@ -0,0 +1,55 @@
+use crate::protocol::types::error::DatatypeError;
+use crate::protocol::types::{Datatype, VarInt};
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+
+/// Either a registry ID or the registry entry sent inline.
+/// A leading VarInt of 0 means inline, anything else is the ID plus one.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum IdOr<T> {
+    Registry(i32),
+    Inline(T),
+}
+
+impl<'a, T> Datatype<'a> for IdOr<T>
+where
+    T: Datatype<'a>,
+{
+    fn from_bytes(bytes: &mut &'a [u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let id = VarInt::from_bytes(bytes)?.value;
+        if id < 0 {
+            Err(DatatypeError::Invalid)
+        } else if id == 0 {
+            Ok(Self::Inline(T::from_bytes(bytes)?))
+        } else {
+            Ok(Self::Registry(id - 1))
+        }
+    }
+}
+
+impl<T> Display for IdOr<T>
+where
+    T: Display,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IdOr::Registry(id) => write!(f, "#{id}"),
+            IdOr::Inline(value) => value.fmt(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn id_or() {
+        let mut bytes: &[u8] = &[0x05, 0x00, 0x2A];
+        assert_eq!(IdOr::<u8>::from_bytes(&mut bytes).unwrap(), IdOr::Registry(4));
+        assert_eq!(IdOr::<u8>::from_bytes(&mut bytes).unwrap(), IdOr::Inline(42));
+    }
+}
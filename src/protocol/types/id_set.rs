@@ -0,0 +1,59 @@
+use crate::protocol::types::error::DatatypeError;
+use crate::protocol::types::{Datatype, Identifier, MinecraftArray, VarInt};
+use itertools::Itertools;
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+
+/// Set of registry IDs, either named by a tag or listed inline
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum IDSet {
+    Tag(Identifier),
+    Ids(Vec<VarInt>),
+}
+
+impl Datatype<'_> for IDSet {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let set_type = VarInt::from_bytes(bytes)?.value;
+        if set_type < 0 {
+            Err(DatatypeError::Invalid)
+        } else if set_type == 0 {
+            Ok(Self::Tag(Identifier::from_bytes(bytes)?))
+        } else {
+            let ids = Vec::from_array_bytes(set_type as usize - 1, bytes)?;
+            Ok(Self::Ids(ids))
+        }
+    }
+}
+
+impl Display for IDSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IDSet::Tag(tag) => write!(f, "#{tag}"),
+            IDSet::Ids(ids) => write!(f, "[{}]", ids.iter().join(", ")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tag() {
+        let mut data = vec![0x00, 0x0E];
+        data.extend(b"minecraft:dirt");
+        let mut bytes = &data[..];
+        let set = IDSet::from_bytes(&mut bytes).unwrap();
+        assert_eq!(set.to_string(), "#minecraft:dirt");
+    }
+
+    #[test]
+    fn ids() {
+        let mut bytes: &[u8] = &[0x03, 0x01, 0x80, 0x01];
+        let set = IDSet::from_bytes(&mut bytes).unwrap();
+        assert_eq!(set.to_string(), "[1, 128]");
+    }
+}
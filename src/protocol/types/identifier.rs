@@ -1,24 +1,23 @@
-use crate::protocol::types::error::DatatypeError;
+use crate::protocol::types::error::{DatatypeError, WrapDatatypeErr};
 use crate::protocol::types::Datatype;
-use color_eyre::eyre::{OptionExt, WrapErr};
-use std::collections::VecDeque;
+use serde::{Serialize, Serializer};
 use std::fmt::{Display, Formatter};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Identifier {
     pub namespace: String,
     pub value: String,
 }
 
-impl Datatype for Identifier {
-    fn from_bytes(bytes: &mut VecDeque<u8>) -> color_eyre::Result<Self>
+impl Datatype<'_> for Identifier {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
     where
         Self: Sized,
     {
         let data = String::from_bytes(bytes)?;
         let (namespace, value) = data
             .split_once(":")
-            .ok_or_eyre(DatatypeError::Invalid)
+            .ok_or(DatatypeError::Invalid)
             .wrap_err(format!("Can't parse Identifier: {data}"))?;
 
         Ok(Self {
@@ -33,3 +32,9 @@ impl Display for Identifier {
         write!(f, "{}:{}", self.namespace, self.value)
     }
 }
+
+impl Serialize for Identifier {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
@@ -0,0 +1,1066 @@
+use crate::protocol::types::error::{DatatypeError, WrapDatatypeErr};
+use crate::protocol::types::{
+    Datatype, GlobalPosition, IDSet, IdOr, Identifier, Nbt, PlayerProperty, PotionEffect, Slot,
+    SoundEvent, TextComponent, VarInt,
+};
+use itertools::Itertools;
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+use uuid::Uuid;
+
+/// Structured item data attached to a [`Slot`], as of protocol 769 (1.21.4).
+/// Variants are listed in registry order, which is also their network ID.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum ItemComponent {
+    CustomData(Nbt),
+    MaxStackSize(VarInt),
+    MaxDamage(VarInt),
+    Damage(VarInt),
+    Unbreakable {
+        show_in_tooltip: bool,
+    },
+    CustomName(TextComponent),
+    ItemName(TextComponent),
+    ItemModel(Identifier),
+    Lore(Vec<TextComponent>),
+    Rarity(VarInt),
+    Enchantments {
+        enchantments: Vec<EnchantmentLevel>,
+        show_in_tooltip: bool,
+    },
+    CanPlaceOn {
+        predicates: Vec<BlockPredicate>,
+        show_in_tooltip: bool,
+    },
+    CanBreak {
+        predicates: Vec<BlockPredicate>,
+        show_in_tooltip: bool,
+    },
+    AttributeModifiers {
+        modifiers: Vec<AttributeModifier>,
+        show_in_tooltip: bool,
+    },
+    CustomModelData {
+        floats: Vec<f32>,
+        flags: Vec<bool>,
+        strings: Vec<String>,
+        colors: Vec<i32>,
+    },
+    HideAdditionalTooltip,
+    HideTooltip,
+    RepairCost(VarInt),
+    CreativeSlotLock,
+    EnchantmentGlintOverride(bool),
+    IntangibleProjectile(Nbt),
+    Food {
+        nutrition: VarInt,
+        saturation_modifier: f32,
+        can_always_eat: bool,
+    },
+    Consumable {
+        consume_seconds: f32,
+        animation: VarInt,
+        sound: IdOr<SoundEvent>,
+        has_consume_particles: bool,
+        effects: Vec<ConsumeEffect>,
+    },
+    UseRemainder(Box<Slot>),
+    UseCooldown {
+        seconds: f32,
+        cooldown_group: Option<Identifier>,
+    },
+    DamageResistant(Identifier),
+    Tool {
+        rules: Vec<ToolRule>,
+        default_mining_speed: f32,
+        damage_per_block: VarInt,
+    },
+    Enchantable(VarInt),
+    Equippable {
+        slot: VarInt,
+        equip_sound: IdOr<SoundEvent>,
+        model: Option<Identifier>,
+        camera_overlay: Option<Identifier>,
+        allowed_entities: Option<IDSet>,
+        dispensable: bool,
+        swappable: bool,
+        damage_on_hurt: bool,
+    },
+    Repairable(IDSet),
+    Glider,
+    TooltipStyle(Identifier),
+    DeathProtection(Vec<ConsumeEffect>),
+    StoredEnchantments {
+        enchantments: Vec<EnchantmentLevel>,
+        show_in_tooltip: bool,
+    },
+    DyedColor {
+        color: i32,
+        show_in_tooltip: bool,
+    },
+    MapColor(i32),
+    MapId(VarInt),
+    MapDecorations(Nbt),
+    MapPostProcessing(VarInt),
+    ChargedProjectiles(Vec<Slot>),
+    BundleContents(Vec<Slot>),
+    PotionContents {
+        potion_id: Option<VarInt>,
+        custom_color: Option<i32>,
+        custom_effects: Vec<PotionEffect>,
+        custom_name: Option<String>,
+    },
+    SuspiciousStewEffects(Vec<StewEffect>),
+    WritableBookContent(Vec<Filtered<String>>),
+    WrittenBookContent {
+        title: Filtered<String>,
+        author: String,
+        generation: VarInt,
+        pages: Vec<Filtered<TextComponent>>,
+        resolved: bool,
+    },
+    Trim {
+        material: IdOr<TrimMaterial>,
+        pattern: IdOr<TrimPattern>,
+        show_in_tooltip: bool,
+    },
+    DebugStickState(Nbt),
+    EntityData(Nbt),
+    BucketEntityData(Nbt),
+    BlockEntityData(Nbt),
+    Instrument(IdOr<Instrument>),
+    OminousBottleAmplifier(VarInt),
+    JukeboxPlayable {
+        song: JukeboxSongReference,
+        show_in_tooltip: bool,
+    },
+    Recipes(Nbt),
+    LodestoneTracker {
+        target: Option<GlobalPosition>,
+        tracked: bool,
+    },
+    FireworkExplosion(FireworkExplosion),
+    Fireworks {
+        flight_duration: VarInt,
+        explosions: Vec<FireworkExplosion>,
+    },
+    Profile {
+        name: Option<String>,
+        uuid: Option<Uuid>,
+        properties: Vec<PlayerProperty>,
+    },
+    NoteBlockSound(Identifier),
+    BannerPatterns(Vec<BannerLayer>),
+    BaseColor(VarInt),
+    PotDecorations(Vec<VarInt>),
+    Container(Vec<Slot>),
+    BlockState(Vec<BlockStateProperty>),
+    Bees(Vec<BeeData>),
+    Lock(Nbt),
+    ContainerLoot(Nbt),
+}
+
+impl ItemComponent {
+    const NAMES: [&'static str; 67] = [
+        "custom_data",
+        "max_stack_size",
+        "max_damage",
+        "damage",
+        "unbreakable",
+        "custom_name",
+        "item_name",
+        "item_model",
+        "lore",
+        "rarity",
+        "enchantments",
+        "can_place_on",
+        "can_break",
+        "attribute_modifiers",
+        "custom_model_data",
+        "hide_additional_tooltip",
+        "hide_tooltip",
+        "repair_cost",
+        "creative_slot_lock",
+        "enchantment_glint_override",
+        "intangible_projectile",
+        "food",
+        "consumable",
+        "use_remainder",
+        "use_cooldown",
+        "damage_resistant",
+        "tool",
+        "enchantable",
+        "equippable",
+        "repairable",
+        "glider",
+        "tooltip_style",
+        "death_protection",
+        "stored_enchantments",
+        "dyed_color",
+        "map_color",
+        "map_id",
+        "map_decorations",
+        "map_post_processing",
+        "charged_projectiles",
+        "bundle_contents",
+        "potion_contents",
+        "suspicious_stew_effects",
+        "writable_book_content",
+        "written_book_content",
+        "trim",
+        "debug_stick_state",
+        "entity_data",
+        "bucket_entity_data",
+        "block_entity_data",
+        "instrument",
+        "ominous_bottle_amplifier",
+        "jukebox_playable",
+        "recipes",
+        "lodestone_tracker",
+        "firework_explosion",
+        "fireworks",
+        "profile",
+        "note_block_sound",
+        "banner_patterns",
+        "base_color",
+        "pot_decorations",
+        "container",
+        "block_state",
+        "bees",
+        "lock",
+        "container_loot",
+    ];
+
+    pub fn name_from_id(id: i32) -> String {
+        match usize::try_from(id).ok().and_then(|i| Self::NAMES.get(i)) {
+            Some(name) => format!("minecraft:{name}"),
+            None => format!("unknown({id})"),
+        }
+    }
+
+    pub fn id(&self) -> i32 {
+        match self {
+            ItemComponent::CustomData(_) => 0,
+            ItemComponent::MaxStackSize(_) => 1,
+            ItemComponent::MaxDamage(_) => 2,
+            ItemComponent::Damage(_) => 3,
+            ItemComponent::Unbreakable { .. } => 4,
+            ItemComponent::CustomName(_) => 5,
+            ItemComponent::ItemName(_) => 6,
+            ItemComponent::ItemModel(_) => 7,
+            ItemComponent::Lore(_) => 8,
+            ItemComponent::Rarity(_) => 9,
+            ItemComponent::Enchantments { .. } => 10,
+            ItemComponent::CanPlaceOn { .. } => 11,
+            ItemComponent::CanBreak { .. } => 12,
+            ItemComponent::AttributeModifiers { .. } => 13,
+            ItemComponent::CustomModelData { .. } => 14,
+            ItemComponent::HideAdditionalTooltip => 15,
+            ItemComponent::HideTooltip => 16,
+            ItemComponent::RepairCost(_) => 17,
+            ItemComponent::CreativeSlotLock => 18,
+            ItemComponent::EnchantmentGlintOverride(_) => 19,
+            ItemComponent::IntangibleProjectile(_) => 20,
+            ItemComponent::Food { .. } => 21,
+            ItemComponent::Consumable { .. } => 22,
+            ItemComponent::UseRemainder(_) => 23,
+            ItemComponent::UseCooldown { .. } => 24,
+            ItemComponent::DamageResistant(_) => 25,
+            ItemComponent::Tool { .. } => 26,
+            ItemComponent::Enchantable(_) => 27,
+            ItemComponent::Equippable { .. } => 28,
+            ItemComponent::Repairable(_) => 29,
+            ItemComponent::Glider => 30,
+            ItemComponent::TooltipStyle(_) => 31,
+            ItemComponent::DeathProtection(_) => 32,
+            ItemComponent::StoredEnchantments { .. } => 33,
+            ItemComponent::DyedColor { .. } => 34,
+            ItemComponent::MapColor(_) => 35,
+            ItemComponent::MapId(_) => 36,
+            ItemComponent::MapDecorations(_) => 37,
+            ItemComponent::MapPostProcessing(_) => 38,
+            ItemComponent::ChargedProjectiles(_) => 39,
+            ItemComponent::BundleContents(_) => 40,
+            ItemComponent::PotionContents { .. } => 41,
+            ItemComponent::SuspiciousStewEffects(_) => 42,
+            ItemComponent::WritableBookContent(_) => 43,
+            ItemComponent::WrittenBookContent { .. } => 44,
+            ItemComponent::Trim { .. } => 45,
+            ItemComponent::DebugStickState(_) => 46,
+            ItemComponent::EntityData(_) => 47,
+            ItemComponent::BucketEntityData(_) => 48,
+            ItemComponent::BlockEntityData(_) => 49,
+            ItemComponent::Instrument(_) => 50,
+            ItemComponent::OminousBottleAmplifier(_) => 51,
+            ItemComponent::JukeboxPlayable { .. } => 52,
+            ItemComponent::Recipes(_) => 53,
+            ItemComponent::LodestoneTracker { .. } => 54,
+            ItemComponent::FireworkExplosion(_) => 55,
+            ItemComponent::Fireworks { .. } => 56,
+            ItemComponent::Profile { .. } => 57,
+            ItemComponent::NoteBlockSound(_) => 58,
+            ItemComponent::BannerPatterns(_) => 59,
+            ItemComponent::BaseColor(_) => 60,
+            ItemComponent::PotDecorations(_) => 61,
+            ItemComponent::Container(_) => 62,
+            ItemComponent::BlockState(_) => 63,
+            ItemComponent::Bees(_) => 64,
+            ItemComponent::Lock(_) => 65,
+            ItemComponent::ContainerLoot(_) => 66,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        Self::name_from_id(self.id())
+    }
+
+    /// Reads a component of an item nested `depth` slots deep, see [`Slot::read`]
+    pub(crate) fn read_nested(bytes: &mut &[u8], depth: usize) -> Result<Self, DatatypeError> {
+        let id = VarInt::from_bytes(bytes)?.value;
+        Self::read(id, bytes, depth).wrap_err(format!(
+            "Can't parse item component {}",
+            Self::name_from_id(id)
+        ))
+    }
+
+    fn read(id: i32, bytes: &mut &[u8], depth: usize) -> Result<Self, DatatypeError> {
+        Ok(match id {
+            0 => Self::CustomData(Nbt::from_bytes(bytes)?),
+            1 => Self::MaxStackSize(VarInt::from_bytes(bytes)?),
+            2 => Self::MaxDamage(VarInt::from_bytes(bytes)?),
+            3 => Self::Damage(VarInt::from_bytes(bytes)?),
+            4 => Self::Unbreakable {
+                show_in_tooltip: bool::from_bytes(bytes)?,
+            },
+            5 => Self::CustomName(TextComponent::from_bytes(bytes)?),
+            6 => Self::ItemName(TextComponent::from_bytes(bytes)?),
+            7 => Self::ItemModel(Identifier::from_bytes(bytes)?),
+            8 => Self::Lore(Vec::from_bytes(bytes)?),
+            9 => Self::Rarity(VarInt::from_bytes(bytes)?),
+            10 => Self::Enchantments {
+                enchantments: Vec::from_bytes(bytes)?,
+                show_in_tooltip: bool::from_bytes(bytes)?,
+            },
+            11 => Self::CanPlaceOn {
+                predicates: Vec::from_bytes(bytes)?,
+                show_in_tooltip: bool::from_bytes(bytes)?,
+            },
+            12 => Self::CanBreak {
+                predicates: Vec::from_bytes(bytes)?,
+                show_in_tooltip: bool::from_bytes(bytes)?,
+            },
+            13 => Self::AttributeModifiers {
+                modifiers: Vec::from_bytes(bytes)?,
+                show_in_tooltip: bool::from_bytes(bytes)?,
+            },
+            14 => Self::CustomModelData {
+                floats: Vec::from_bytes(bytes)?,
+                flags: Vec::from_bytes(bytes)?,
+                strings: Vec::from_bytes(bytes)?,
+                colors: Vec::from_bytes(bytes)?,
+            },
+            15 => Self::HideAdditionalTooltip,
+            16 => Self::HideTooltip,
+            17 => Self::RepairCost(VarInt::from_bytes(bytes)?),
+            18 => Self::CreativeSlotLock,
+            19 => Self::EnchantmentGlintOverride(bool::from_bytes(bytes)?),
+            20 => Self::IntangibleProjectile(Nbt::from_bytes(bytes)?),
+            21 => Self::Food {
+                nutrition: VarInt::from_bytes(bytes)?,
+                saturation_modifier: f32::from_bytes(bytes)?,
+                can_always_eat: bool::from_bytes(bytes)?,
+            },
+            22 => Self::Consumable {
+                consume_seconds: f32::from_bytes(bytes)?,
+                animation: VarInt::from_bytes(bytes)?,
+                sound: IdOr::from_bytes(bytes)?,
+                has_consume_particles: bool::from_bytes(bytes)?,
+                effects: Vec::from_bytes(bytes)?,
+            },
+            23 => Self::UseRemainder(Box::new(Slot::read(bytes, depth + 1)?)),
+            24 => Self::UseCooldown {
+                seconds: f32::from_bytes(bytes)?,
+                cooldown_group: Option::from_bytes(bytes)?,
+            },
+            25 => Self::DamageResistant(Identifier::from_bytes(bytes)?),
+            26 => Self::Tool {
+                rules: Vec::from_bytes(bytes)?,
+                default_mining_speed: f32::from_bytes(bytes)?,
+                damage_per_block: VarInt::from_bytes(bytes)?,
+            },
+            27 => Self::Enchantable(VarInt::from_bytes(bytes)?),
+            28 => Self::Equippable {
+                slot: VarInt::from_bytes(bytes)?,
+                equip_sound: IdOr::from_bytes(bytes)?,
+                model: Option::from_bytes(bytes)?,
+                camera_overlay: Option::from_bytes(bytes)?,
+                allowed_entities: Option::from_bytes(bytes)?,
+                dispensable: bool::from_bytes(bytes)?,
+                swappable: bool::from_bytes(bytes)?,
+                damage_on_hurt: bool::from_bytes(bytes)?,
+            },
+            29 => Self::Repairable(IDSet::from_bytes(bytes)?),
+            30 => Self::Glider,
+            31 => Self::TooltipStyle(Identifier::from_bytes(bytes)?),
+            32 => Self::DeathProtection(Vec::from_bytes(bytes)?),
+            33 => Self::StoredEnchantments {
+                enchantments: Vec::from_bytes(bytes)?,
+                show_in_tooltip: bool::from_bytes(bytes)?,
+            },
+            34 => Self::DyedColor {
+                color: i32::from_bytes(bytes)?,
+                show_in_tooltip: bool::from_bytes(bytes)?,
+            },
+            35 => Self::MapColor(i32::from_bytes(bytes)?),
+            36 => Self::MapId(VarInt::from_bytes(bytes)?),
+            37 => Self::MapDecorations(Nbt::from_bytes(bytes)?),
+            38 => Self::MapPostProcessing(VarInt::from_bytes(bytes)?),
+            39 => Self::ChargedProjectiles(Slot::read_all(bytes, depth + 1)?),
+            40 => Self::BundleContents(Slot::read_all(bytes, depth + 1)?),
+            41 => Self::PotionContents {
+                potion_id: Option::from_bytes(bytes)?,
+                custom_color: Option::from_bytes(bytes)?,
+                custom_effects: Vec::from_bytes(bytes)?,
+                custom_name: Option::from_bytes(bytes)?,
+            },
+            42 => Self::SuspiciousStewEffects(Vec::from_bytes(bytes)?),
+            43 => Self::WritableBookContent(Vec::from_bytes(bytes)?),
+            44 => Self::WrittenBookContent {
+                title: Filtered::from_bytes(bytes)?,
+                author: String::from_bytes(bytes)?,
+                generation: VarInt::from_bytes(bytes)?,
+                pages: Vec::from_bytes(bytes)?,
+                resolved: bool::from_bytes(bytes)?,
+            },
+            45 => Self::Trim {
+                material: IdOr::from_bytes(bytes)?,
+                pattern: IdOr::from_bytes(bytes)?,
+                show_in_tooltip: bool::from_bytes(bytes)?,
+            },
+            46 => Self::DebugStickState(Nbt::from_bytes(bytes)?),
+            47 => Self::EntityData(Nbt::from_bytes(bytes)?),
+            48 => Self::BucketEntityData(Nbt::from_bytes(bytes)?),
+            49 => Self::BlockEntityData(Nbt::from_bytes(bytes)?),
+            50 => Self::Instrument(IdOr::from_bytes(bytes)?),
+            51 => Self::OminousBottleAmplifier(VarInt::from_bytes(bytes)?),
+            52 => Self::JukeboxPlayable {
+                song: JukeboxSongReference::from_bytes(bytes)?,
+                show_in_tooltip: bool::from_bytes(bytes)?,
+            },
+            53 => Self::Recipes(Nbt::from_bytes(bytes)?),
+            54 => Self::LodestoneTracker {
+                target: Option::from_bytes(bytes)?,
+                tracked: bool::from_bytes(bytes)?,
+            },
+            55 => Self::FireworkExplosion(FireworkExplosion::from_bytes(bytes)?),
+            56 => Self::Fireworks {
+                flight_duration: VarInt::from_bytes(bytes)?,
+                explosions: Vec::from_bytes(bytes)?,
+            },
+            57 => Self::Profile {
+                name: Option::from_bytes(bytes)?,
+                uuid: Option::from_bytes(bytes)?,
+                properties: Vec::from_bytes(bytes)?,
+            },
+            58 => Self::NoteBlockSound(Identifier::from_bytes(bytes)?),
+            59 => Self::BannerPatterns(Vec::from_bytes(bytes)?),
+            60 => Self::BaseColor(VarInt::from_bytes(bytes)?),
+            61 => Self::PotDecorations(Vec::from_bytes(bytes)?),
+            62 => Self::Container(Slot::read_all(bytes, depth + 1)?),
+            63 => Self::BlockState(Vec::from_bytes(bytes)?),
+            64 => Self::Bees(Vec::from_bytes(bytes)?),
+            65 => Self::Lock(Nbt::from_bytes(bytes)?),
+            66 => Self::ContainerLoot(Nbt::from_bytes(bytes)?),
+            _ => return Err(DatatypeError::Invalid),
+        })
+    }
+}
+
+impl Datatype<'_> for ItemComponent {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        Self::read_nested(bytes, 0)
+    }
+}
+
+impl Display for ItemComponent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())?;
+        match self {
+            ItemComponent::HideAdditionalTooltip
+            | ItemComponent::HideTooltip
+            | ItemComponent::CreativeSlotLock
+            | ItemComponent::Glider => Ok(()),
+            ItemComponent::MaxStackSize(v)
+            | ItemComponent::MaxDamage(v)
+            | ItemComponent::Damage(v)
+            | ItemComponent::Rarity(v)
+            | ItemComponent::RepairCost(v)
+            | ItemComponent::Enchantable(v)
+            | ItemComponent::MapId(v)
+            | ItemComponent::MapPostProcessing(v)
+            | ItemComponent::OminousBottleAmplifier(v)
+            | ItemComponent::BaseColor(v) => write!(f, "={v}"),
+            ItemComponent::CustomName(v) | ItemComponent::ItemName(v) => write!(f, "={v}"),
+            ItemComponent::ItemModel(v)
+            | ItemComponent::DamageResistant(v)
+            | ItemComponent::TooltipStyle(v)
+            | ItemComponent::NoteBlockSound(v) => write!(f, "={v}"),
+            ItemComponent::CustomData(v)
+            | ItemComponent::IntangibleProjectile(v)
+            | ItemComponent::MapDecorations(v)
+            | ItemComponent::DebugStickState(v)
+            | ItemComponent::EntityData(v)
+            | ItemComponent::BucketEntityData(v)
+            | ItemComponent::BlockEntityData(v)
+            | ItemComponent::Recipes(v)
+            | ItemComponent::Lock(v)
+            | ItemComponent::ContainerLoot(v) => write!(f, "={v}"),
+            ItemComponent::Lore(v) => write!(f, "=[{}]", v.iter().join(", ")),
+            ItemComponent::EnchantmentGlintOverride(v) => write!(f, "={v}"),
+            ItemComponent::MapColor(v) => write!(f, "=#{v:06X}"),
+            ItemComponent::UseRemainder(v) => write!(f, "={v}"),
+            ItemComponent::ChargedProjectiles(v)
+            | ItemComponent::BundleContents(v)
+            | ItemComponent::Container(v) => write!(f, "=[{}]", v.iter().join(", ")),
+            ItemComponent::Enchantments { enchantments, .. }
+            | ItemComponent::StoredEnchantments { enchantments, .. } => {
+                write!(f, "=[{}]", enchantments.iter().join(", "))
+            }
+            other => write!(f, "={other:?}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EnchantmentLevel {
+    pub enchantment_id: VarInt,
+    pub level: VarInt,
+}
+
+impl Datatype<'_> for EnchantmentLevel {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let enchantment_id = VarInt::from_bytes(bytes)?;
+        let level = VarInt::from_bytes(bytes)?;
+        Ok(Self {
+            enchantment_id,
+            level,
+        })
+    }
+}
+
+impl Display for EnchantmentLevel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{} lvl {}", self.enchantment_id, self.level)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BlockPredicate {
+    pub blocks: Option<IDSet>,
+    pub properties: Option<Vec<PropertyMatcher>>,
+    pub nbt: Option<Nbt>,
+}
+
+impl Datatype<'_> for BlockPredicate {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let blocks = Option::from_bytes(bytes)?;
+        let properties = Option::from_bytes(bytes)?;
+        let nbt = Option::from_bytes(bytes)?;
+        Ok(Self {
+            blocks,
+            properties,
+            nbt,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum PropertyMatcher {
+    Exact {
+        name: String,
+        value: String,
+    },
+    Range {
+        name: String,
+        min: String,
+        max: String,
+    },
+}
+
+impl Datatype<'_> for PropertyMatcher {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let name = String::from_bytes(bytes)?;
+        let is_exact = bool::from_bytes(bytes)?;
+        if is_exact {
+            let value = String::from_bytes(bytes)?;
+            Ok(Self::Exact { name, value })
+        } else {
+            let min = String::from_bytes(bytes)?;
+            let max = String::from_bytes(bytes)?;
+            Ok(Self::Range { name, min, max })
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AttributeModifier {
+    pub attribute_id: VarInt,
+    pub modifier_id: Identifier,
+    pub value: f64,
+    pub operation: VarInt,
+    pub slot: VarInt,
+}
+
+impl Datatype<'_> for AttributeModifier {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let attribute_id = VarInt::from_bytes(bytes)?;
+        let modifier_id = Identifier::from_bytes(bytes)?;
+        let value = f64::from_bytes(bytes)?;
+        let operation = VarInt::from_bytes(bytes)?;
+        let slot = VarInt::from_bytes(bytes)?;
+        Ok(Self {
+            attribute_id,
+            modifier_id,
+            value,
+            operation,
+            slot,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum ConsumeEffect {
+    ApplyEffects {
+        effects: Vec<PotionEffect>,
+        probability: f32,
+    },
+    RemoveEffects(IDSet),
+    ClearAllEffects,
+    TeleportRandomly {
+        diameter: f32,
+    },
+    PlaySound(IdOr<SoundEvent>),
+}
+
+impl Datatype<'_> for ConsumeEffect {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let effect_type = VarInt::from_bytes(bytes)?.value;
+        Ok(match effect_type {
+            0 => Self::ApplyEffects {
+                effects: Vec::from_bytes(bytes)?,
+                probability: f32::from_bytes(bytes)?,
+            },
+            1 => Self::RemoveEffects(IDSet::from_bytes(bytes)?),
+            2 => Self::ClearAllEffects,
+            3 => Self::TeleportRandomly {
+                diameter: f32::from_bytes(bytes)?,
+            },
+            4 => Self::PlaySound(IdOr::from_bytes(bytes)?),
+            _ => return Err(DatatypeError::Invalid),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ToolRule {
+    pub blocks: IDSet,
+    pub speed: Option<f32>,
+    pub correct_drop_for_blocks: Option<bool>,
+}
+
+impl Datatype<'_> for ToolRule {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let blocks = IDSet::from_bytes(bytes)?;
+        let speed = Option::from_bytes(bytes)?;
+        let correct_drop_for_blocks = Option::from_bytes(bytes)?;
+        Ok(Self {
+            blocks,
+            speed,
+            correct_drop_for_blocks,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StewEffect {
+    pub effect_id: VarInt,
+    pub duration: VarInt,
+}
+
+impl Datatype<'_> for StewEffect {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let effect_id = VarInt::from_bytes(bytes)?;
+        let duration = VarInt::from_bytes(bytes)?;
+        Ok(Self {
+            effect_id,
+            duration,
+        })
+    }
+}
+
+/// Text that may have a chat-filtered variant alongside the raw one
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Filtered<T> {
+    pub raw: T,
+    pub filtered: Option<T>,
+}
+
+impl<'a, T> Datatype<'a> for Filtered<T>
+where
+    T: Datatype<'a>,
+{
+    fn from_bytes(bytes: &mut &'a [u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let raw = T::from_bytes(bytes)?;
+        let filtered = Option::from_bytes(bytes)?;
+        Ok(Self { raw, filtered })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TrimMaterial {
+    pub asset_name: String,
+    pub ingredient: VarInt,
+    pub override_armor_assets: Vec<ArmorAssetOverride>,
+    pub description: TextComponent,
+}
+
+impl Datatype<'_> for TrimMaterial {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let asset_name = String::from_bytes(bytes)?;
+        let ingredient = VarInt::from_bytes(bytes)?;
+        let override_armor_assets = Vec::from_bytes(bytes)?;
+        let description = TextComponent::from_bytes(bytes)?;
+        Ok(Self {
+            asset_name,
+            ingredient,
+            override_armor_assets,
+            description,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ArmorAssetOverride {
+    pub armor_asset: Identifier,
+    pub asset_name: String,
+}
+
+impl Datatype<'_> for ArmorAssetOverride {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let armor_asset = Identifier::from_bytes(bytes)?;
+        let asset_name = String::from_bytes(bytes)?;
+        Ok(Self {
+            armor_asset,
+            asset_name,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TrimPattern {
+    pub asset_id: Identifier,
+    pub template_item: VarInt,
+    pub description: TextComponent,
+    pub decal: bool,
+}
+
+impl Datatype<'_> for TrimPattern {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let asset_id = Identifier::from_bytes(bytes)?;
+        let template_item = VarInt::from_bytes(bytes)?;
+        let description = TextComponent::from_bytes(bytes)?;
+        let decal = bool::from_bytes(bytes)?;
+        Ok(Self {
+            asset_id,
+            template_item,
+            description,
+            decal,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Instrument {
+    pub sound: IdOr<SoundEvent>,
+    pub use_duration: f32,
+    pub range: f32,
+    pub description: TextComponent,
+}
+
+impl Datatype<'_> for Instrument {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let sound = IdOr::from_bytes(bytes)?;
+        let use_duration = f32::from_bytes(bytes)?;
+        let range = f32::from_bytes(bytes)?;
+        let description = TextComponent::from_bytes(bytes)?;
+        Ok(Self {
+            sound,
+            use_duration,
+            range,
+            description,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JukeboxSong {
+    pub sound: IdOr<SoundEvent>,
+    pub description: TextComponent,
+    pub length_in_seconds: f32,
+    pub comparator_output: VarInt,
+}
+
+impl Datatype<'_> for JukeboxSong {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let sound = IdOr::from_bytes(bytes)?;
+        let description = TextComponent::from_bytes(bytes)?;
+        let length_in_seconds = f32::from_bytes(bytes)?;
+        let comparator_output = VarInt::from_bytes(bytes)?;
+        Ok(Self {
+            sound,
+            description,
+            length_in_seconds,
+            comparator_output,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum JukeboxSongReference {
+    Direct(IdOr<JukeboxSong>),
+    Named(Identifier),
+}
+
+impl Datatype<'_> for JukeboxSongReference {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let direct = bool::from_bytes(bytes)?;
+        if direct {
+            Ok(Self::Direct(IdOr::from_bytes(bytes)?))
+        } else {
+            Ok(Self::Named(Identifier::from_bytes(bytes)?))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FireworkExplosion {
+    pub shape: VarInt,
+    pub colors: Vec<i32>,
+    pub fade_colors: Vec<i32>,
+    pub has_trail: bool,
+    pub has_twinkle: bool,
+}
+
+impl Datatype<'_> for FireworkExplosion {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let shape = VarInt::from_bytes(bytes)?;
+        let colors = Vec::from_bytes(bytes)?;
+        let fade_colors = Vec::from_bytes(bytes)?;
+        let has_trail = bool::from_bytes(bytes)?;
+        let has_twinkle = bool::from_bytes(bytes)?;
+        Ok(Self {
+            shape,
+            colors,
+            fade_colors,
+            has_trail,
+            has_twinkle,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BannerPattern {
+    pub asset_id: Identifier,
+    pub translation_key: String,
+}
+
+impl Datatype<'_> for BannerPattern {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let asset_id = Identifier::from_bytes(bytes)?;
+        let translation_key = String::from_bytes(bytes)?;
+        Ok(Self {
+            asset_id,
+            translation_key,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BannerLayer {
+    pub pattern: IdOr<BannerPattern>,
+    pub color: VarInt,
+}
+
+impl Datatype<'_> for BannerLayer {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let pattern = IdOr::from_bytes(bytes)?;
+        let color = VarInt::from_bytes(bytes)?;
+        Ok(Self { pattern, color })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BlockStateProperty {
+    pub name: String,
+    pub value: String,
+}
+
+impl Datatype<'_> for BlockStateProperty {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let name = String::from_bytes(bytes)?;
+        let value = String::from_bytes(bytes)?;
+        Ok(Self { name, value })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BeeData {
+    pub entity_data: Nbt,
+    pub ticks_in_hive: VarInt,
+    pub min_ticks_in_hive: VarInt,
+}
+
+impl Datatype<'_> for BeeData {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let entity_data = Nbt::from_bytes(bytes)?;
+        let ticks_in_hive = VarInt::from_bytes(bytes)?;
+        let min_ticks_in_hive = VarInt::from_bytes(bytes)?;
+        Ok(Self {
+            entity_data,
+            ticks_in_hive,
+            min_ticks_in_hive,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn identifier(s: &str) -> Vec<u8> {
+        let mut bytes = vec![s.len() as u8];
+        bytes.extend(s.as_bytes());
+        bytes
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(ItemComponent::name_from_id(0), "minecraft:custom_data");
+        assert_eq!(ItemComponent::name_from_id(66), "minecraft:container_loot");
+        assert_eq!(ItemComponent::name_from_id(67), "unknown(67)");
+        assert_eq!(ItemComponent::Glider.name(), "minecraft:glider");
+    }
+
+    #[test]
+    fn food_and_consumable() {
+        let mut bytes: &[u8] = &[0x15, 0x04, 0x3F, 0x80, 0x00, 0x00, 0x01];
+        assert_eq!(
+            ItemComponent::from_bytes(&mut bytes).unwrap(),
+            ItemComponent::Food {
+                nutrition: VarInt { value: 4 },
+                saturation_modifier: 1.0,
+                can_always_eat: true,
+            }
+        );
+
+        let mut data = vec![0x16, 0x3F, 0xCC, 0xCC, 0xCD, 0x01, 0x00];
+        data.extend(identifier("minecraft:entity.generic.eat"));
+        data.extend([
+            0x00, // no fixed range
+            0x01, // has particles
+            0x02, // 2 effects
+            0x02, // clear all effects
+            0x03, 0x41, 0x80, 0x00, 0x00, // teleport randomly 16.0
+        ]);
+        let mut bytes = &data[..];
+        let component = ItemComponent::from_bytes(&mut bytes).unwrap();
+        assert!(bytes.is_empty());
+        let ItemComponent::Consumable { sound, effects, .. } = component else {
+            panic!("Expected consumable, got {component:?}");
+        };
+        assert_eq!(sound.to_string(), "minecraft:entity.generic.eat");
+        assert_eq!(
+            effects,
+            vec![
+                ConsumeEffect::ClearAllEffects,
+                ConsumeEffect::TeleportRandomly { diameter: 16.0 }
+            ]
+        );
+    }
+
+    #[test]
+    fn enchantments() {
+        let mut bytes: &[u8] = &[0x0A, 0x02, 0x05, 0x03, 0x0C, 0x01, 0x01];
+        let component = ItemComponent::from_bytes(&mut bytes).unwrap();
+        assert_eq!(
+            component.to_string(),
+            "minecraft:enchantments=[#5 lvl 3, #12 lvl 1]"
+        );
+    }
+
+    #[test]
+    fn unknown_component() {
+        let mut bytes: &[u8] = &[0x50, 0x00];
+        assert!(ItemComponent::from_bytes(&mut bytes).is_err());
+    }
+}
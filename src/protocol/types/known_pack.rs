@@ -1,15 +1,16 @@
-use std::collections::VecDeque;
+use super::error::DatatypeError;
+use serde::Serialize;
 use crate::protocol::types::Datatype;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct KnownPack {
     namespace: String,
     id: String,
     version: String,
 }
 
-impl Datatype for KnownPack {
-    fn from_bytes(bytes: &mut VecDeque<u8>) -> color_eyre::Result<Self>
+impl Datatype<'_> for KnownPack {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
     where
         Self: Sized
     {
@@ -0,0 +1,95 @@
+use super::error::DatatypeError;
+use crate::protocol::types::{BitSet, Datatype};
+use serde::Serialize;
+
+/// Sky and block light for a chunk column.
+///
+/// Bit `i` of a mask refers to section `i - 1` counted from the bottom of the world,
+/// since light is also tracked one section below and above the world.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LightData {
+    pub sky_light_mask: BitSet,
+    pub block_light_mask: BitSet,
+    pub empty_sky_light_mask: BitSet,
+    pub empty_block_light_mask: BitSet,
+    /// One 2048 byte nibble array per set bit in `sky_light_mask`, in order
+    pub sky_light_arrays: Vec<Vec<u8>>,
+    /// One 2048 byte nibble array per set bit in `block_light_mask`, in order
+    pub block_light_arrays: Vec<Vec<u8>>,
+}
+
+impl LightData {
+    pub const ARRAY_LENGTH: usize = 2048;
+
+    /// Pairs each sky light array with its light section index
+    pub fn sky_light(&self) -> impl Iterator<Item = (usize, &Vec<u8>)> {
+        self.sky_light_mask.ones().zip(&self.sky_light_arrays)
+    }
+
+    /// Pairs each block light array with its light section index
+    pub fn block_light(&self) -> impl Iterator<Item = (usize, &Vec<u8>)> {
+        self.block_light_mask.ones().zip(&self.block_light_arrays)
+    }
+
+    /// Reads the light level at section-local coordinates from a nibble array
+    pub fn level(array: &[u8], x: usize, y: usize, z: usize) -> Option<u8> {
+        let index = (y * 16 + z) * 16 + x;
+        let byte = array.get(index / 2)?;
+        Some(if index.is_multiple_of(2) {
+            byte & 0x0F
+        } else {
+            byte >> 4
+        })
+    }
+}
+
+impl Datatype<'_> for LightData {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let sky_light_mask = BitSet::from_bytes(bytes)?;
+        let block_light_mask = BitSet::from_bytes(bytes)?;
+        let empty_sky_light_mask = BitSet::from_bytes(bytes)?;
+        let empty_block_light_mask = BitSet::from_bytes(bytes)?;
+        let sky_light_arrays = Vec::from_bytes(bytes)?;
+        let block_light_arrays = Vec::from_bytes(bytes)?;
+
+        Ok(Self {
+            sky_light_mask,
+            block_light_mask,
+            empty_sky_light_mask,
+            empty_block_light_mask,
+            sky_light_arrays,
+            block_light_arrays,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn light_data() {
+        let mut data = vec![0x01];
+        data.extend(0b100i64.to_be_bytes()); // sky light for light section 2
+        data.extend([0x00, 0x00, 0x00]); // no block light, nothing empty
+        data.extend([0x01, 0x80, 0x10]); // 1 sky light array of 2048 bytes
+        let mut array = vec![0xFFu8; LightData::ARRAY_LENGTH];
+        array[0] = 0x5A;
+        data.extend(array);
+        data.extend([0x00]); // no block light arrays
+        let mut bytes = &data[..];
+
+        let light = LightData::from_bytes(&mut bytes).unwrap();
+        assert!(bytes.is_empty());
+        let sky_light = light.sky_light().collect::<Vec<_>>();
+        assert_eq!(sky_light.len(), 1);
+        assert_eq!(sky_light[0].0, 2);
+        assert_eq!(LightData::level(sky_light[0].1, 0, 0, 0), Some(0xA));
+        assert_eq!(LightData::level(sky_light[0].1, 1, 0, 0), Some(0x5));
+        assert_eq!(LightData::level(sky_light[0].1, 15, 15, 15), Some(0xF));
+        assert_eq!(light.block_light().count(), 0);
+    }
+}
@@ -8,8 +8,28 @@ mod player_property;
 mod vec;
 mod identifier;
 mod known_pack;
+mod position;
+mod angle;
+mod bit_set;
+mod option;
+mod id_set;
+mod teleport_flags;
+mod vec3;
+mod fixed_point;
+mod nbt;
+mod text_component;
+mod id_or;
+mod sound_event;
+mod potion_effect;
+mod slot;
+mod item_component;
+mod particle;
+mod entity_type;
+mod entity_metadata;
+mod chunk;
+mod light;
 
-use std::collections::VecDeque;
+use error::DatatypeError;
 
 pub use var_num::*;
 pub use primitives::*;
@@ -18,15 +38,35 @@ pub use player_property::*;
 pub use identifier::*;
 pub use vec::*;
 pub use known_pack::*;
+pub use position::*;
+pub use angle::*;
+pub use bit_set::*;
+pub use id_set::*;
+pub use teleport_flags::*;
+pub use vec3::*;
+pub use fixed_point::*;
+pub use nbt::*;
+pub use text_component::*;
+pub use id_or::*;
+pub use sound_event::*;
+pub use potion_effect::*;
+pub use slot::*;
+pub use item_component::*;
+pub use particle::*;
+pub use entity_type::*;
+pub use entity_metadata::*;
+pub use chunk::*;
+pub use light::*;
 
-pub trait Datatype {
-    fn from_bytes(bytes: &mut VecDeque<u8>) -> color_eyre::Result<Self>
+/// Read from the front of `bytes`, which is advanced past it. `'a` lets types borrow from the packet.
+pub trait Datatype<'a> {
+    fn from_bytes(bytes: &mut &'a [u8]) -> Result<Self, DatatypeError>
     where
         Self: Sized;
 }
 
-pub trait MinecraftArray {
-    fn from_array_bytes(len: usize, bytes: &mut VecDeque<u8>) -> color_eyre::Result<Self>
+pub trait MinecraftArray<'a> {
+    fn from_array_bytes(len: usize, bytes: &mut &'a [u8]) -> Result<Self, DatatypeError>
     where
         Self: Sized;
 }
\ No newline at end of file
@@ -0,0 +1,365 @@
+use crate::protocol::types::error::DatatypeError;
+use crate::protocol::types::{read_bytes, Datatype, MinecraftArray};
+use itertools::Itertools;
+use serde::{Serialize, Serializer};
+use std::fmt::{Display, Formatter};
+
+/// Named Binary Tag, as sent over the network (the root tag has no name)
+#[derive(Debug, Clone, PartialEq)]
+pub enum Nbt {
+    End,
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    List(Vec<Nbt>),
+    Compound(Vec<(String, Nbt)>),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+impl Nbt {
+    /// Guards against malicious input blowing the stack
+    const MAX_DEPTH: usize = 512;
+
+    pub fn get(&self, key: &str) -> Option<&Nbt> {
+        match self {
+            Nbt::Compound(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Nbt::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Nbt::Byte(v) => Some(v as i64),
+            Nbt::Short(v) => Some(v as i64),
+            Nbt::Int(v) => Some(v as i64),
+            Nbt::Long(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn is_end(&self) -> bool {
+        matches!(self, Nbt::End)
+    }
+
+    pub fn tag_id(&self) -> u8 {
+        match self {
+            Nbt::End => 0,
+            Nbt::Byte(_) => 1,
+            Nbt::Short(_) => 2,
+            Nbt::Int(_) => 3,
+            Nbt::Long(_) => 4,
+            Nbt::Float(_) => 5,
+            Nbt::Double(_) => 6,
+            Nbt::ByteArray(_) => 7,
+            Nbt::String(_) => 8,
+            Nbt::List(_) => 9,
+            Nbt::Compound(_) => 10,
+            Nbt::IntArray(_) => 11,
+            Nbt::LongArray(_) => 12,
+        }
+    }
+
+    /// Encodes the tag the way it is sent over the network, with a nameless root
+    pub fn write(&self, out: &mut Vec<u8>) {
+        out.push(self.tag_id());
+        self.write_payload(out);
+    }
+
+    /// Encodes the tag the way it is stored on disk, with a named root
+    pub fn write_named(&self, name: &str, out: &mut Vec<u8>) {
+        out.push(self.tag_id());
+        Self::write_string(name, out);
+        self.write_payload(out);
+    }
+
+    fn write_string(s: &str, out: &mut Vec<u8>) {
+        let mut data = Vec::with_capacity(s.len());
+        for unit in s.encode_utf16() {
+            match unit {
+                0x0001..=0x007F => data.push(unit as u8),
+                0x0000 | 0x0080..=0x07FF => {
+                    data.push(0xC0 | (unit >> 6) as u8);
+                    data.push(0x80 | (unit & 0x3F) as u8);
+                }
+                _ => {
+                    data.push(0xE0 | (unit >> 12) as u8);
+                    data.push(0x80 | ((unit >> 6) & 0x3F) as u8);
+                    data.push(0x80 | (unit & 0x3F) as u8);
+                }
+            }
+        }
+        out.extend((data.len().min(u16::MAX as usize) as u16).to_be_bytes());
+        out.extend(data.into_iter().take(u16::MAX as usize));
+    }
+
+    fn write_payload(&self, out: &mut Vec<u8>) {
+        match self {
+            Nbt::End => {}
+            Nbt::Byte(v) => out.push(*v as u8),
+            Nbt::Short(v) => out.extend(v.to_be_bytes()),
+            Nbt::Int(v) => out.extend(v.to_be_bytes()),
+            Nbt::Long(v) => out.extend(v.to_be_bytes()),
+            Nbt::Float(v) => out.extend(v.to_be_bytes()),
+            Nbt::Double(v) => out.extend(v.to_be_bytes()),
+            Nbt::ByteArray(v) => {
+                out.extend((v.len() as i32).to_be_bytes());
+                out.extend(v.iter().map(|x| *x as u8));
+            }
+            Nbt::String(v) => Self::write_string(v, out),
+            Nbt::List(v) => {
+                // An empty list has no element type, which is written as End
+                out.push(v.first().map_or(0, Nbt::tag_id));
+                out.extend((v.len() as i32).to_be_bytes());
+                for element in v {
+                    element.write_payload(out);
+                }
+            }
+            Nbt::Compound(v) => {
+                for (name, value) in v {
+                    out.push(value.tag_id());
+                    Self::write_string(name, out);
+                    value.write_payload(out);
+                }
+                out.push(0);
+            }
+            Nbt::IntArray(v) => {
+                out.extend((v.len() as i32).to_be_bytes());
+                v.iter().for_each(|x| out.extend(x.to_be_bytes()));
+            }
+            Nbt::LongArray(v) => {
+                out.extend((v.len() as i32).to_be_bytes());
+                v.iter().for_each(|x| out.extend(x.to_be_bytes()));
+            }
+        }
+    }
+
+    fn read_length(bytes: &mut &[u8]) -> Result<usize, DatatypeError> {
+        let length = i32::from_bytes(bytes)?;
+        if length < 0 {
+            return Err(DatatypeError::Invalid);
+        }
+        Ok(length as usize)
+    }
+
+    /// Strings are Java's modified UTF-8 with an unsigned short length
+    fn read_string(bytes: &mut &[u8]) -> Result<String, DatatypeError> {
+        let length = u16::from_bytes(bytes)? as usize;
+        let data = read_bytes(bytes, length)?;
+        if let Ok(s) = std::str::from_utf8(data) {
+            return Ok(s.to_string());
+        }
+
+        // Decode to UTF-16 units first so surrogate pairs and overlong nulls come out right
+        let mut units = Vec::with_capacity(length);
+        let mut iter = data.iter().copied();
+        while let Some(a) = iter.next() {
+            let a = a as u16;
+            if a & 0x80 == 0 {
+                units.push(a);
+            } else if a & 0xE0 == 0xC0 {
+                let b = iter.next().unwrap_or(0) as u16;
+                units.push(((a & 0x1F) << 6) | (b & 0x3F));
+            } else {
+                let b = iter.next().unwrap_or(0) as u16;
+                let c = iter.next().unwrap_or(0) as u16;
+                units.push(((a & 0x0F) << 12) | ((b & 0x3F) << 6) | (c & 0x3F));
+            }
+        }
+        Ok(String::from_utf16_lossy(&units))
+    }
+
+    fn read_payload(
+        tag_id: u8,
+        bytes: &mut &[u8],
+        depth: usize,
+    ) -> Result<Self, DatatypeError> {
+        if depth > Self::MAX_DEPTH {
+            return Err(DatatypeError::TooBig("NBT depth"));
+        }
+
+        Ok(match tag_id {
+            0 => Nbt::End,
+            1 => Nbt::Byte(i8::from_bytes(bytes)?),
+            2 => Nbt::Short(i16::from_bytes(bytes)?),
+            3 => Nbt::Int(i32::from_bytes(bytes)?),
+            4 => Nbt::Long(i64::from_bytes(bytes)?),
+            5 => Nbt::Float(f32::from_bytes(bytes)?),
+            6 => Nbt::Double(f64::from_bytes(bytes)?),
+            7 => {
+                let length = Self::read_length(bytes)?;
+                Nbt::ByteArray(Vec::from_array_bytes(length, bytes)?)
+            }
+            8 => Nbt::String(Self::read_string(bytes)?),
+            9 => {
+                let element_id = u8::from_bytes(bytes)?;
+                let length = Self::read_length(bytes)?;
+                let mut list = Vec::with_capacity(length.min(bytes.len()));
+                for _ in 0..length {
+                    list.push(Self::read_payload(element_id, bytes, depth + 1)?);
+                }
+                Nbt::List(list)
+            }
+            10 => {
+                let mut entries = vec![];
+                loop {
+                    let entry_id = u8::from_bytes(bytes)?;
+                    if entry_id == 0 {
+                        break;
+                    }
+                    let name = Self::read_string(bytes)?;
+                    let value = Self::read_payload(entry_id, bytes, depth + 1)?;
+                    entries.push((name, value));
+                }
+                Nbt::Compound(entries)
+            }
+            11 => {
+                let length = Self::read_length(bytes)?;
+                Nbt::IntArray(Vec::from_array_bytes(length, bytes)?)
+            }
+            12 => {
+                let length = Self::read_length(bytes)?;
+                Nbt::LongArray(Vec::from_array_bytes(length, bytes)?)
+            }
+            _ => return Err(DatatypeError::Invalid),
+        })
+    }
+}
+
+impl Datatype<'_> for Nbt {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let tag_id = u8::from_bytes(bytes)?;
+        Self::read_payload(tag_id, bytes, 0)
+    }
+}
+
+impl Display for Nbt {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Nbt::End => write!(f, "END"),
+            Nbt::Byte(v) => write!(f, "{v}b"),
+            Nbt::Short(v) => write!(f, "{v}s"),
+            Nbt::Int(v) => write!(f, "{v}"),
+            Nbt::Long(v) => write!(f, "{v}L"),
+            Nbt::Float(v) => write!(f, "{v}f"),
+            Nbt::Double(v) => write!(f, "{v}d"),
+            Nbt::ByteArray(v) => write!(f, "[B; {}]", v.iter().join(", ")),
+            Nbt::String(v) => write!(f, "{v:?}"),
+            Nbt::List(v) => write!(f, "[{}]", v.iter().join(", ")),
+            Nbt::Compound(v) => write!(
+                f,
+                "{{{}}}",
+                v.iter().map(|(k, v)| format!("{k}: {v}")).join(", ")
+            ),
+            Nbt::IntArray(v) => write!(f, "[I; {}]", v.iter().join(", ")),
+            Nbt::LongArray(v) => write!(f, "[L; {}]", v.iter().join(", ")),
+        }
+    }
+}
+
+/// Serializes as the plain value, with compounds as maps (key order kept)
+impl Serialize for Nbt {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Nbt::End => serializer.serialize_unit(),
+            Nbt::Byte(v) => serializer.serialize_i8(*v),
+            Nbt::Short(v) => serializer.serialize_i16(*v),
+            Nbt::Int(v) => serializer.serialize_i32(*v),
+            Nbt::Long(v) => serializer.serialize_i64(*v),
+            Nbt::Float(v) => serializer.serialize_f32(*v),
+            Nbt::Double(v) => serializer.serialize_f64(*v),
+            Nbt::ByteArray(v) => v.serialize(serializer),
+            Nbt::String(v) => serializer.serialize_str(v),
+            Nbt::List(v) => v.serialize(serializer),
+            Nbt::Compound(v) => serializer.collect_map(v.iter().map(|(k, v)| (k, v))),
+            Nbt::IntArray(v) => v.serialize(serializer),
+            Nbt::LongArray(v) => v.serialize(serializer),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn compound() {
+        let mut bytes: &[u8] = &[
+            0x0A, // Root compound, no name
+            0x08, 0x00, 0x04, b't', b'e', b'x', b't', 0x00, 0x02, b'h', b'i', // text: "hi"
+            0x09, 0x00, 0x01, b'l', 0x03, 0x00, 0x00, 0x00, 0x02, // l: [Int; 2]
+            0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x02, //
+            0x0C, 0x00, 0x01, b'a', 0x00, 0x00, 0x00, 0x01, // a: [L; 1]
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x07, //
+            0x00, // End
+        ];
+        let nbt = Nbt::from_bytes(&mut bytes).unwrap();
+        assert!(bytes.is_empty());
+        assert_eq!(nbt.get("text").and_then(Nbt::as_str), Some("hi"));
+        assert_eq!(
+            nbt.get("l"),
+            Some(&Nbt::List(vec![Nbt::Int(1), Nbt::Int(2)]))
+        );
+        assert_eq!(nbt.to_string(), r#"{text: "hi", l: [1, 2], a: [L; 7]}"#);
+    }
+
+    #[test]
+    fn round_trip() {
+        let nbt = Nbt::Compound(vec![
+            ("name".to_string(), Nbt::String("a\0\u{1F600}".to_string())),
+            ("empty".to_string(), Nbt::List(vec![])),
+            ("bytes".to_string(), Nbt::ByteArray(vec![-1, 2])),
+            (
+                "nested".to_string(),
+                Nbt::Compound(vec![("d".to_string(), Nbt::Double(0.5))]),
+            ),
+        ]);
+        let mut out = vec![];
+        nbt.write(&mut out);
+        assert_eq!(Nbt::from_bytes(&mut &out[..]).unwrap(), nbt);
+
+        let mut out = vec![];
+        Nbt::Int(7).write_named("x", &mut out);
+        assert_eq!(out, [0x03, 0x00, 0x01, b'x', 0x00, 0x00, 0x00, 0x07]);
+    }
+
+    #[test]
+    fn string_root() {
+        let mut bytes: &[u8] = &[0x08, 0x00, 0x03, b'a', 0xC0, 0x80];
+        assert_eq!(
+            Nbt::from_bytes(&mut bytes).unwrap(),
+            Nbt::String("a\0".to_string())
+        );
+    }
+
+    #[test]
+    fn end_root() {
+        let mut bytes: &[u8] = &[0x00];
+        assert!(Nbt::from_bytes(&mut bytes).unwrap().is_end());
+    }
+
+    #[test]
+    fn too_deep() {
+        let mut data = vec![0x09, 0x09, 0x00, 0x00, 0x00, 0x01];
+        for _ in 0..1000 {
+            data.extend([0x09, 0x00, 0x00, 0x00, 0x01]);
+        }
+        let mut bytes = &data[..];
+        assert!(Nbt::from_bytes(&mut bytes).is_err());
+    }
+}
@@ -0,0 +1,32 @@
+use super::error::DatatypeError;
+use crate::protocol::types::Datatype;
+
+/// Boolean-prefixed optional value
+impl<'a, T> Datatype<'a> for Option<T>
+where
+    T: Datatype<'a>,
+{
+    fn from_bytes(bytes: &mut &'a [u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let is_present = bool::from_bytes(bytes)?;
+        if is_present {
+            Ok(Some(T::from_bytes(bytes)?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn option() {
+        let mut bytes: &[u8] = &[0x00, 0x01, 0x00, 0x2A];
+        assert_eq!(Option::<u16>::from_bytes(&mut bytes).unwrap(), None);
+        assert_eq!(Option::<u16>::from_bytes(&mut bytes).unwrap(), Some(42));
+    }
+}
@@ -0,0 +1,274 @@
+use crate::protocol::types::error::DatatypeError;
+use crate::protocol::types::{Datatype, Position, Slot, VarInt, Vec3};
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Particle {
+    pub particle_id: VarInt,
+    pub data: ParticleData,
+}
+
+impl Particle {
+    const NAMES: [&'static str; 112] = [
+        "angry_villager",
+        "block",
+        "block_marker",
+        "bubble",
+        "cloud",
+        "crit",
+        "damage_indicator",
+        "dragon_breath",
+        "dripping_lava",
+        "falling_lava",
+        "landing_lava",
+        "dripping_water",
+        "falling_water",
+        "dust",
+        "dust_color_transition",
+        "effect",
+        "elder_guardian",
+        "enchanted_hit",
+        "enchant",
+        "end_rod",
+        "entity_effect",
+        "explosion_emitter",
+        "explosion",
+        "gust",
+        "small_gust",
+        "gust_emitter_large",
+        "gust_emitter_small",
+        "sonic_boom",
+        "falling_dust",
+        "firework",
+        "fishing",
+        "flame",
+        "infested",
+        "cherry_leaves",
+        "pale_oak_leaves",
+        "sculk_soul",
+        "sculk_charge",
+        "sculk_charge_pop",
+        "soul_fire_flame",
+        "soul",
+        "flash",
+        "happy_villager",
+        "composter",
+        "heart",
+        "instant_effect",
+        "item",
+        "vibration",
+        "trail",
+        "item_slime",
+        "item_cobweb",
+        "item_snowball",
+        "large_smoke",
+        "lava",
+        "mycelium",
+        "note",
+        "poof",
+        "portal",
+        "rain",
+        "smoke",
+        "white_smoke",
+        "sneeze",
+        "spit",
+        "squid_ink",
+        "sweep_attack",
+        "totem_of_undying",
+        "underwater",
+        "splash",
+        "witch",
+        "bubble_pop",
+        "current_down",
+        "bubble_column_up",
+        "nautilus",
+        "dolphin",
+        "campfire_cosy_smoke",
+        "campfire_signal_smoke",
+        "dripping_honey",
+        "falling_honey",
+        "landing_honey",
+        "falling_nectar",
+        "falling_spore_blossom",
+        "ash",
+        "crimson_spore",
+        "warped_spore",
+        "spore_blossom_air",
+        "dripping_obsidian_tear",
+        "falling_obsidian_tear",
+        "landing_obsidian_tear",
+        "reverse_portal",
+        "white_ash",
+        "small_flame",
+        "snowflake",
+        "dripping_dripstone_lava",
+        "falling_dripstone_lava",
+        "dripping_dripstone_water",
+        "falling_dripstone_water",
+        "glow_squid_ink",
+        "glow",
+        "wax_on",
+        "wax_off",
+        "electric_spark",
+        "scrape",
+        "shriek",
+        "egg_crack",
+        "dust_plume",
+        "trial_spawner_detection",
+        "trial_spawner_detection_ominous",
+        "vault_connection",
+        "dust_pillar",
+        "ominous_spawning",
+        "raid_omen",
+        "trial_omen",
+        "block_crumble",
+    ];
+
+    pub fn name(&self) -> String {
+        match usize::try_from(self.particle_id.value)
+            .ok()
+            .and_then(|i| Self::NAMES.get(i))
+        {
+            Some(name) => format!("minecraft:{name}"),
+            None => format!("unknown({})", self.particle_id),
+        }
+    }
+}
+
+impl Datatype<'_> for Particle {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let particle_id = VarInt::from_bytes(bytes)?;
+        let data = match particle_id.value {
+            // block, block_marker, falling_dust, dust_pillar, block_crumble
+            1 | 2 | 28 | 107 | 111 => ParticleData::BlockState(VarInt::from_bytes(bytes)?),
+            13 => ParticleData::Dust {
+                color: i32::from_bytes(bytes)?,
+                scale: f32::from_bytes(bytes)?,
+            },
+            14 => ParticleData::DustColorTransition {
+                from_color: i32::from_bytes(bytes)?,
+                to_color: i32::from_bytes(bytes)?,
+                scale: f32::from_bytes(bytes)?,
+            },
+            20 => ParticleData::Color(i32::from_bytes(bytes)?),
+            36 => ParticleData::SculkCharge {
+                roll: f32::from_bytes(bytes)?,
+            },
+            45 => ParticleData::Item(Slot::from_bytes(bytes)?),
+            46 => ParticleData::Vibration {
+                source: PositionSource::from_bytes(bytes)?,
+                ticks: VarInt::from_bytes(bytes)?,
+            },
+            47 => ParticleData::Trail {
+                target: Vec3::from_bytes(bytes)?,
+                color: i32::from_bytes(bytes)?,
+                duration: VarInt::from_bytes(bytes)?,
+            },
+            101 => ParticleData::Shriek {
+                delay: VarInt::from_bytes(bytes)?,
+            },
+            _ => ParticleData::None,
+        };
+
+        Ok(Self { particle_id, data })
+    }
+}
+
+impl Display for Particle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())?;
+        match &self.data {
+            ParticleData::None => Ok(()),
+            ParticleData::BlockState(state) => write!(f, "[block state {state}]"),
+            ParticleData::Item(slot) => write!(f, "[{slot}]"),
+            other => write!(f, "[{other:?}]"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum ParticleData {
+    None,
+    BlockState(VarInt),
+    Dust {
+        color: i32,
+        scale: f32,
+    },
+    DustColorTransition {
+        from_color: i32,
+        to_color: i32,
+        scale: f32,
+    },
+    Color(i32),
+    SculkCharge {
+        roll: f32,
+    },
+    Item(Slot),
+    Vibration {
+        source: PositionSource,
+        ticks: VarInt,
+    },
+    Trail {
+        target: Vec3,
+        color: i32,
+        duration: VarInt,
+    },
+    Shriek {
+        delay: VarInt,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum PositionSource {
+    Block(Position),
+    Entity { entity_id: VarInt, eye_height: f32 },
+}
+
+impl Datatype<'_> for PositionSource {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let source_type = VarInt::from_bytes(bytes)?.value;
+        match source_type {
+            0 => Ok(Self::Block(Position::from_bytes(bytes)?)),
+            1 => Ok(Self::Entity {
+                entity_id: VarInt::from_bytes(bytes)?,
+                eye_height: f32::from_bytes(bytes)?,
+            }),
+            _ => Err(DatatypeError::Invalid),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn dust() {
+        let mut bytes: &[u8] = &[0x0D, 0x00, 0xFF, 0x00, 0x00, 0x3F, 0x80, 0x00, 0x00];
+        let particle = Particle::from_bytes(&mut bytes).unwrap();
+        assert!(bytes.is_empty());
+        assert_eq!(
+            particle.data,
+            ParticleData::Dust {
+                color: 0xFF0000,
+                scale: 1.0
+            }
+        );
+        assert_eq!(particle.name(), "minecraft:dust");
+    }
+
+    #[test]
+    fn no_data() {
+        let mut bytes: &[u8] = &[0x2B, 0x01];
+        let particle = Particle::from_bytes(&mut bytes).unwrap();
+        assert_eq!(particle.to_string(), "minecraft:heart");
+        assert_eq!(bytes.len(), 1);
+    }
+}
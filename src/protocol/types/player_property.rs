@@ -1,28 +1,23 @@
-use std::collections::VecDeque;
+use super::error::DatatypeError;
 use std::fmt::{Display, Formatter};
+use serde::Serialize;
 use crate::protocol::types::Datatype;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct PlayerProperty {
     name: String,
     value: String,
     signature: Option<String>
 }
 
-impl Datatype for PlayerProperty {
-    fn from_bytes(bytes: &mut VecDeque<u8>) -> color_eyre::Result<Self>
+impl Datatype<'_> for PlayerProperty {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
     where
         Self: Sized
     {
         let name = String::from_bytes(bytes)?;
         let value = String::from_bytes(bytes)?;
-
-        let sig_is_present = bool::from_bytes(bytes)?;
-        let signature = if sig_is_present {
-            Some(String::from_bytes(bytes)?)
-        } else {
-            None
-        };
+        let signature = Option::from_bytes(bytes)?;
 
         Ok(Self {
             name,
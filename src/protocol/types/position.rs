@@ -0,0 +1,89 @@
+use super::error::DatatypeError;
+use crate::protocol::types::{Datatype, Identifier};
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+
+/// Block position packed into a single Long: x (26 bits), z (26 bits), y (12 bits)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub struct Position {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl Datatype<'_> for Position {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let value = i64::from_bytes(bytes)?;
+
+        // Arithmetic shifts keep the sign of each component
+        let x = (value >> 38) as i32;
+        let y = (value << 52 >> 52) as i32;
+        let z = (value << 26 >> 38) as i32;
+
+        Ok(Self { x, y, z })
+    }
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}, {}, {})", self.x, self.y, self.z)
+    }
+}
+
+/// Position together with the dimension it is in
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GlobalPosition {
+    pub dimension: Identifier,
+    pub position: Position,
+}
+
+impl Datatype<'_> for GlobalPosition {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let dimension = Identifier::from_bytes(bytes)?;
+        let position = Position::from_bytes(bytes)?;
+        Ok(Self {
+            dimension,
+            position,
+        })
+    }
+}
+
+impl Display for GlobalPosition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} in {}", self.position, self.dimension)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn decode() {
+        // Example from the protocol documentation: (18357644, 831, -20882616)
+        let mut bytes: &[u8] = &[0x46, 0x07, 0x63, 0x2C, 0x15, 0xB4, 0x83, 0x3F];
+        let position = Position::from_bytes(&mut bytes).unwrap();
+        assert_eq!(
+            position,
+            Position {
+                x: 18357644,
+                y: 831,
+                z: -20882616
+            }
+        );
+    }
+
+    #[test]
+    fn negative() {
+        // x = -1, z = -2, y = -64
+        let mut bytes: &[u8] = &[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xEF, 0xC0];
+        let position = Position::from_bytes(&mut bytes).unwrap();
+        assert_eq!(position, Position { x: -1, y: -64, z: -2 });
+    }
+}
@@ -0,0 +1,58 @@
+use super::error::DatatypeError;
+use crate::protocol::types::{Datatype, VarInt};
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PotionEffect {
+    pub effect_id: VarInt,
+    pub details: PotionEffectDetails,
+}
+
+impl Datatype<'_> for PotionEffect {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let effect_id = VarInt::from_bytes(bytes)?;
+        let details = PotionEffectDetails::from_bytes(bytes)?;
+        Ok(Self { effect_id, details })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PotionEffectDetails {
+    pub amplifier: VarInt,
+    pub duration: VarInt,
+    pub ambient: bool,
+    pub show_particles: bool,
+    pub show_icon: bool,
+    /// Weaker effect of the same type that resumes once this one runs out
+    pub hidden_effect: Option<Box<PotionEffectDetails>>,
+}
+
+impl Datatype<'_> for PotionEffectDetails {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let amplifier = VarInt::from_bytes(bytes)?;
+        let duration = VarInt::from_bytes(bytes)?;
+        let ambient = bool::from_bytes(bytes)?;
+        let show_particles = bool::from_bytes(bytes)?;
+        let show_icon = bool::from_bytes(bytes)?;
+        let hidden_effect = if bool::from_bytes(bytes)? {
+            Some(Box::new(Self::from_bytes(bytes)?))
+        } else {
+            None
+        };
+
+        Ok(Self {
+            amplifier,
+            duration,
+            ambient,
+            show_particles,
+            show_icon,
+            hidden_effect,
+        })
+    }
+}
@@ -1,59 +1,146 @@
 use super::Datatype;
 use crate::protocol::types::error::DatatypeError;
 use itertools::Itertools;
-use std::collections::VecDeque;
-use color_eyre::eyre::OptionExt;
 
-impl Datatype for u16 {
-    fn from_bytes(bytes: &mut VecDeque<u8>) -> color_eyre::Result<Self>
+/// Takes exactly `N` bytes from the front of the buffer, leaving it untouched if there aren't enough
+pub(crate) fn read_array<const N: usize>(bytes: &mut &[u8]) -> Result<[u8; N], DatatypeError> {
+    let (array, rest) = bytes
+        .split_first_chunk()
+        .ok_or(DatatypeError::NotEnoughData)?;
+    *bytes = rest;
+    Ok(*array)
+}
+
+/// Takes `len` bytes from the front of the buffer without copying them
+pub(crate) fn read_bytes<'a>(bytes: &mut &'a [u8], len: usize) -> Result<&'a [u8], DatatypeError> {
+    let (read, rest) = bytes
+        .split_at_checked(len)
+        .ok_or(DatatypeError::NotEnoughData)?;
+    *bytes = rest;
+    Ok(read)
+}
+
+impl Datatype<'_> for u16 {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
     where
         Self: Sized,
     {
-        let short_bytes = bytes
-            .drain(..2)
-            .collect_array()
-            .ok_or(DatatypeError::NotEnoughData)?;
-        Ok(u16::from_be_bytes(short_bytes))
+        Ok(u16::from_be_bytes(read_array(bytes)?))
     }
 }
 
-impl Datatype for i64 {
-    fn from_bytes(bytes: &mut VecDeque<u8>) -> color_eyre::Result<Self>
+impl Datatype<'_> for i64 {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
     where
         Self: Sized,
     {
-        let long_bytes = bytes
-            .drain(..8)
-            .collect_array()
-            .ok_or(DatatypeError::NotEnoughData)?;
-        Ok(i64::from_be_bytes(long_bytes))
+        Ok(i64::from_be_bytes(read_array(bytes)?))
     }
 }
 
-impl Datatype for bool {
-    fn from_bytes(bytes: &mut VecDeque<u8>) -> color_eyre::Result<Self>
+impl Datatype<'_> for bool {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
     where
-        Self: Sized
+        Self: Sized,
     {
-        let bool_bytes = bytes.pop_front().ok_or(DatatypeError::NotEnoughData)?;
-        Ok(bool_bytes == 1)
+        Ok(u8::from_bytes(bytes)? == 1)
     }
 }
 
-impl Datatype for u8 {
-    fn from_bytes(bytes: &mut VecDeque<u8>) -> color_eyre::Result<Self>
+impl Datatype<'_> for u8 {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
     where
-        Self: Sized
+        Self: Sized,
     {
-        bytes.pop_front().ok_or_eyre(DatatypeError::NotEnoughData)
+        Ok(read_array::<1>(bytes)?[0])
     }
 }
 
-impl Datatype for i8 {
-    fn from_bytes(bytes: &mut VecDeque<u8>) -> color_eyre::Result<Self>
+impl Datatype<'_> for i8 {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
     where
-        Self: Sized
+        Self: Sized,
     {
         Ok(u8::from_bytes(bytes)? as i8)
     }
-}
\ No newline at end of file
+}
+impl Datatype<'_> for i16 {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        Ok(u16::from_bytes(bytes)? as i16)
+    }
+}
+
+impl Datatype<'_> for i32 {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        Ok(i32::from_be_bytes(read_array(bytes)?))
+    }
+}
+
+impl Datatype<'_> for u32 {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        Ok(i32::from_bytes(bytes)? as u32)
+    }
+}
+
+impl Datatype<'_> for u64 {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        Ok(i64::from_bytes(bytes)? as u64)
+    }
+}
+
+impl Datatype<'_> for f32 {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        Ok(f32::from_bits(u32::from_bytes(bytes)?))
+    }
+}
+
+impl Datatype<'_> for f64 {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        Ok(f64::from_bits(u64::from_bytes(bytes)?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn signed_and_float() {
+        let mut bytes: &[u8] = &[
+            0xFF, 0xFE, // i16: -2
+            0xFF, 0xFF, 0xFF, 0x9C, // i32: -100
+            0x3F, 0xC0, 0x00, 0x00, // f32: 1.5
+            0xC0, 0x09, 0x21, 0xFB, 0x54, 0x44, 0x2D, 0x18, // f64: -pi
+        ];
+
+        assert_eq!(i16::from_bytes(&mut bytes).unwrap(), -2);
+        assert_eq!(i32::from_bytes(&mut bytes).unwrap(), -100);
+        assert_eq!(f32::from_bytes(&mut bytes).unwrap(), 1.5);
+        assert_eq!(f64::from_bytes(&mut bytes).unwrap(), -std::f64::consts::PI);
+        assert!(bytes.is_empty());
+    }
+
+    #[test]
+    fn not_enough_data() {
+        let mut bytes: &[u8] = &[0x00, 0x01];
+        assert!(i32::from_bytes(&mut bytes).is_err());
+    }
+}
@@ -0,0 +1,180 @@
+use super::error::DatatypeError;
+use crate::protocol::types::{Datatype, ItemComponent, MinecraftArray, VarInt};
+use itertools::Itertools;
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+
+/// Item stack with the data components that differ from the item's defaults
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Slot {
+    pub item_count: VarInt,
+    pub item_id: VarInt,
+    pub added_components: Vec<ItemComponent>,
+    pub removed_components: Vec<VarInt>,
+}
+
+impl Slot {
+    /// Items hold items, this guards against malicious input blowing the stack
+    const MAX_DEPTH: usize = 64;
+
+    pub fn empty() -> Self {
+        Self {
+            item_count: VarInt { value: 0 },
+            item_id: VarInt { value: 0 },
+            added_components: vec![],
+            removed_components: vec![],
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.item_count.value <= 0
+    }
+
+    /// Reads a slot held by items `depth` deep, like a shulker box's contents
+    pub(crate) fn read(bytes: &mut &[u8], depth: usize) -> Result<Self, DatatypeError> {
+        if depth > Self::MAX_DEPTH {
+            return Err(DatatypeError::TooBig("Slot depth"));
+        }
+
+        let item_count = VarInt::from_bytes(bytes)?;
+        if item_count.value <= 0 {
+            return Ok(Self {
+                item_count,
+                ..Self::empty()
+            });
+        }
+
+        let item_id = VarInt::from_bytes(bytes)?;
+        let added_len = VarInt::from_bytes(bytes)?.value as usize;
+        let removed_len = VarInt::from_bytes(bytes)?.value as usize;
+        // The length comes off the wire, so don't trust it for the allocation
+        let mut added_components = Vec::with_capacity(added_len.min(bytes.len()));
+        for _ in 0..added_len {
+            added_components.push(ItemComponent::read_nested(bytes, depth)?);
+        }
+        let removed_components = Vec::from_array_bytes(removed_len, bytes)?;
+
+        Ok(Self {
+            item_count,
+            item_id,
+            added_components,
+            removed_components,
+        })
+    }
+
+    /// Length prefixed slots held by items `depth` deep
+    pub(crate) fn read_all(bytes: &mut &[u8], depth: usize) -> Result<Vec<Self>, DatatypeError> {
+        let len = VarInt::from_bytes(bytes)?.value as usize;
+        let mut slots = Vec::with_capacity(len.min(bytes.len()));
+        for _ in 0..len {
+            slots.push(Self::read(bytes, depth)?);
+        }
+        Ok(slots)
+    }
+}
+
+impl Datatype<'_> for Slot {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        Self::read(bytes, 0)
+    }
+}
+
+impl Display for Slot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return write!(f, "Empty");
+        }
+
+        write!(f, "{}x Item #{}", self.item_count, self.item_id)?;
+        if !self.added_components.is_empty() {
+            write!(f, " +[{}]", self.added_components.iter().join(", "))?;
+        }
+        if !self.removed_components.is_empty() {
+            let removed = self
+                .removed_components
+                .iter()
+                .map(|x| ItemComponent::name_from_id(x.value))
+                .join(", ");
+            write!(f, " -[{removed}]")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::protocol::types::{Nbt, TextComponent};
+
+    #[test]
+    fn empty() {
+        let mut bytes: &[u8] = &[0x00, 0x01];
+        let slot = Slot::from_bytes(&mut bytes).unwrap();
+        assert!(slot.is_empty());
+        assert_eq!(bytes.len(), 1);
+    }
+
+    #[test]
+    fn with_components() {
+        let mut bytes: &[u8] = &[
+            0x40, // 64 items
+            0xA8, 0x06, // Item ID 808
+            0x02, // 2 added
+            0x01, // 1 removed
+            0x03, 0x0A, // damage: 10
+            0x05, 0x08, 0x00, 0x03, b'B', b'o', b'b', // custom_name: "Bob"
+            0x13, // enchantment_glint_override removed
+        ];
+        let slot = Slot::from_bytes(&mut bytes).unwrap();
+        assert!(bytes.is_empty());
+        assert_eq!(slot.item_id.value, 808);
+        assert_eq!(
+            slot.added_components,
+            vec![
+                ItemComponent::Damage(VarInt { value: 10 }),
+                ItemComponent::CustomName(TextComponent(Nbt::String("Bob".to_string()))),
+            ]
+        );
+        assert_eq!(
+            slot.to_string(),
+            r#"64x Item #808 +[minecraft:damage=10, minecraft:custom_name="Bob"] -[minecraft:enchantment_glint_override]"#
+        );
+    }
+
+    #[test]
+    fn nested_container() {
+        let mut bytes: &[u8] = &[
+            0x01, 0x01, 0x01, 0x00, // 1 item, ID 1, 1 added, 0 removed
+            0x3E, 0x02, // container with 2 slots
+            0x00, // empty
+            0x02, 0x05, 0x00, 0x00, // 2x item 5
+        ];
+        let slot = Slot::from_bytes(&mut bytes).unwrap();
+        assert!(bytes.is_empty());
+        let ItemComponent::Container(items) = &slot.added_components[0] else {
+            panic!("Expected container, got {:?}", slot.added_components[0]);
+        };
+        assert!(items[0].is_empty());
+        assert_eq!(items[1].item_count.value, 2);
+    }
+
+    #[test]
+    fn nesting() {
+        // 1 item, ID 1, 1 added, 0 removed, use_remainder: the next item
+        let item = [0x01, 0x01, 0x01, 0x00, 0x17];
+        let nested = |depth: usize| {
+            let mut bytes = item.repeat(depth);
+            bytes.push(0x00);
+            bytes
+        };
+
+        let bytes = nested(Slot::MAX_DEPTH);
+        assert!(Slot::from_bytes(&mut &bytes[..]).is_ok());
+        // A megabyte of nesting is an error rather than a stack overflow
+        let bytes = nested((1 << 20) / item.len());
+        assert!(Slot::from_bytes(&mut &bytes[..]).is_err());
+    }
+}
@@ -0,0 +1,27 @@
+use super::error::DatatypeError;
+use crate::protocol::types::{Datatype, Identifier};
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SoundEvent {
+    pub name: Identifier,
+    pub fixed_range: Option<f32>,
+}
+
+impl Datatype<'_> for SoundEvent {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let name = Identifier::from_bytes(bytes)?;
+        let fixed_range = Option::from_bytes(bytes)?;
+        Ok(Self { name, fixed_range })
+    }
+}
+
+impl Display for SoundEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
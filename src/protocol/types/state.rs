@@ -1,10 +1,10 @@
 use std::fmt::{Display, Formatter};
-use color_eyre::eyre::OptionExt;
 use num_derive::FromPrimitive;
 use num_traits::FromPrimitive;
+use serde::Serialize;
 use crate::protocol::types::error::DatatypeError;
 
-#[derive(FromPrimitive, Debug, Copy, Clone)]
+#[derive(FromPrimitive, Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
 #[repr(i32)]
 pub enum State {
     Handshaking = 0,
@@ -12,11 +12,12 @@ pub enum State {
     Login = 2,
     Transfer = 3,
     Configuration = 4,
+    Play = 5,
 }
 
 impl State {
-    pub fn from_num(num: i32) -> color_eyre::Result<Self> {
-        Self::from_i32(num).ok_or_eyre(DatatypeError::UnknownState(num))
+    pub fn from_num(num: i32) -> Result<Self, DatatypeError> {
+        Self::from_i32(num).ok_or(DatatypeError::UnknownState(num))
     }
 }
 
@@ -27,7 +28,8 @@ impl Display for State {
             State::Status => "Status",
             State::Login => "Login",
             State::Transfer => "Transfer",
-            State::Configuration => "Configuration"
+            State::Configuration => "Configuration",
+            State::Play => "Play",
         })
     }
 }
\ No newline at end of file
@@ -1,15 +1,23 @@
-use super::Datatype;
+use super::error::DatatypeError;
 use super::VarInt;
-use itertools::Itertools;
-use std::collections::VecDeque;
+use super::{read_bytes, Datatype};
 
-impl Datatype for String {
-    fn from_bytes(bytes: &mut VecDeque<u8>) -> color_eyre::Result<Self>
+/// Borrowed from the packet, for reading a string without allocating
+impl<'a> Datatype<'a> for &'a str {
+    fn from_bytes(bytes: &mut &'a [u8]) -> Result<Self, DatatypeError>
     where
         Self: Sized,
     {
-        let length = VarInt::from_bytes(bytes)?;
-        let string_bytes = bytes.drain(..length.value as usize).collect_vec();
-        Ok(String::from_utf8(string_bytes)?)
+        let length = VarInt::from_bytes(bytes)?.value as usize;
+        Ok(std::str::from_utf8(read_bytes(bytes, length)?)?)
+    }
+}
+
+impl Datatype<'_> for String {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        Ok(<&str>::from_bytes(bytes)?.to_owned())
     }
 }
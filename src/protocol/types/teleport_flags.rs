@@ -0,0 +1,44 @@
+use super::error::DatatypeError;
+use crate::protocol::types::Datatype;
+use bitflags::bitflags;
+use serde::Serialize;
+
+bitflags! {
+    /// Marks which fields of a teleport are relative to the current values instead of absolute
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+    pub struct TeleportFlags: u32 {
+        const RELATIVE_X = 0x0001;
+        const RELATIVE_Y = 0x0002;
+        const RELATIVE_Z = 0x0004;
+        const RELATIVE_YAW = 0x0008;
+        const RELATIVE_PITCH = 0x0010;
+        const RELATIVE_VELOCITY_X = 0x0020;
+        const RELATIVE_VELOCITY_Y = 0x0040;
+        const RELATIVE_VELOCITY_Z = 0x0080;
+        const ROTATE_VELOCITY = 0x0100;
+    }
+}
+
+impl Datatype<'_> for TeleportFlags {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        Ok(Self::from_bits_retain(u32::from_bytes(bytes)?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn flags() {
+        let mut bytes: &[u8] = &[0x00, 0x00, 0x01, 0x18];
+        let flags = TeleportFlags::from_bytes(&mut bytes).unwrap();
+        assert_eq!(
+            flags,
+            TeleportFlags::RELATIVE_YAW | TeleportFlags::RELATIVE_PITCH | TeleportFlags::ROTATE_VELOCITY
+        );
+    }
+}
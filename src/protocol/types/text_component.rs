@@ -0,0 +1,75 @@
+use super::error::DatatypeError;
+use crate::protocol::types::{Datatype, Nbt};
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+
+/// Chat component, sent as NBT. Either a plain string or a compound with `text`/`translate` and `extra`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TextComponent(pub Nbt);
+
+impl TextComponent {
+    /// Flattens the component into its visible text, ignoring styling.
+    /// Translated components fall back to their translation key.
+    pub fn plain_text(&self) -> String {
+        let mut output = String::new();
+        Self::append_plain_text(&self.0, &mut output);
+        output
+    }
+
+    fn append_plain_text(nbt: &Nbt, output: &mut String) {
+        match nbt {
+            Nbt::String(s) => output.push_str(s),
+            Nbt::List(list) => list.iter().for_each(|x| Self::append_plain_text(x, output)),
+            Nbt::Compound(_) => {
+                if let Some(text) = nbt.get("text").or(nbt.get("")) {
+                    Self::append_plain_text(text, output);
+                } else if let Some(key) = nbt.get("translate").and_then(Nbt::as_str) {
+                    output.push_str(key);
+                }
+                if let Some(extra) = nbt.get("extra") {
+                    Self::append_plain_text(extra, output);
+                }
+            }
+            other => {
+                if let Some(value) = other.as_i64() {
+                    output.push_str(&value.to_string());
+                }
+            }
+        }
+    }
+}
+
+impl Datatype<'_> for TextComponent {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        Ok(Self(Nbt::from_bytes(bytes)?))
+    }
+}
+
+impl Display for TextComponent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.plain_text())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn plain_text() {
+        let component = TextComponent(Nbt::Compound(vec![
+            ("text".to_string(), Nbt::String("Hello ".to_string())),
+            (
+                "extra".to_string(),
+                Nbt::List(vec![
+                    Nbt::String("there".to_string()),
+                    Nbt::Compound(vec![("translate".to_string(), Nbt::String("chat.x".to_string()))]),
+                ]),
+            ),
+        ]));
+        assert_eq!(component.plain_text(), "Hello therechat.x");
+    }
+}
@@ -1,16 +1,12 @@
-use std::collections::VecDeque;
-use color_eyre::eyre::OptionExt;
-use itertools::Itertools;
+use super::error::DatatypeError;
 use uuid::Uuid;
-use crate::protocol::types::Datatype;
-use crate::protocol::types::error::DatatypeError;
+use crate::protocol::types::{read_array, Datatype};
 
-impl Datatype for Uuid {
-    fn from_bytes(bytes: &mut VecDeque<u8>) -> color_eyre::Result<Self>
+impl Datatype<'_> for Uuid {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
     where
         Self: Sized
     {
-        let uuid_bytes = bytes.drain(..16).collect_array().ok_or_eyre(DatatypeError::NotEnoughData)?;
-        Ok(Uuid::from_bytes(uuid_bytes))
+        Ok(Uuid::from_bytes(read_array(bytes)?))
     }
 }
\ No newline at end of file
@@ -1,11 +1,10 @@
 use super::{error::DatatypeError, Datatype};
-use color_eyre::eyre::bail;
-use color_eyre::Result;
-use std::collections::VecDeque;
+use serde::Serialize;
 use std::fmt::{Display, Formatter};
 use std::ops::Deref;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
 pub struct VarInt {
     pub value: i32,
 }
@@ -13,15 +12,28 @@ pub struct VarInt {
 impl VarInt {
     const SEGMENT_BITS: i32 = 0x7F;
     const CONTINUE_BIT: i32 = 0x80;
+
+    pub fn to_bytes(self) -> Vec<u8> {
+        let mut value = self.value as u32;
+        let mut bytes = vec![];
+        loop {
+            if value & !(Self::SEGMENT_BITS as u32) == 0 {
+                bytes.push(value as u8);
+                return bytes;
+            }
+            bytes.push((value as i32 & Self::SEGMENT_BITS | Self::CONTINUE_BIT) as u8);
+            value >>= 7;
+        }
+    }
 }
 
-impl Datatype for VarInt {
-    fn from_bytes(bytes: &mut VecDeque<u8>) -> color_eyre::Result<Self> {
+impl Datatype<'_> for VarInt {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError> {
         let mut value = 0;
         let mut position = 0;
 
         loop {
-            let byte = bytes.pop_front().ok_or(DatatypeError::NotEnoughData)? as i32;
+            let byte = u8::from_bytes(bytes)? as i32;
             value |= (byte & Self::SEGMENT_BITS) << position;
 
             if (byte & Self::CONTINUE_BIT) == 0 {
@@ -31,7 +43,7 @@ impl Datatype for VarInt {
             position += 7;
 
             if position >= 32 {
-                bail!(DatatypeError::TooBig("VarInt"));
+                return Err(DatatypeError::TooBig("VarInt"));
             }
         }
 
@@ -53,7 +65,8 @@ impl Deref for VarInt {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
 pub struct VarLong {
     pub value: i64,
 }
@@ -61,15 +74,28 @@ pub struct VarLong {
 impl VarLong {
     const SEGMENT_BITS: i64 = 0x7F;
     const CONTINUE_BIT: i64 = 0x80;
+
+    pub fn to_bytes(self) -> Vec<u8> {
+        let mut value = self.value as u64;
+        let mut bytes = vec![];
+        loop {
+            if value & !(Self::SEGMENT_BITS as u64) == 0 {
+                bytes.push(value as u8);
+                return bytes;
+            }
+            bytes.push((value as i64 & Self::SEGMENT_BITS | Self::CONTINUE_BIT) as u8);
+            value >>= 7;
+        }
+    }
 }
 
-impl Datatype for VarLong {
-    fn from_bytes(bytes: &mut VecDeque<u8>) -> color_eyre::Result<Self> {
+impl Datatype<'_> for VarLong {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError> {
         let mut value = 0;
         let mut position = 0;
 
         loop {
-            let byte = bytes.pop_front().ok_or(DatatypeError::NotEnoughData)? as i64;
+            let byte = u8::from_bytes(bytes)? as i64;
             value |= (byte & Self::SEGMENT_BITS) << position;
 
             if (byte & Self::CONTINUE_BIT) == 0 {
@@ -79,7 +105,7 @@ impl Datatype for VarLong {
             position += 7;
 
             if position >= 64 {
-                bail!(DatatypeError::TooBig("VarLong"));
+                return Err(DatatypeError::TooBig("VarLong"));
             }
         }
 
@@ -101,7 +127,7 @@ mod test {
 
     #[test]
     fn test() {
-        let mut bytes = VecDeque::from([
+        let mut bytes: &[u8] = &[
             198, 77, 162, 219, 173, 140, 199, 62, 186, 139, 30, 177, 50, 29, 123, 233, 43, 244, 0,
             221, 67, 147, 153, 17, 215, 108, 194, 110, 122, 152, 94, 102, 215, 216, 77, 54, 11, 98,
             248, 69, 128, 156, 118, 132, 159, 202, 4, 92, 228, 251, 233, 50, 136, 140, 215, 243,
@@ -181,7 +207,7 @@ mod test {
             137, 209, 246, 186, 34, 126, 22, 38, 27, 21, 251, 105, 219, 50, 203, 67, 90, 20, 212,
             43, 229, 176, 150, 70, 222, 198, 37, 106, 113, 251, 250, 249, 173, 178, 27, 54, 109,
             214, 164, 65, 161, 210,
-        ]);
+        ];
         println!("{}", bytes.len());
 
         let temp = VarInt::from_bytes(&mut bytes).unwrap();
@@ -190,4 +216,20 @@ mod test {
         println!("{bytes:?}");
 
     }
+
+    #[test]
+    fn encode() {
+        for value in [0, 1, 127, 128, 25565, 2097151, i32::MAX, -1, i32::MIN] {
+            let bytes = VarInt { value }.to_bytes();
+            assert_eq!(VarInt::from_bytes(&mut &bytes[..]).unwrap().value, value);
+        }
+        assert_eq!(VarInt { value: 25565 }.to_bytes(), [0xDD, 0xC7, 0x01]);
+        assert_eq!(VarInt { value: -1 }.to_bytes().len(), 5);
+
+        for value in [0, 300, i64::MAX, -1, i64::MIN] {
+            let bytes = VarLong { value }.to_bytes();
+            assert_eq!(VarLong::from_bytes(&mut &bytes[..]).unwrap().value, value);
+        }
+        assert_eq!(VarLong { value: -1 }.to_bytes().len(), 10);
+    }
 }
@@ -1,11 +1,11 @@
-use crate::protocol::types::{Datatype, MinecraftArray, VarInt};
-use std::collections::VecDeque;
+use super::error::DatatypeError;
+use crate::protocol::types::{read_bytes, Datatype, MinecraftArray, VarInt};
 
-impl<T> Datatype for Vec<T>
+impl<'a, T> Datatype<'a> for Vec<T>
 where
-    T: Datatype,
+    T: Datatype<'a>,
 {
-    fn from_bytes(bytes: &mut VecDeque<u8>) -> color_eyre::Result<Self>
+    fn from_bytes(bytes: &mut &'a [u8]) -> Result<Self, DatatypeError>
     where
         Self: Sized,
     {
@@ -14,12 +14,13 @@ where
     }
 }
 
-impl<T> MinecraftArray for Vec<T>
+impl<'a, T> MinecraftArray<'a> for Vec<T>
 where
-    T: Datatype,
+    T: Datatype<'a>,
 {
-    fn from_array_bytes(len: usize, bytes: &mut VecDeque<u8>) -> color_eyre::Result<Self> {
-        let mut output = Vec::with_capacity(len);
+    fn from_array_bytes(len: usize, bytes: &mut &'a [u8]) -> Result<Self, DatatypeError> {
+        // The length comes off the wire, so don't trust it for the allocation
+        let mut output = Vec::with_capacity(len.min(bytes.len()));
         for _ in 0..len {
             output.push(T::from_bytes(bytes)?);
         }
@@ -27,3 +28,36 @@ where
         Ok(output)
     }
 }
+
+/// Array whose length is known from context rather than sent with it
+impl<'a, T, const N: usize> Datatype<'a> for [T; N]
+where
+    T: Datatype<'a>,
+{
+    fn from_bytes(bytes: &mut &'a [u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let output: Vec<T> = Vec::from_array_bytes(N, bytes)?;
+        Ok(output
+            .try_into()
+            .unwrap_or_else(|_| unreachable!("Vec has exactly N elements")))
+    }
+}
+
+/// VarInt length prefixed bytes, borrowed from the packet
+impl<'a> Datatype<'a> for &'a [u8] {
+    fn from_bytes(bytes: &mut &'a [u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let len = VarInt::from_bytes(bytes)?.value as usize;
+        read_bytes(bytes, len)
+    }
+}
+
+impl<'a> MinecraftArray<'a> for &'a [u8] {
+    fn from_array_bytes(len: usize, bytes: &mut &'a [u8]) -> Result<Self, DatatypeError> {
+        read_bytes(bytes, len)
+    }
+}
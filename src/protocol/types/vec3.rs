@@ -0,0 +1,131 @@
+use super::error::DatatypeError;
+use crate::protocol::types::{Datatype, VarInt};
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+
+/// Three Doubles, used for positions and velocities
+#[derive(Debug, Copy, Clone, Default, PartialEq, Serialize)]
+pub struct Vec3 {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl Datatype<'_> for Vec3 {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let x = f64::from_bytes(bytes)?;
+        let y = f64::from_bytes(bytes)?;
+        let z = f64::from_bytes(bytes)?;
+        Ok(Self { x, y, z })
+    }
+}
+
+impl Display for Vec3 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}, {}, {})", self.x, self.y, self.z)
+    }
+}
+
+/// Low precision Vec3 used for entity velocities.
+///
+/// Each component is quantized to 15 bits in the range [-1, 1] and multiplied by a shared scale.
+/// A zero vector is sent as a single 0 byte.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Serialize)]
+pub struct LpVec3(pub Vec3);
+
+impl LpVec3 {
+    const CONTINUATION_FLAG: u64 = 0x4;
+    const SCALE_BITS: u64 = 0x3;
+
+    fn unpack(value: u64) -> f64 {
+        ((value & 0x7FFF) as f64).min(32766.0) * 2.0 / 32766.0 - 1.0
+    }
+}
+
+impl Datatype<'_> for LpVec3 {
+    fn from_bytes(bytes: &mut &[u8]) -> Result<Self, DatatypeError>
+    where
+        Self: Sized,
+    {
+        let first = u8::from_bytes(bytes)? as u64;
+        if first == 0 {
+            return Ok(Self::default());
+        }
+
+        let second = u8::from_bytes(bytes)? as u64;
+        let rest = u32::from_bytes(bytes)? as u64;
+        let packed = (rest << 16) | (second << 8) | first;
+
+        let mut scale = first & Self::SCALE_BITS;
+        if first & Self::CONTINUATION_FLAG != 0 {
+            scale |= (VarInt::from_bytes(bytes)?.value as u32 as u64) << 2;
+        }
+        let scale = scale as f64;
+
+        Ok(Self(Vec3 {
+            x: Self::unpack(packed >> 3) * scale,
+            y: Self::unpack(packed >> 18) * scale,
+            z: Self::unpack(packed >> 33) * scale,
+        }))
+    }
+}
+
+impl Display for LpVec3 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn pack(x: u64, y: u64, z: u64, scale: u64) -> Vec<u8> {
+        let packed = (z << 33) | (y << 18) | (x << 3) | (scale & 0x3);
+        let mut data = vec![(packed & 0xFF) as u8, ((packed >> 8) & 0xFF) as u8];
+        data.extend(((packed >> 16) as u32).to_be_bytes());
+        data
+    }
+
+    #[test]
+    fn vec3() {
+        let mut data = Vec::new();
+        data.extend(1.0f64.to_be_bytes());
+        data.extend((-2.5f64).to_be_bytes());
+        data.extend(0.25f64.to_be_bytes());
+        let mut bytes = &data[..];
+        let vec = Vec3::from_bytes(&mut bytes).unwrap();
+        assert_eq!(vec, Vec3 { x: 1.0, y: -2.5, z: 0.25 });
+    }
+
+    #[test]
+    fn lp_vec3_zero() {
+        let mut bytes: &[u8] = &[0x00, 0xAA];
+        assert_eq!(LpVec3::from_bytes(&mut bytes).unwrap(), LpVec3::default());
+        assert_eq!(bytes.len(), 1);
+    }
+
+    #[test]
+    fn lp_vec3() {
+        let data = pack(32766, 16383, 0, 2);
+        let mut bytes = &data[..];
+        let LpVec3(vec) = LpVec3::from_bytes(&mut bytes).unwrap();
+        assert_eq!(vec.x, 2.0);
+        assert!(vec.y.abs() < 0.001);
+        assert_eq!(vec.z, -2.0);
+        assert!(bytes.is_empty());
+    }
+
+    #[test]
+    fn lp_vec3_large_scale() {
+        let mut data = pack(32766, 32766, 32766, 1);
+        data[0] |= 0x4;
+        data.push(0x02); // scale = 1 | 2 << 2 = 9
+        let mut bytes = &data[..];
+        let LpVec3(vec) = LpVec3::from_bytes(&mut bytes).unwrap();
+        assert_eq!(vec, Vec3 { x: 9.0, y: 9.0, z: 9.0 });
+    }
+}
@@ -0,0 +1,298 @@
+hf.length = ProtoField.uint32("minecraft.length", "Length")
+hf.data_length = ProtoField.uint32("minecraft.data_length", "Uncompressed length")
+hf.state = ProtoField.string("minecraft.state", "State")
+hf.direction = ProtoField.string("minecraft.direction", "Direction")
+hf.id = ProtoField.uint32("minecraft.id", "Packet ID", base.HEX)
+hf.name = ProtoField.string("minecraft.name", "Packet")
+hf.data = ProtoField.bytes("minecraft.data", "Data")
+minecraft.fields = hf
+
+-- State and compression at the start of each packet, keyed by frame number and offset.
+-- They are only known by going through the conversation in order, on the first pass.
+local conversations = {}
+local packet_states = {}
+
+function minecraft.init()
+    conversations = {}
+    packet_states = {}
+end
+
+-- Value and size of the VarInt at offset, nil if the buffer ends before it does
+local function peek_varint(tvb, offset, max_size)
+    local value, multiplier = 0, 1
+    for size = 1, max_size do
+        if offset + size > tvb:len() then
+            return nil
+        end
+        local byte = tvb(offset + size - 1, 1):uint()
+        value = value + (byte % 128) * multiplier
+        if byte < 128 then
+            value = value % 4294967296
+            if value >= 2147483648 then
+                value = value - 4294967296
+            end
+            return value, size
+        end
+        multiplier = multiplier * 128
+    end
+    error("VarInt is too big")
+end
+
+local function read_varint(tvb, offset)
+    local value, size = peek_varint(tvb, offset, 5)
+    if value == nil then
+        error("Packet ends inside a VarInt")
+    end
+    return value, size
+end
+
+local function read_varlong(tvb, offset)
+    local value = UInt64(0)
+    for size = 1, 10 do
+        local byte = tvb(offset + size - 1, 1):uint()
+        value = value:bor(UInt64(byte % 128):lshift(7 * (size - 1)))
+        if byte < 128 then
+            return Int64.fromhex(value:tohex()), size
+        end
+    end
+    error("VarLong is too big")
+end
+
+local function read_string(tvb, offset)
+    local length, size = read_varint(tvb, offset)
+    if length == 0 then
+        return "", size
+    end
+    return tvb(offset + size, length):string(ENC_UTF_8), size + length
+end
+
+local function format_uuid(range)
+    local hex = string.lower(tostring(range:bytes()))
+    return string.format("%s-%s-%s-%s-%s", hex:sub(1, 8), hex:sub(9, 12), hex:sub(13, 16),
+        hex:sub(17, 20), hex:sub(21, 32))
+end
+
+local FIXED_SIZES = {
+    bool = 1, byte = 1, unsigned_byte = 1, angle = 1, short = 2, unsigned_short = 2,
+    int = 4, float = 4, long = 8, double = 8, position = 8,
+}
+
+-- Decodes one field, returning the offset after it (nil once the rest of the packet was
+-- shown as raw bytes) and its value if it has one. Structs are labelled with `label` if given.
+local function dissect_field(tvb, offset, tree, entry, label)
+    local kind = entry.kind
+    local size = FIXED_SIZES[kind]
+    if size ~= nil then
+        local range = tvb(offset, size)
+        local item = tree:add(entry.field, range)
+        local value
+        if kind == "bool" then
+            value = range:uint() ~= 0
+        elseif kind == "long" or kind == "position" then
+            value = range:int64()
+        elseif kind == "float" or kind == "double" then
+            value = range:float()
+        elseif kind == "byte" or kind == "short" or kind == "int" then
+            value = range:int()
+        else
+            value = range:uint()
+        end
+        if kind == "position" then
+            item:append_text(string.format(" (%d, %d, %d)", value:arshift(38):tonumber(),
+                value:lshift(52):arshift(52):tonumber(), value:lshift(26):arshift(38):tonumber()))
+        elseif kind == "angle" then
+            item:append_text(string.format(" (%.1f degrees)", value * 360 / 256))
+        end
+        return offset + size, value
+    elseif kind == "varint" then
+        local value, size = read_varint(tvb, offset)
+        tree:add(entry.field, tvb(offset, size), value)
+        return offset + size, value
+    elseif kind == "varlong" then
+        local value, size = read_varlong(tvb, offset)
+        tree:add(entry.field, tvb(offset, size), value)
+        return offset + size, value
+    elseif kind == "string" or kind == "identifier" then
+        local value, size = read_string(tvb, offset)
+        tree:add(entry.field, tvb(offset, size), value)
+        return offset + size, value
+    elseif kind == "uuid" then
+        local range = tvb(offset, 16)
+        local value = format_uuid(range)
+        tree:add(entry.field, range, value)
+        return offset + 16, value
+    elseif kind == "array" then
+        local count, size = read_varint(tvb, offset)
+        tree:add(entry.field, tvb(offset, size), count)
+        offset = offset + size
+        for i = 1, count do
+            offset = dissect_field(tvb, offset, tree, entry.of, string.format("%s [%d]", entry.name, i))
+            if offset == nil then
+                return nil
+            end
+        end
+        return offset, count
+    elseif kind == "optional" then
+        local range = tvb(offset, 1)
+        tree:add(entry.field, range)
+        if range:uint() == 0 then
+            return offset + 1
+        end
+        return dissect_field(tvb, offset + 1, tree, entry.of, label)
+    elseif kind == "struct" then
+        local start = offset
+        local subtree = tree:add(minecraft, tvb(offset, 1), label or entry.name)
+        for _, child in ipairs(entry.fields) do
+            offset = dissect_field(tvb, offset, subtree, child)
+            if offset == nil then
+                return nil
+            end
+        end
+        subtree:set_len(offset - start)
+        return offset
+    elseif kind == "bytes" or kind == "rest" then
+        if offset < tvb:len() then
+            tree:add(entry.field, tvb(offset))
+        end
+        if kind == "bytes" then
+            return tvb:len()
+        end
+        return nil
+    end
+    error("Unknown field kind " .. kind)
+end
+
+local function conversation(pinfo, server_bounded)
+    -- The client end tells connections to the same server apart
+    local key
+    if server_bounded then
+        key = tostring(pinfo.src) .. ":" .. pinfo.src_port
+    else
+        key = tostring(pinfo.dst) .. ":" .. pinfo.dst_port
+    end
+    local state = conversations[key]
+    if state == nil then
+        state = { state = "Handshaking", compression = false }
+        conversations[key] = state
+    end
+    return state
+end
+
+local function apply_transition(state, transition, values)
+    if transition.kind == "handshake" then
+        state.state = STATES[values.next_state] or state.state
+    elseif transition.kind == "state" then
+        state.state = transition.state
+    elseif transition.kind == "compression" then
+        state.compression = values.threshold >= 0
+    end
+end
+
+local function inflate(range)
+    -- Renamed in Wireshark 4.4
+    local ok, data = pcall(function() return range:uncompress_zlib("Decompressed") end)
+    if not ok then
+        data = range:uncompress("Decompressed")
+    end
+    if data == nil then
+        error("Can't decompress the packet")
+    end
+    return data:tvb()
+end
+
+-- Decodes one length prefixed packet and returns its name
+local function dissect_packet(tvb, pinfo, tree, server_bounded, key)
+    local state = packet_states[key]
+    if state == nil then
+        local current = conversation(pinfo, server_bounded)
+        state = { state = current.state, compression = current.compression }
+        packet_states[key] = state
+    end
+    local direction = server_bounded and "serverbound" or "clientbound"
+
+    local length, offset = read_varint(tvb, 0)
+    tree:add(hf.length, tvb(0, offset), length)
+    tree:add(hf.state, state.state):set_generated()
+    tree:add(hf.direction, direction):set_generated()
+
+    local data
+    if state.compression then
+        local data_length, size = read_varint(tvb, offset)
+        tree:add(hf.data_length, tvb(offset, size), data_length)
+        offset = offset + size
+        if data_length == 0 then
+            data = tvb(offset):tvb()
+        else
+            data = inflate(tvb(offset))
+        end
+    else
+        data = tvb(offset):tvb()
+    end
+
+    local id, size = read_varint(data, 0)
+    tree:add(hf.id, data(0, size), id)
+    local info = PACKETS[state.state][direction][id]
+    local name = info and info.name or "Unknown"
+    tree:add(hf.name, name):set_generated()
+    tree:append_text(string.format(", %s %s (0x%02X)", state.state, name, id))
+
+    if info == nil then
+        if size < data:len() then
+            tree:add(hf.data, data(size))
+        end
+        return name
+    end
+    local values = {}
+    offset = size
+    for _, entry in ipairs(info.fields) do
+        local value
+        offset, value = dissect_field(data, offset, tree, entry)
+        values[entry.name] = value
+        if offset == nil then
+            break
+        end
+    end
+    if offset ~= nil and offset < data:len() then
+        tree:add_expert_info(PI_MALFORMED, PI_WARN, (data:len() - offset) .. " bytes left over")
+    end
+    if not pinfo.visited and info.transition ~= nil then
+        apply_transition(conversation(pinfo, server_bounded), info.transition, values)
+    end
+    return name
+end
+
+function minecraft.dissector(tvb, pinfo, tree)
+    pinfo.cols.protocol = "Minecraft"
+    local server_bounded = pinfo.dst_port == pinfo.match_uint
+    local names = {}
+    local offset = 0
+    while offset < tvb:len() do
+        local length, size = peek_varint(tvb, offset, 3)
+        if length == nil then
+            pinfo.desegment_offset = offset
+            pinfo.desegment_len = DESEGMENT_ONE_MORE_SEGMENT
+            break
+        end
+        if offset + size + length > tvb:len() then
+            pinfo.desegment_offset = offset
+            pinfo.desegment_len = offset + size + length - tvb:len()
+            break
+        end
+
+        local packet = tvb(offset, size + length)
+        local subtree = tree:add(minecraft, packet)
+        local ok, name = pcall(dissect_packet, packet:tvb(), pinfo, subtree, server_bounded,
+            pinfo.number .. ":" .. offset)
+        if not ok then
+            subtree:add_expert_info(PI_MALFORMED, PI_ERROR, tostring(name))
+            name = "Malformed"
+        end
+        table.insert(names, name)
+        offset = offset + size + length
+    end
+    if #names > 0 then
+        pinfo.cols.info = (server_bounded and "[C->S] " or "[S->C] ") .. table.concat(names, ", ")
+    end
+end
+
+DissectorTable.get("tcp.port"):add(25565, minecraft)
@@ -0,0 +1,178 @@
+use crate::protocol::registry::{FieldKind, Transition, PACKETS};
+use crate::protocol::types::State;
+use crate::protocol::{MINECRAFT_VERSION, PROTOCOL_VERSION};
+use itertools::Itertools;
+
+/// Framing, decompression, state tracking and the decoder walking the generated layouts
+const RUNTIME: &str = include_str!("wireshark.lua");
+
+/// Wireshark Lua dissector generated from [`PACKETS`], so both decode the same packets
+pub fn dissector() -> String {
+    let mut fields = String::new();
+    let mut packets = String::new();
+    for info in PACKETS {
+        let prefix = snake_case(info.name);
+        let layout = info
+            .fields
+            .iter()
+            .map(|x| {
+                entry(
+                    x.name,
+                    &x.kind,
+                    &format!("{prefix}.{}", x.name),
+                    &mut fields,
+                )
+            })
+            .join(", ");
+        let layout = if layout.is_empty() {
+            layout
+        } else {
+            format!(" {layout} ")
+        };
+        let transition = match info.transition {
+            None => String::new(),
+            Some(Transition::Handshake) => r#", transition = { kind = "handshake" }"#.to_string(),
+            Some(Transition::To(state)) => {
+                format!(r#", transition = {{ kind = "state", state = "{state}" }}"#)
+            }
+            Some(Transition::Compression) => {
+                r#", transition = { kind = "compression" }"#.to_string()
+            }
+        };
+        packets.push_str(&format!(
+            "PACKETS.{}.{}[0x{:02X}] = {{ name = {:?}, fields = {{{layout}}}{transition} }}\n",
+            info.state,
+            if info.server_bounded {
+                "serverbound"
+            } else {
+                "clientbound"
+            },
+            info.id,
+            info.name,
+        ));
+    }
+
+    let states = (0..)
+        .map_while(|x| State::from_num(x).ok())
+        .map(|x| format!("[{}] = \"{x}\"", x as i32))
+        .join(", ");
+
+    format!(
+        "-- Minecraft {MINECRAFT_VERSION} (protocol {PROTOCOL_VERSION}) dissector for Wireshark\n\
+         -- Generated from the packet registry of the sniffer, regenerate it instead of editing\n\
+         \n\
+         local minecraft = Proto(\"minecraft\", \"Minecraft\")\n\
+         local hf = {{}}\n\
+         \n\
+         {fields}\n\
+         local STATES = {{ {states} }}\n\
+         local PACKETS = {{}}\n\
+         for _, state in pairs(STATES) do\n    \
+             PACKETS[state] = {{ serverbound = {{}}, clientbound = {{}} }}\n\
+         end\n\
+         \n\
+         {packets}\n\
+         {RUNTIME}"
+    )
+}
+
+/// Lua table describing how to decode one field, declaring its `ProtoField`s on the way
+fn entry(name: &str, kind: &FieldKind, path: &str, fields: &mut String) -> String {
+    // Packets shared by several states, like the keep alives, declare their fields once
+    let mut declare = |path: &str, constructor: &str, label: &str| {
+        let line =
+            format!("hf[{path:?}] = ProtoField.{constructor}(\"minecraft.{path}\", {label:?})\n");
+        if !fields.contains(&line) {
+            fields.push_str(&line);
+        }
+        format!("hf[{path:?}]")
+    };
+
+    let (kind_name, constructor) = match kind {
+        FieldKind::Bool => ("bool", "bool"),
+        FieldKind::Byte => ("byte", "int8"),
+        FieldKind::UnsignedByte => ("unsigned_byte", "uint8"),
+        FieldKind::Short => ("short", "int16"),
+        FieldKind::UnsignedShort => ("unsigned_short", "uint16"),
+        FieldKind::Int => ("int", "int32"),
+        FieldKind::Long => ("long", "int64"),
+        FieldKind::Float => ("float", "float"),
+        FieldKind::Double => ("double", "double"),
+        FieldKind::VarInt => ("varint", "int32"),
+        FieldKind::VarLong => ("varlong", "int64"),
+        FieldKind::String => ("string", "string"),
+        FieldKind::Identifier => ("identifier", "string"),
+        FieldKind::Uuid => ("uuid", "string"),
+        FieldKind::Position => ("position", "int64"),
+        FieldKind::Angle => ("angle", "uint8"),
+        FieldKind::Bytes => ("bytes", "bytes"),
+        FieldKind::Rest(what) => {
+            let field = declare(path, "bytes", &format!("{name} ({what})"));
+            return format!("{{ name = {name:?}, kind = \"rest\", field = {field} }}");
+        }
+        FieldKind::Array(element) => {
+            let field = declare(&format!("{path}.count"), "uint32", &format!("{name} count"));
+            let element = entry(name, element, path, fields);
+            return format!(
+                "{{ name = {name:?}, kind = \"array\", field = {field}, of = {element} }}"
+            );
+        }
+        FieldKind::Optional(inner) => {
+            let field = declare(
+                &format!("{path}.present"),
+                "bool",
+                &format!("{name} present"),
+            );
+            let inner = entry(name, inner, path, fields);
+            return format!(
+                "{{ name = {name:?}, kind = \"optional\", field = {field}, of = {inner} }}"
+            );
+        }
+        FieldKind::Struct(children) => {
+            let children = children
+                .iter()
+                .map(|x| entry(x.name, &x.kind, &format!("{path}.{}", x.name), fields))
+                .join(", ");
+            return format!("{{ name = {name:?}, kind = \"struct\", fields = {{ {children} }} }}");
+        }
+    };
+    let field = declare(path, constructor, name);
+    format!("{{ name = {name:?}, kind = \"{kind_name}\", field = {field} }}")
+}
+
+/// `LoginSuccess` to `login_success`
+fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    for c in name.chars() {
+        if c.is_ascii_uppercase() && !snake.is_empty() {
+            snake.push('_');
+        }
+        snake.push(c.to_ascii_lowercase());
+    }
+    snake
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn generated() {
+        let lua = dissector();
+        assert!(lua.contains(r#"PACKETS.Handshaking.serverbound[0x00] = { name = "Handshake""#));
+        assert!(lua.contains(r#"PACKETS.Play.clientbound[0x2C] = { name = "LoginPlay""#));
+        assert!(lua.contains(r#"transition = { kind = "state", state = "Play" }"#));
+        assert!(lua.contains(
+            r#"hf["login_success.property.signature.present"] = ProtoField.bool("minecraft.login_success.property.signature.present", "signature present")"#
+        ));
+        assert!(lua.contains(r#"local STATES = { [0] = "Handshaking", [1] = "Status""#));
+
+        // Every packet gets its own entry and no field is declared twice
+        assert_eq!(lua.matches("\nPACKETS.").count(), PACKETS.len());
+        let declared = lua
+            .lines()
+            .filter(|x| x.starts_with("hf["))
+            .collect::<Vec<_>>();
+        assert_eq!(declared.iter().unique().count(), declared.len());
+    }
+}
@@ -0,0 +1,446 @@
+mod rules;
+
+use crate::protocol::types::error::DatatypeError;
+use crate::protocol::types::{Datatype, State, VarInt};
+use crate::session::{Session, SessionEvent};
+use crate::sink::SinkOptions;
+use crate::sniffer::Sniffer;
+use bytes::{Bytes, BytesMut};
+use color_eyre::eyre::WrapErr;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use log::{error, info, warn};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
+use tokio_util::sync::CancellationToken;
+
+pub use rules::{Outcome, RuleError, Rules};
+
+/// Forwards every client connecting to `listen` on to `server`, decoding both directions the
+/// same way captured traffic is and applying `rules` to the packets. Only works for offline
+/// mode servers, as it can't decrypt. Stops accepting clients once `cancel` is cancelled.
+pub fn run(
+    listen: SocketAddr,
+    server: SocketAddr,
+    sink_options: SinkOptions,
+    rules: Rules,
+    cancel: CancellationToken,
+) -> color_eyre::Result<()> {
+    let listener = TcpListener::bind(listen).wrap_err(format!("Can't listen on {listen}"))?;
+    // Polled so a cancellation is noticed without a client connecting
+    listener.set_nonblocking(true)?;
+    info!("Proxying {listen} to {server}");
+    serve(listener, server, sink_options, rules, cancel);
+    Ok(())
+}
+
+fn serve(
+    listener: TcpListener,
+    server: SocketAddr,
+    sink_options: SinkOptions,
+    rules: Rules,
+    cancel: CancellationToken,
+) {
+    while !cancel.is_cancelled() {
+        let client = match listener.accept() {
+            Ok((client, _)) => client,
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                thread::sleep(Duration::from_millis(100));
+                continue;
+            }
+            Err(e) => {
+                warn!("Can't accept client: {e}");
+                continue;
+            }
+        };
+        // Accepted sockets inherit non-blocking on some platforms
+        if let Err(e) = client.set_nonblocking(false) {
+            warn!("Can't accept client: {e}");
+            continue;
+        }
+        let sink_options = sink_options.clone();
+        // Each connection counts its own matches
+        let rules = rules.clone();
+        thread::spawn(move || {
+            if let Err(e) = proxy(client, server, &sink_options, rules) {
+                error!("Proxy error: {e:?}");
+            }
+        });
+    }
+}
+
+fn proxy(
+    client: TcpStream,
+    server: SocketAddr,
+    sink_options: &SinkOptions,
+    rules: Rules,
+) -> color_eyre::Result<()> {
+    let client_addr = client.peer_addr()?;
+    let upstream = TcpStream::connect(server).wrap_err(format!("Can't connect to {server}"))?;
+    client.set_nodelay(true)?;
+    upstream.set_nodelay(true)?;
+
+    let mut sniffer = Sniffer::new(server.ip(), client_addr.ip(), sink_options);
+    sniffer.set_ports(client_addr.port(), server.port());
+    let mut incoming = Session::new(server.ip(), client_addr.ip());
+    incoming.set_ports(client_addr.port(), server.port());
+    let relay = Arc::new(Mutex::new(Relay {
+        sniffer,
+        incoming,
+        rules,
+        buffers: [BytesMut::new(), BytesMut::new()],
+        threshold: None,
+        passthrough: false,
+    }));
+    // Indexed by whether what's written is server bounded
+    let writers = Arc::new([Mutex::new(client.try_clone()?), Mutex::new(upstream.try_clone()?)]);
+    let forwarders = [
+        forward(client, true, relay.clone(), writers.clone()),
+        forward(upstream, false, relay.clone(), writers),
+    ];
+    for forwarder in forwarders {
+        let _ = forwarder.join();
+    }
+
+    let relay = Arc::into_inner(relay).expect("Forwarders are done");
+    relay.into_inner().unwrap().sniffer.finish();
+    Ok(())
+}
+
+/// Reads from `from` and writes what the relay makes of it, until either side closes
+fn forward(
+    mut from: TcpStream,
+    server_bounded: bool,
+    relay: Arc<Mutex<Relay>>,
+    writers: Arc<[Mutex<TcpStream>; 2]>,
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        let mut buffer = vec![0; 1 << 16];
+        'read: loop {
+            let n = match from.read(&mut buffer) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => n,
+            };
+            let outputs = relay.lock().unwrap().process(&buffer[..n], server_bounded);
+            for output in outputs {
+                thread::sleep(output.delay);
+                let mut to = writers[output.server_bounded as usize].lock().unwrap();
+                if to.write_all(&output.data).is_err() {
+                    break 'read;
+                }
+            }
+        }
+        // Pass the close on, the other direction ends once the other side follows
+        let _ = writers[server_bounded as usize]
+            .lock()
+            .unwrap()
+            .shutdown(Shutdown::Write);
+        let _ = from.shutdown(Shutdown::Read);
+    })
+}
+
+/// Bytes to write to one side
+struct Output {
+    delay: Duration,
+    server_bounded: bool,
+    data: Bytes,
+}
+
+/// Decoding and rewriting state of one proxied connection, shared by both directions
+struct Relay {
+    /// Decodes what is forwarded, so the outputs see the traffic as the other side got it
+    sniffer: Sniffer,
+    /// Decodes what arrives, for the rules to match on
+    incoming: Session,
+    rules: Rules,
+    buffers: [BytesMut; 2],
+    /// Compression threshold the server set
+    threshold: Option<i32>,
+    /// Once encrypted, or after a broken frame, everything is forwarded as is
+    passthrough: bool,
+}
+
+impl Relay {
+    fn process(&mut self, data: &[u8], server_bounded: bool) -> Vec<Output> {
+        let timestamp = SystemTime::now();
+        self.buffers[server_bounded as usize].extend_from_slice(data);
+        let mut outputs = vec![];
+        while !self.passthrough {
+            let buffer = &mut self.buffers[server_bounded as usize];
+            match next_frame(buffer) {
+                Ok(Some(raw)) => outputs.extend(self.packet(raw, server_bounded)),
+                Ok(None) => break,
+                Err(e) => {
+                    warn!("Can't frame packets ({e}), forwarding the rest as is");
+                    self.passthrough = true;
+                }
+            }
+        }
+        if self.passthrough {
+            outputs.push(Output {
+                delay: Duration::ZERO,
+                server_bounded,
+                data: self.buffers[server_bounded as usize].split().freeze(),
+            });
+        }
+
+        // Decoded as they will be written, after the delays before them
+        let mut sent = timestamp;
+        for output in &outputs {
+            sent += output.delay;
+            self.feed(&output.data, output.server_bounded, sent);
+        }
+        outputs
+    }
+
+    /// Runs the rules on one packet, length prefix included, returning what to send instead
+    fn packet(&mut self, raw: Bytes, server_bounded: bool) -> Vec<Output> {
+        let events = self
+            .incoming
+            .feed_payload(&raw, server_bounded, SystemTime::now())
+            .unwrap_or_else(|e| {
+                error!("Sniffer error: {e}");
+                vec![]
+            });
+
+        let mut outputs = vec![];
+        let mut forwarded = false;
+        for event in &events {
+            match event {
+                SessionEvent::Packet(packet) => {
+                    forwarded = true;
+                    let outcome = self.rules.apply(packet);
+                    let data = match &outcome.frame {
+                        Some(frame) => self.encode(frame),
+                        None => raw.clone(),
+                    };
+                    outputs.extend((0..outcome.copies).map(|i| Output {
+                        delay: if i == 0 { outcome.delay } else { Duration::ZERO },
+                        server_bounded,
+                        data: data.clone(),
+                    }));
+                    for (server_bounded, frame) in &outcome.inject {
+                        info!("Injected packet {:#04x}", frame[0]);
+                        outputs.push(Output {
+                            delay: Duration::ZERO,
+                            server_bounded: *server_bounded,
+                            data: self.encode(frame),
+                        });
+                    }
+                    // The encryption response that follows is the last thing in the clear
+                    if packet.state == State::Login && !server_bounded && packet.packet_id == 0x01
+                    {
+                        self.passthrough = true;
+                    }
+                }
+                SessionEvent::Compression(threshold) => {
+                    self.threshold = (*threshold >= 0).then_some(*threshold);
+                }
+                _ => {}
+            }
+        }
+        // Malformed packets go through untouched
+        if !forwarded {
+            outputs.push(Output {
+                delay: Duration::ZERO,
+                server_bounded,
+                data: raw,
+            });
+        }
+        outputs
+    }
+
+    fn feed(&mut self, data: &[u8], server_bounded: bool, timestamp: SystemTime) {
+        match self.sniffer.session().feed_payload(data, server_bounded, timestamp) {
+            Ok(events) => {
+                self.sniffer.handle(&events);
+            }
+            Err(e) => error!("Sniffer error: {e}"),
+        }
+    }
+
+    /// Packet ID and data as sent on the wire, compressed and length prefixed
+    fn encode(&self, frame: &[u8]) -> Bytes {
+        let body = match self.threshold {
+            None => frame.to_vec(),
+            Some(threshold) if frame.len() < threshold as usize => {
+                let mut body = VarInt { value: 0 }.to_bytes();
+                body.extend(frame);
+                body
+            }
+            Some(_) => {
+                let length = VarInt {
+                    value: frame.len() as i32,
+                };
+                let mut encoder = ZlibEncoder::new(length.to_bytes(), Compression::default());
+                encoder.write_all(frame).expect("Writing to a Vec");
+                encoder.finish().expect("Writing to a Vec")
+            }
+        };
+        let mut data = VarInt {
+            value: body.len() as i32,
+        }
+        .to_bytes();
+        data.extend(body);
+        data.into()
+    }
+}
+
+/// Splits the next whole packet, length prefix included, off the front of `buffer`
+fn next_frame(buffer: &mut BytesMut) -> Result<Option<Bytes>, DatatypeError> {
+    let mut bytes = &buffer[..];
+    let length = match VarInt::from_bytes(&mut bytes) {
+        Ok(length) => usize::try_from(length.value).map_err(|_| DatatypeError::Invalid)?,
+        Err(DatatypeError::NotEnoughData) => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    if bytes.len() < length {
+        return Ok(None);
+    }
+    let header = buffer.len() - bytes.len();
+    Ok(Some(buffer.split_to(header + length).freeze()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hooks::Hooks;
+    use crate::protocol::registry::{Packet, StatusClientbound, StatusServerbound};
+
+    #[test]
+    fn proxy() {
+        let server = TcpListener::bind("127.0.0.1:0").unwrap();
+        let server_addr = server.local_addr().unwrap();
+        let proxy = TcpListener::bind("127.0.0.1:0").unwrap();
+        let proxy_addr = proxy.local_addr().unwrap();
+
+        let (packets_tx, packets) = flume::unbounded();
+        let mut hooks = Hooks::new();
+        hooks.on_packet(move |_, packet| {
+            let _ = packets_tx.send(packet.packet.name());
+        });
+        let sink_options = SinkOptions {
+            hooks: Some(Arc::new(hooks)),
+            ..Default::default()
+        };
+        proxy.set_nonblocking(true).unwrap();
+        let cancel = CancellationToken::new();
+        let token = cancel.clone();
+        thread::spawn(move || serve(proxy, server_addr, sink_options, Rules::default(), token));
+
+        let response = [0x04, 0x00, 0x02, b'{', b'}'];
+        let mut client = TcpStream::connect(proxy_addr).unwrap();
+        client.write_all(&HANDSHAKE).unwrap();
+        let (mut upstream, _) = server.accept().unwrap();
+        let mut received = [0; 19];
+        upstream.read_exact(&mut received).unwrap();
+        assert_eq!(received, HANDSHAKE);
+        upstream.write_all(&response).unwrap();
+        let mut received = [0; 5];
+        client.read_exact(&mut received).unwrap();
+        assert_eq!(received, response);
+
+        let names: Vec<_> = (0..3)
+            .map(|_| packets.recv_timeout(Duration::from_secs(5)).unwrap())
+            .collect();
+        assert_eq!(names, ["Handshake", "StatusRequest", "StatusResponse"]);
+
+        cancel.cancel();
+        thread::sleep(Duration::from_millis(300));
+        assert!(TcpStream::connect(proxy_addr).is_err());
+    }
+
+    /// Handshake to status followed by a status request
+    const HANDSHAKE: [u8; 19] = [
+        0x10, 0x00, 0x81, 0x06, 0x09, b'l', b'o', b'c', b'a', b'l', b'h', b'o', b's', b't', 0x63,
+        0xDD, 0x01, 0x01, 0x00,
+    ];
+
+    fn new_relay(rules: Rules, sink_options: &SinkOptions) -> Relay {
+        let (server_ip, client_ip) = ([10, 0, 0, 1].into(), [10, 0, 0, 2].into());
+        let mut sniffer = Sniffer::new(server_ip, client_ip, sink_options);
+        sniffer.set_ports(50000, 25565);
+        let mut incoming = Session::new(server_ip, client_ip);
+        incoming.set_ports(50000, 25565);
+        Relay {
+            sniffer,
+            incoming,
+            rules,
+            buffers: [BytesMut::new(), BytesMut::new()],
+            threshold: None,
+            passthrough: false,
+        }
+    }
+
+    #[test]
+    fn relay() {
+        let rules = Rules::parse(
+            r#"
+            duplicate if name == "StatusRequest"
+            inject s2c 0x01 0000000000000001 if name == "StatusRequest"
+            "#,
+        )
+        .unwrap();
+        let mut relay = new_relay(rules, &SinkOptions::default());
+        assert!(relay.process(&HANDSHAKE[..5], true).is_empty());
+        let outputs: Vec<_> = relay
+            .process(&HANDSHAKE[5..], true)
+            .into_iter()
+            .map(|x| (x.server_bounded, x.data.to_vec()))
+            .collect();
+        assert_eq!(
+            outputs,
+            [
+                (true, HANDSHAKE[..17].to_vec()),
+                (true, vec![0x01, 0x00]),
+                (true, vec![0x01, 0x00]),
+                (false, vec![0x09, 0x01, 0, 0, 0, 0, 0, 0, 0, 1]),
+            ]
+        );
+
+        relay.threshold = Some(4);
+        assert_eq!(&relay.encode(&[0x01, 0x02])[..], [0x03, 0x00, 0x01, 0x02]);
+        let compressed = relay.encode(&[0x01; 32]);
+        assert_eq!(compressed[0] as usize, compressed.len() - 1);
+        assert_eq!(compressed[1], 32);
+    }
+
+    /// The outputs see what was forwarded, not what arrived
+    #[test]
+    fn forwarded() {
+        let rules = Rules::parse(
+            r#"
+            drop if name == "StatusRequest"
+            set timestamp = 5 if name == "PingRequest"
+            inject s2c 0x01 0000000000000007 if name == "PingRequest"
+            "#,
+        )
+        .unwrap();
+        let (packets_tx, packets) = flume::unbounded();
+        let mut hooks = Hooks::new();
+        hooks.on_packet(move |_, packet| {
+            let timestamp = match &packet.packet {
+                Packet::StatusServerbound(StatusServerbound::PingRequest(x)) => x.timestamp,
+                Packet::StatusClientbound(StatusClientbound::PongResponse(x)) => x.timestamp,
+                _ => 0,
+            };
+            let _ = packets_tx.send((packet.packet.name(), timestamp));
+        });
+        let sink_options = SinkOptions {
+            hooks: Some(Arc::new(hooks)),
+            ..Default::default()
+        };
+        let mut relay = new_relay(rules, &sink_options);
+
+        let mut payload = HANDSHAKE.to_vec();
+        payload.extend([0x09, 0x01, 0, 0, 0, 0, 0, 0, 0, 1]);
+        relay.process(&payload, true);
+        assert_eq!(
+            packets.try_iter().collect::<Vec<_>>(),
+            [("Handshake", 0), ("PingRequest", 5), ("PongResponse", 7)]
+        );
+    }
+}
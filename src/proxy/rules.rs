@@ -0,0 +1,312 @@
+use crate::filter::{Filter, FilterError};
+use crate::protocol::types::{Datatype, VarInt};
+use crate::protocol::{layout, registry};
+use crate::session::DecodedPacket;
+use color_eyre::eyre::WrapErr;
+use log::{info, warn};
+use std::path::Path;
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Debug, Clone, Error)]
+pub enum RuleError {
+    #[error("Line {line}: {message}")]
+    Syntax { line: usize, message: String },
+    #[error("Line {line}: {source}")]
+    Filter { line: usize, source: FilterError },
+}
+
+#[derive(Debug, Clone)]
+enum Action {
+    /// Every nth matching packet
+    Drop(u64),
+    Delay(Duration),
+    Duplicate,
+    Set {
+        path: String,
+        value: String,
+    },
+    /// Packet ID and data, sent after the matching packet
+    Inject {
+        server_bounded: bool,
+        frame: Vec<u8>,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    action: Action,
+    filter: Filter,
+    matched: u64,
+}
+
+/// Rules the proxy applies to the packets going through it, one per line as
+/// `<action> if <filter>`, with the filter in the same syntax as `--filter`. Actions are
+/// `drop`, `drop every <n>`, `delay <n>ms`, `duplicate`, `set <field> = <value>` and
+/// `inject <c2s|s2c> <id> [<hex data>]`. Empty lines and lines starting with `#` are skipped.
+///
+/// ```
+/// use minecraft_packet_sniffer::proxy::Rules;
+///
+/// let rules = Rules::parse(r#"
+///     set view_distance = 2 if name == "ClientInformation"
+///     drop every 10 if state == play && dir == c2s && id == 0x1A
+/// "#)?;
+/// # Ok::<(), minecraft_packet_sniffer::proxy::RuleError>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Rules {
+    rules: Vec<Rule>,
+}
+
+/// What becomes of one packet
+#[derive(Debug, PartialEq)]
+pub struct Outcome {
+    /// Wait before sending it, holding back what comes after it in the same direction
+    pub delay: Duration,
+    /// Times to send it, none drops it
+    pub copies: usize,
+    /// Packet ID and data if a rule changed them
+    pub frame: Option<Vec<u8>>,
+    /// Packets to send after it, with whether they are server bounded
+    pub inject: Vec<(bool, Vec<u8>)>,
+}
+
+impl Rules {
+    pub fn load(path: &Path) -> color_eyre::Result<Self> {
+        let source =
+            std::fs::read_to_string(path).wrap_err(format!("Can't read {}", path.display()))?;
+        Ok(Self::parse(&source)?)
+    }
+
+    pub fn parse(source: &str) -> Result<Self, RuleError> {
+        let mut rules = vec![];
+        for (index, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let line_number = index + 1;
+            let syntax = |message: &str| RuleError::Syntax {
+                line: line_number,
+                message: message.to_string(),
+            };
+            let (action, filter) = line
+                .split_once(" if ")
+                .ok_or_else(|| syntax("Expected `<action> if <filter>`"))?;
+            let filter = Filter::parse(filter).map_err(|source| RuleError::Filter {
+                line: line_number,
+                source,
+            })?;
+            let action = parse_action(action).map_err(|message| syntax(&message))?;
+            rules.push(Rule {
+                action,
+                filter,
+                matched: 0,
+            });
+        }
+        Ok(Self { rules })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Runs every rule matching `packet`, in order
+    pub fn apply(&mut self, packet: &DecodedPacket) -> Outcome {
+        let record = packet.record();
+        let mut outcome = Outcome {
+            delay: Duration::ZERO,
+            copies: 1,
+            frame: None,
+            inject: vec![],
+        };
+        let mut dropped = false;
+        for rule in &mut self.rules {
+            if !rule.filter.matches(&record) {
+                continue;
+            }
+            rule.matched += 1;
+            match &rule.action {
+                Action::Drop(every) => dropped |= rule.matched.is_multiple_of(*every),
+                Action::Delay(delay) => outcome.delay += *delay,
+                Action::Duplicate => outcome.copies += 1,
+                Action::Set { path, value } => {
+                    let frame = outcome.frame.as_deref().unwrap_or(&packet.frame);
+                    match set(packet, frame, path, value) {
+                        Ok(frame) => outcome.frame = Some(frame),
+                        Err(e) => warn!("Can't set {path} on {}: {e}", packet.packet.name()),
+                    }
+                }
+                Action::Inject {
+                    server_bounded,
+                    frame,
+                } => outcome.inject.push((*server_bounded, frame.clone())),
+            }
+        }
+        if dropped {
+            info!("Dropped {}", packet.packet.name());
+            outcome.copies = 0;
+        }
+        outcome
+    }
+}
+
+fn parse_action(action: &str) -> Result<Action, String> {
+    let words: Vec<_> = action.split_whitespace().collect();
+    let action = match words[..] {
+        ["drop"] => Action::Drop(1),
+        ["drop", "every", n] => match n.parse() {
+            Ok(0) | Err(_) => return Err(format!("Invalid count: {n}")),
+            Ok(n) => Action::Drop(n),
+        },
+        ["delay", delay] => {
+            let ms = delay.strip_suffix("ms").unwrap_or(delay);
+            let ms = ms.parse().map_err(|_| format!("Invalid delay: {delay}"))?;
+            Action::Delay(Duration::from_millis(ms))
+        }
+        ["duplicate"] => Action::Duplicate,
+        ["set", path, "=", ..] => {
+            let (_, value) = action.split_once('=').expect("Matched above");
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|x| x.strip_suffix('"'))
+                .unwrap_or(value);
+            Action::Set {
+                path: path.to_string(),
+                value: value.to_string(),
+            }
+        }
+        ["inject", direction, id, ref data @ ..] if data.len() <= 1 => {
+            let server_bounded = match direction {
+                "c2s" => true,
+                "s2c" => false,
+                _ => return Err(format!("Invalid direction: {direction}")),
+            };
+            let id = match id.strip_prefix("0x") {
+                Some(hex) => i32::from_str_radix(hex, 16),
+                None => id.parse(),
+            }
+            .map_err(|_| format!("Invalid packet ID: {id}"))?;
+            let mut frame = VarInt { value: id }.to_bytes();
+            if let Some(data) = data.first() {
+                frame.extend(hex(data).ok_or_else(|| format!("Invalid hex: {data}"))?);
+            }
+            Action::Inject {
+                server_bounded,
+                frame,
+            }
+        }
+        _ => return Err(format!("Unknown action: {action}")),
+    };
+    Ok(action)
+}
+
+fn hex(data: &str) -> Option<Vec<u8>> {
+    if !data.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..data.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(data.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// `frame` with the field at `path` replaced by `value`
+fn set(packet: &DecodedPacket, frame: &[u8], path: &str, value: &str) -> Result<Vec<u8>, String> {
+    let info = registry::find(packet.state, packet.server_bounded, packet.packet_id)
+        .ok_or("no layout for the packet")?;
+    let mut data = frame;
+    VarInt::from_bytes(&mut data).map_err(|e| e.to_string())?;
+    let header = frame.len() - data.len();
+    let (range, kind) = layout::locate(info.fields, data, path)
+        .map_err(|e| e.to_string())?
+        .ok_or("no such field")?;
+    let encoded =
+        layout::encode(kind, value).ok_or_else(|| format!("{value:?} isn't a {kind:?}"))?;
+
+    let mut output = frame[..header + range.start].to_vec();
+    output.extend(encoded);
+    output.extend(&frame[header + range.end..]);
+    Ok(output)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::protocol::types::State;
+    use crate::session::{Session, SessionEvent};
+    use std::time::SystemTime;
+
+    fn client_information() -> DecodedPacket {
+        let mut session = Session::new([10, 0, 0, 1].into(), [10, 0, 0, 2].into());
+        let mut payload = vec![
+            0x10, 0x00, 0x81, 0x06, 0x09, b'l', b'o', b'c', b'a', b'l', b'h', b'o', b's', b't',
+            0x63, 0xDD, 0x02, 0x01, 0x03,
+        ];
+        payload.extend([0x0F, 0x00, 0x05]);
+        payload.extend(b"en_us");
+        payload.extend([0x0C, 0x00, 0x01, 0x7F, 0x01, 0x00, 0x01, 0x00]);
+        let events = session
+            .feed_payload(&payload, true, SystemTime::UNIX_EPOCH)
+            .unwrap();
+        assert_eq!(session.state(), State::Configuration);
+        let Some(SessionEvent::Packet(packet)) = events.into_iter().last() else {
+            panic!("Expected a packet");
+        };
+        packet
+    }
+
+    #[test]
+    fn rules() {
+        let mut rules = Rules::parse(
+            r#"
+            # Comment
+            set view_distance = 2 if name == "ClientInformation"
+            set locale = "de_de" if fields.view_distance > 10
+            drop every 2 if dir == c2s
+            delay 50ms if state == configuration
+            duplicate if id == 0
+            inject s2c 0x03 if name == "ClientInformation"
+            "#,
+        )
+        .unwrap();
+        let packet = client_information();
+
+        let outcome = rules.apply(&packet);
+        let mut frame = vec![0x00, 0x05];
+        frame.extend(b"de_de");
+        frame.extend([0x02, 0x00, 0x01, 0x7F, 0x01, 0x00, 0x01, 0x00]);
+        assert_eq!(
+            outcome,
+            Outcome {
+                delay: Duration::from_millis(50),
+                copies: 2,
+                frame: Some(frame),
+                inject: vec![(false, vec![0x03])],
+            }
+        );
+        assert_eq!(rules.apply(&packet).copies, 0);
+    }
+
+    #[test]
+    fn errors() {
+        for source in [
+            "drop",
+            "drop every 0 if id == 0",
+            "explode if id == 0",
+            "inject up 0x03 if id == 0",
+            "inject c2s 0x03 zz if id == 0",
+        ] {
+            assert!(matches!(
+                Rules::parse(source),
+                Err(RuleError::Syntax { line: 1, .. })
+            ));
+        }
+        assert!(matches!(
+            Rules::parse("\ndrop if name =="),
+            Err(RuleError::Filter { line: 2, .. })
+        ));
+    }
+}
@@ -0,0 +1,423 @@
+use crate::get_client_ip_from_packet;
+use crate::hooks::Hooks;
+use crate::latency::{RoundTrip, Tracker};
+use crate::parser::ether_ip::IPPacketInfo;
+use crate::parser::PacketParser;
+use crate::protocol::registry::{LoginClientbound, LoginServerbound, Packet};
+use crate::protocol::types::error::DatatypeError;
+use crate::protocol::types::{Datatype, State, VarInt};
+use crate::protocol::{DecodeError, DecodeErrorKind, MinecraftListener};
+use crate::sink::PacketRecord;
+use crate::tcp_connection::{ConnectionState, TCPConnection};
+use bytes::{Buf, Bytes, BytesMut};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// Longest packet the protocol allows, the length prefix is at most 3 bytes
+const MAX_LENGTH: i32 = (1 << 21) - 1;
+
+/// What came out of the data fed into a [`Session`]
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub enum SessionEvent {
+    Packet(DecodedPacket),
+    /// The connection switched state, after the packet that caused it
+    StateChanged {
+        from: State,
+        to: State,
+    },
+    /// The server set the compression threshold, negative turns compression off
+    Compression(i32),
+    /// The client logged in with this name
+    Player(Arc<str>),
+    /// A packet that couldn't be decoded and was skipped
+    Malformed(DecodeError),
+    /// A request was answered, see [`crate::latency::Probe`] for which
+    RoundTrip(RoundTrip),
+    /// Both sides closed the TCP connection, or its packets can't be told apart anymore
+    Closed,
+}
+
+/// A decoded packet with where and when it was seen, the owned version of [`PacketRecord`]
+#[derive(Debug)]
+pub struct DecodedPacket {
+    pub timestamp: SystemTime,
+    pub client: SocketAddr,
+    pub server: SocketAddr,
+    pub server_bounded: bool,
+    /// State the packet was sent in, before the packet itself switched it
+    pub state: State,
+    pub player: Option<Arc<str>>,
+    pub packet_id: i32,
+    /// Packet as sent, after its length prefix (still compressed if compression is on)
+    pub raw: Bytes,
+    /// Uncompressed packet ID followed by the packet data, shares `raw` if it wasn't compressed
+    pub frame: Bytes,
+    pub packet: Packet,
+}
+
+impl DecodedPacket {
+    pub fn record(&self) -> PacketRecord<'_> {
+        PacketRecord {
+            timestamp: self.timestamp,
+            client: self.client,
+            server: self.server,
+            server_bounded: self.server_bounded,
+            state: self.state,
+            player: self.player.as_deref(),
+            packet_id: self.packet_id,
+            raw: &self.raw,
+            frame: &self.frame,
+            packet: &self.packet,
+        }
+    }
+}
+
+/// Decodes one client's connection to the server, from captured frames or TCP payloads.
+///
+/// Nothing here does any IO: feed it data with [`Self::feed_frame`], [`Self::feed_ip`] or
+/// [`Self::feed_payload`] and it hands back the events that data completes.
+#[derive(Debug)]
+pub struct Session {
+    server_ip: IpAddr,
+    client_ip: IpAddr,
+    tcp: TCPConnection,
+    listener: MinecraftListener,
+    /// Each direction is its own stream, so a packet split over segments isn't mixed with the other side
+    buffers: [BytesMut; 2],
+    expected_lengths: [usize; 2],
+    /// A length prefix was invalid, nothing after it can be split into packets
+    desynced: bool,
+    player: Option<Arc<str>>,
+    latency: Tracker,
+    hooks: Option<Arc<Hooks>>,
+}
+
+impl Session {
+    pub fn new(server_ip: IpAddr, client_ip: IpAddr) -> Self {
+        Self {
+            server_ip,
+            client_ip,
+            tcp: TCPConnection::new(),
+            listener: MinecraftListener::new(server_ip, client_ip),
+            buffers: [BytesMut::new(), BytesMut::new()],
+            expected_lengths: [0; 2],
+            desynced: false,
+            player: None,
+            latency: Tracker::new(),
+            hooks: None,
+        }
+    }
+
+    /// Runs `hooks` on every event, starting with the opened hooks right away
+    pub fn with_hooks(server_ip: IpAddr, client_ip: IpAddr, hooks: Arc<Hooks>) -> Self {
+        hooks.opened(client_ip);
+        Self {
+            hooks: Some(hooks),
+            ..Self::new(server_ip, client_ip)
+        }
+    }
+
+    pub fn state(&self) -> State {
+        self.listener.state()
+    }
+
+    /// Name the client logged in with, once known
+    pub fn player(&self) -> Option<&str> {
+        self.player.as_deref()
+    }
+
+    /// Client and server address, ports as of the last segment
+    pub fn endpoints(&self) -> (SocketAddr, SocketAddr) {
+        let (client_port, server_port) = self.tcp.ports();
+        (
+            SocketAddr::new(self.client_ip, client_port),
+            SocketAddr::new(self.server_ip, server_port),
+        )
+    }
+
+    /// Ports of the connection, for when it isn't fed TCP segments
+    pub fn set_ports(&mut self, client_port: u16, server_port: u16) {
+        self.tcp.set_ports(client_port, server_port);
+    }
+
+    /// See [`TCPConnection::gaps`]
+    pub fn gaps(&self) -> u64 {
+        self.tcp.gaps()
+    }
+
+    /// Feeds one captured Ethernet frame of this connection
+    pub fn feed_frame(
+        &mut self,
+        data: &[u8],
+        timestamp: SystemTime,
+    ) -> color_eyre::Result<Vec<SessionEvent>> {
+        let ip_packet = IPPacketInfo::from_bytes(&Bytes::copy_from_slice(data))?;
+        let (_, server_bounded) = get_client_ip_from_packet(&ip_packet, self.server_ip);
+        self.feed_ip(&ip_packet, server_bounded, timestamp)
+    }
+
+    /// Feeds one IP packet of this connection, segments may come out of order
+    pub fn feed_ip(
+        &mut self,
+        ip_packet: &IPPacketInfo,
+        server_bounded: bool,
+        timestamp: SystemTime,
+    ) -> color_eyre::Result<Vec<SessionEvent>> {
+        let state = self.tcp.segment(ip_packet, server_bounded, timestamp)?;
+        let mut events: Vec<_> = self
+            .tcp
+            .take_round_trips()
+            .into_iter()
+            .map(SessionEvent::RoundTrip)
+            .collect();
+        match state {
+            Some(ConnectionState::Data(payload, server_bounded, timestamp)) => {
+                events.extend(self.payload(&payload, server_bounded, timestamp)?);
+            }
+            Some(ConnectionState::Closed) => events.push(SessionEvent::Closed),
+            None => {}
+        }
+        Ok(self.dispatch(events))
+    }
+
+    /// Feeds TCP payload of one direction, in order, e.g. as read from a socket
+    pub fn feed_payload(
+        &mut self,
+        payload: &[u8],
+        server_bounded: bool,
+        timestamp: SystemTime,
+    ) -> color_eyre::Result<Vec<SessionEvent>> {
+        let events = self.payload(payload, server_bounded, timestamp)?;
+        Ok(self.dispatch(events))
+    }
+
+    fn dispatch(&self, events: Vec<SessionEvent>) -> Vec<SessionEvent> {
+        if let Some(hooks) = &self.hooks {
+            for event in &events {
+                hooks.dispatch(self.client_ip, event);
+            }
+        }
+        events
+    }
+
+    fn payload(
+        &mut self,
+        payload: &[u8],
+        server_bounded: bool,
+        timestamp: SystemTime,
+    ) -> color_eyre::Result<Vec<SessionEvent>> {
+        let mut events = vec![];
+        if self.desynced {
+            return Ok(events);
+        }
+        let buffer = &mut self.buffers[server_bounded as usize];
+        let expected_length = &mut self.expected_lengths[server_bounded as usize];
+        buffer.extend_from_slice(payload);
+
+        // One segment can carry several packets
+        loop {
+            if *expected_length == 0 {
+                let mut prefix = &buffer[..];
+                let kind = match VarInt::from_bytes(&mut prefix) {
+                    Ok(length) if (1..=MAX_LENGTH).contains(&length.value) => {
+                        *expected_length = length.value as usize;
+                        buffer.advance(buffer.len() - prefix.len());
+                        None
+                    }
+                    Ok(length) => Some(DecodeErrorKind::Length(length.value)),
+                    // The length itself can be split over segments
+                    Err(DatatypeError::NotEnoughData) => break,
+                    Err(e) => Some(e.into()),
+                };
+                // Without a length there's no telling where the next packet starts, so give up
+                // on the connection rather than decode garbage
+                if let Some(kind) = kind {
+                    let error = self.listener.error(server_bounded, None, 0, kind);
+                    events.push(SessionEvent::Malformed(error));
+                    events.push(SessionEvent::Closed);
+                    self.desynced = true;
+                    self.buffers = [BytesMut::new(), BytesMut::new()];
+                    break;
+                }
+            }
+
+            if buffer.len() < *expected_length {
+                break;
+            }
+
+            let raw = buffer.split_to(*expected_length).freeze();
+            *expected_length = 0;
+            let state = self.listener.state();
+            let decoded = self
+                .listener
+                .decompress(&raw, server_bounded)
+                .and_then(|frame| Ok((self.listener.parse_frame(&frame, server_bounded)?, frame)));
+            let (packet, frame) = match decoded {
+                Ok(decoded) => decoded,
+                Err(e) => {
+                    events.push(SessionEvent::Malformed(e));
+                    continue;
+                }
+            };
+
+            let name = match &packet {
+                Packet::LoginServerbound(LoginServerbound::LoginStart(packet)) => {
+                    Some(packet.name.as_str())
+                }
+                Packet::LoginClientbound(LoginClientbound::LoginSuccess(packet)) => {
+                    Some(packet.username.as_str())
+                }
+                _ => None,
+            };
+            if let Some(name) = name {
+                let name: Arc<str> = Arc::from(name);
+                self.player = Some(name.clone());
+                events.push(SessionEvent::Player(name));
+            }
+
+            let threshold = match &packet {
+                Packet::LoginClientbound(LoginClientbound::SetCompression(packet)) => {
+                    Some(packet.threshold.value)
+                }
+                _ => None,
+            };
+
+            let (client_port, server_port) = self.tcp.ports();
+            let packet = DecodedPacket {
+                timestamp,
+                client: SocketAddr::new(self.client_ip, client_port),
+                server: SocketAddr::new(self.server_ip, server_port),
+                server_bounded,
+                state,
+                player: self.player.clone(),
+                packet_id: VarInt::from_bytes(&mut &frame[..])?.value,
+                raw,
+                frame,
+                packet,
+            };
+            let round_trip = self.latency.packet(&packet.record());
+            events.push(SessionEvent::Packet(packet));
+
+            if let Some(round_trip) = round_trip {
+                events.push(SessionEvent::RoundTrip(round_trip));
+            }
+            if let Some(threshold) = threshold {
+                events.push(SessionEvent::Compression(threshold));
+            }
+            let to = self.listener.state();
+            if to != state {
+                events.push(SessionEvent::StateChanged { from: state, to });
+            }
+        }
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::protocol::registry::HandshakingServerbound;
+
+    #[test]
+    fn payload() {
+        let mut session = Session::new([10, 0, 0, 1].into(), [10, 0, 0, 2].into());
+        // Handshake to login, then the start of a login start split over two payloads
+        let handshake = [
+            0x10, 0x00, 0x81, 0x06, 0x09, b'l', b'o', b'c', b'a', b'l', b'h', b'o', b's', b't',
+            0x63, 0xDD, 0x02,
+        ];
+        let mut login_start = vec![0x17, 0x00, 0x05];
+        login_start.extend(b"Notch");
+        login_start.extend([0; 16]);
+
+        let mut payload = handshake.to_vec();
+        payload.extend(&login_start[..4]);
+        let events = session
+            .feed_payload(&payload, true, SystemTime::UNIX_EPOCH)
+            .unwrap();
+        assert_eq!(events.len(), 2);
+        let SessionEvent::Packet(packet) = &events[0] else {
+            panic!("Expected a packet, got {:?}", events[0]);
+        };
+        assert_eq!(packet.state, State::Handshaking);
+        let Packet::HandshakingServerbound(HandshakingServerbound::Handshake(handshake)) =
+            &packet.packet
+        else {
+            panic!("Expected a handshake, got {}", packet.packet);
+        };
+        assert_eq!(handshake.server_port, 25565);
+        assert!(matches!(
+            events[1],
+            SessionEvent::StateChanged {
+                from: State::Handshaking,
+                to: State::Login
+            }
+        ));
+
+        let events = session
+            .feed_payload(&login_start[4..], true, SystemTime::UNIX_EPOCH)
+            .unwrap();
+        assert!(matches!(&events[0], SessionEvent::Player(name) if &**name == "Notch"));
+        let SessionEvent::Packet(packet) = &events[1] else {
+            panic!("Expected a packet, got {:?}", events[1]);
+        };
+        assert_eq!(packet.record().player, Some("Notch"));
+        assert_eq!(session.player(), Some("Notch"));
+    }
+    #[test]
+    fn malformed() {
+        let mut session = Session::new([10, 0, 0, 1].into(), [10, 0, 0, 2].into());
+        // Handshake cut off after the protocol version, then a complete one
+        let mut payload = vec![0x03, 0x00, 0x81, 0x06];
+        payload.extend([
+            0x10, 0x00, 0x81, 0x06, 0x09, b'l', b'o', b'c', b'a', b'l', b'h', b'o', b's', b't',
+            0x63, 0xDD, 0x01,
+        ]);
+
+        let events = session
+            .feed_payload(&payload, true, SystemTime::UNIX_EPOCH)
+            .unwrap();
+        let SessionEvent::Malformed(error) = &events[0] else {
+            panic!("Expected a malformed packet, got {:?}", events[0]);
+        };
+        assert_eq!(error.state, State::Handshaking);
+        assert_eq!(error.packet_id, Some(0));
+        assert_eq!(error.offset, 3);
+        assert!(matches!(events[1], SessionEvent::Packet(_)));
+        assert_eq!(session.state(), State::Status);
+    }
+
+    #[test]
+    fn lengths() {
+        // -1 and 2^21 as VarInts, one more than 3 bytes hold
+        for (prefix, length) in [
+            (&[0xFF, 0xFF, 0xFF, 0xFF, 0x0F][..], -1),
+            (&[0x80, 0x80, 0x80, 0x01][..], 1 << 21),
+        ] {
+            let mut session = Session::new([10, 0, 0, 1].into(), [10, 0, 0, 2].into());
+            let mut payload = vec![
+                0x10, 0x00, 0x81, 0x06, 0x09, b'l', b'o', b'c', b'a', b'l', b'h', b'o', b's', b't',
+                0x63, 0xDD, 0x02,
+            ];
+            payload.extend(prefix);
+            payload.extend([0; 8]);
+
+            let events = session
+                .feed_payload(&payload, true, SystemTime::UNIX_EPOCH)
+                .unwrap();
+            assert_eq!(events.len(), 4);
+            let SessionEvent::Malformed(error) = &events[2] else {
+                panic!("Expected a malformed packet, got {:?}", events[2]);
+            };
+            assert!(matches!(error.kind, DecodeErrorKind::Length(x) if x == length));
+            assert!(matches!(events[3], SessionEvent::Closed));
+            // The rest of the connection is ignored
+            let events = session
+                .feed_payload(&[0x01, 0x00], false, SystemTime::UNIX_EPOCH)
+                .unwrap();
+            assert!(events.is_empty());
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use crate::protocol::registry::Packet;
+use crate::protocol::types::State;
+use crate::sink::{PacketRecord, Sink};
+use color_eyre::eyre::{eyre, WrapErr};
+use serde::Serialize;
+use std::fmt::{Debug, Formatter};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Where the JSON lines of every connection go
+pub struct JsonOutput {
+    writer: Mutex<Box<dyn Write + Send>>,
+}
+
+impl JsonOutput {
+    pub fn new(writer: Box<dyn Write + Send>) -> Self {
+        Self {
+            writer: Mutex::new(writer),
+        }
+    }
+
+    /// Opens `path` for writing, `-` being stdout
+    pub fn open(path: &Path) -> color_eyre::Result<Self> {
+        if path.as_os_str() == "-" {
+            return Ok(Self::new(Box::new(std::io::stdout())));
+        }
+        let file = File::create(path).wrap_err(format!("Can't create {}", path.display()))?;
+        Ok(Self::new(Box::new(BufWriter::new(file))))
+    }
+
+    /// Writes one line at once, so lines of concurrent connections don't interleave
+    fn write_line(&self, line: &str) -> color_eyre::Result<()> {
+        let mut writer = self
+            .writer
+            .lock()
+            .map_err(|_| eyre!("JSON output poisoned"))?;
+        writeln!(writer, "{line}")?;
+        Ok(())
+    }
+
+    fn flush(&self) -> color_eyre::Result<()> {
+        let mut writer = self
+            .writer
+            .lock()
+            .map_err(|_| eyre!("JSON output poisoned"))?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl Debug for JsonOutput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JsonOutput").finish_non_exhaustive()
+    }
+}
+
+#[derive(Serialize)]
+pub(super) struct JsonRecord<'a> {
+    /// Unix time in seconds
+    timestamp: f64,
+    client: SocketAddr,
+    server: SocketAddr,
+    direction: &'static str,
+    state: State,
+    id: i32,
+    name: &'static str,
+    fields: &'a Packet,
+}
+
+impl<'a> From<&'a PacketRecord<'a>> for JsonRecord<'a> {
+    fn from(record: &'a PacketRecord<'a>) -> Self {
+        Self {
+            timestamp: record.unix_time(),
+            client: record.client,
+            server: record.server,
+            direction: record.direction(),
+            state: record.state,
+            id: record.packet_id,
+            name: record.packet.name(),
+            fields: record.packet,
+        }
+    }
+}
+
+/// Writes every packet as one JSON object per line (JSON Lines)
+pub struct JsonSink {
+    output: Arc<JsonOutput>,
+}
+
+impl JsonSink {
+    pub fn new(output: Arc<JsonOutput>) -> Self {
+        Self { output }
+    }
+}
+
+impl Sink for JsonSink {
+    fn packet(&mut self, record: &PacketRecord) -> color_eyre::Result<()> {
+        let line = serde_json::to_string(&JsonRecord::from(record))?;
+        self.output.write_line(&line)
+    }
+
+    fn finish(&mut self) -> color_eyre::Result<()> {
+        self.output.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::protocol::packets::LoginStart;
+    use crate::protocol::types::{Datatype, VarInt};
+    use std::time::{Duration, UNIX_EPOCH};
+    use uuid::Uuid;
+
+    #[test]
+    fn record() {
+        let packet = Packet::from(LoginStart {
+            name: "Steve".to_string(),
+            uuid: Uuid::from_u128(1),
+        });
+        let record = PacketRecord {
+            timestamp: UNIX_EPOCH + Duration::from_millis(1_700_000_000_500),
+            client: "10.0.0.2:50000".parse().unwrap(),
+            server: "10.0.0.1:25565".parse().unwrap(),
+            server_bounded: true,
+            state: State::Login,
+            player: None,
+            packet_id: 0,
+            raw: &[],
+            frame: &[],
+            packet: &packet,
+        };
+
+        let line = serde_json::to_string(&JsonRecord::from(&record)).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "timestamp": 1_700_000_000.5,
+                "client": "10.0.0.2:50000",
+                "server": "10.0.0.1:25565",
+                "direction": "serverbound",
+                "state": "Login",
+                "id": 0,
+                "name": "LoginStart",
+                "fields": {
+                    "name": "Steve",
+                    "uuid": "00000000-0000-0000-0000-000000000001",
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn var_int() {
+        let value = VarInt::from_bytes(&mut &[0xDD, 0xC7, 0x01][..]).unwrap();
+        assert_eq!(serde_json::to_string(&value).unwrap(), "25565");
+    }
+}
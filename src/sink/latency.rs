@@ -0,0 +1,203 @@
+use crate::latency::{Probe, RoundTrip};
+use crate::session::SessionEvent;
+use crate::sink::{PacketRecord, Sink};
+use color_eyre::eyre::{eyre, WrapErr};
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{Debug, Display, Formatter};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::net::IpAddr;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::UNIX_EPOCH;
+
+/// Percentiles are over this many of the latest round trips of each player and probe
+const WINDOW: usize = 4096;
+
+/// Round trips of every connection, written out as they come and summed up as percentiles
+pub struct Latencies {
+    writer: Mutex<Box<dyn Write + Send>>,
+    /// Round trip times in milliseconds by player, or client IP before login, and probe
+    samples: Mutex<HashMap<(String, Probe), VecDeque<f64>>>,
+}
+
+impl Latencies {
+    /// Writes the round trips as CSV to `writer`
+    pub fn new(mut writer: Box<dyn Write + Send>) -> color_eyre::Result<Self> {
+        writeln!(writer, "time,client,player,probe,rtt_ms")?;
+        Ok(Self {
+            writer: Mutex::new(writer),
+            samples: Mutex::default(),
+        })
+    }
+
+    /// Opens `path` for writing, `-` being stdout
+    pub fn open(path: &Path) -> color_eyre::Result<Self> {
+        if path.as_os_str() == "-" {
+            return Self::new(Box::new(std::io::stdout()));
+        }
+        let file = File::create(path).wrap_err(format!("Can't create {}", path.display()))?;
+        Self::new(Box::new(BufWriter::new(file)))
+    }
+
+    fn add(
+        &self,
+        client: IpAddr,
+        player: Option<&str>,
+        round_trip: &RoundTrip,
+    ) -> color_eyre::Result<()> {
+        let time = round_trip
+            .timestamp
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+        let rtt = round_trip.rtt.as_secs_f64() * 1000.0;
+        let probe = round_trip.probe;
+        {
+            let mut writer = self
+                .writer
+                .lock()
+                .map_err(|_| eyre!("Latency output poisoned"))?;
+            writeln!(
+                writer,
+                "{time:.6},{client},{},{probe},{rtt:.3}",
+                player.unwrap_or("")
+            )?;
+        }
+
+        let who = player.map_or_else(|| client.to_string(), str::to_string);
+        let mut samples = self.samples.lock().unwrap();
+        let samples = samples.entry((who, probe)).or_default();
+        if samples.len() == WINDOW {
+            samples.pop_front();
+        }
+        samples.push_back(rtt);
+        Ok(())
+    }
+
+    fn flush(&self) -> color_eyre::Result<()> {
+        let mut writer = self
+            .writer
+            .lock()
+            .map_err(|_| eyre!("Latency output poisoned"))?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl Debug for Latencies {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Latencies").finish_non_exhaustive()
+    }
+}
+
+/// Nearest rank percentile of sorted `samples`
+fn percentile(samples: &[f64], p: f64) -> f64 {
+    let rank = (p / 100.0 * samples.len() as f64).ceil() as usize;
+    samples[rank.clamp(1, samples.len()) - 1]
+}
+
+impl Display for Latencies {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let samples = self.samples.lock().unwrap();
+        let mut keys: Vec<_> = samples.keys().collect();
+        keys.sort();
+        for key @ (who, probe) in keys {
+            let mut sorted: Vec<_> = samples[key].iter().copied().collect();
+            sorted.sort_by(f64::total_cmp);
+            writeln!(
+                f,
+                "{who:<16} {:<11} {:>6} samples, p50 {:>8.1} ms, p90 {:>8.1} ms, p99 {:>8.1} ms, max {:>8.1} ms",
+                probe.to_string(),
+                sorted.len(),
+                percentile(&sorted, 50.0),
+                percentile(&sorted, 90.0),
+                percentile(&sorted, 99.0),
+                sorted[sorted.len() - 1],
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Hands the round trips of one connection on, with who they belong to
+pub struct LatencySink {
+    latencies: Arc<Latencies>,
+    client: IpAddr,
+    player: Option<String>,
+}
+
+impl LatencySink {
+    pub fn new(latencies: Arc<Latencies>, client: IpAddr) -> Self {
+        Self {
+            latencies,
+            client,
+            player: None,
+        }
+    }
+}
+
+impl Sink for LatencySink {
+    fn packet(&mut self, _record: &PacketRecord) -> color_eyre::Result<()> {
+        Ok(())
+    }
+
+    fn filtered(&self) -> bool {
+        false
+    }
+
+    fn event(&mut self, event: &SessionEvent) -> color_eyre::Result<()> {
+        match event {
+            SessionEvent::Player(name) => self.player = Some(name.to_string()),
+            SessionEvent::RoundTrip(round_trip) => {
+                self.latencies
+                    .add(self.client, self.player.as_deref(), round_trip)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> color_eyre::Result<()> {
+        self.latencies.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn latencies() {
+        let latencies = Arc::new(Latencies::new(Box::new(std::io::sink())).unwrap());
+        let mut sink = LatencySink::new(latencies.clone(), [10, 0, 0, 2].into());
+        let round_trip = |probe, ms| {
+            SessionEvent::RoundTrip(RoundTrip {
+                timestamp: UNIX_EPOCH,
+                probe,
+                rtt: Duration::from_millis(ms),
+            })
+        };
+        sink.event(&round_trip(Probe::TcpClient, 5)).unwrap();
+        sink.event(&SessionEvent::Player("Notch".into())).unwrap();
+        for ms in 1..=100 {
+            sink.event(&round_trip(Probe::KeepAlive, ms)).unwrap();
+        }
+
+        let summary = latencies.to_string();
+        let lines: Vec<_> = summary.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(
+            lines[0].starts_with("10.0.0.2         tcp_client       1 samples, p50      5.0 ms")
+        );
+        assert!(lines[1].starts_with("Notch            keep_alive     100 samples, p50     50.0 ms, p90     90.0 ms, p99     99.0 ms, max    100.0 ms"));
+    }
+
+    #[test]
+    fn percentiles() {
+        assert_eq!(percentile(&[1.0], 99.0), 1.0);
+        assert_eq!(percentile(&[1.0, 2.0, 3.0, 4.0], 50.0), 2.0);
+        assert_eq!(percentile(&[1.0, 2.0, 3.0, 4.0], 90.0), 4.0);
+    }
+}
@@ -0,0 +1,251 @@
+use crate::protocol::registry::{LoginClientbound, Packet, PlayClientbound};
+use crate::protocol::types::State;
+use crate::protocol::{MINECRAFT_VERSION, PROTOCOL_VERSION};
+use crate::sink::{PacketRecord, Sink};
+use color_eyre::eyre::WrapErr;
+use log::info;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+/// Version of the replay format written by ReplayMod for 1.21.4
+const FILE_FORMAT_VERSION: i32 = 14;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MetaData {
+    singleplayer: bool,
+    server_name: String,
+    /// Milliseconds
+    duration: i32,
+    /// Unix time in milliseconds
+    date: i64,
+    mcversion: &'static str,
+    file_format: &'static str,
+    file_format_version: i32,
+    protocol: i32,
+    generator: String,
+    self_id: i32,
+    players: Vec<String>,
+}
+
+/// Writes the clientbound packets of a connection as a ReplayMod recording.
+///
+/// A `.mcpr` file is a zip holding `recording.tmcpr`, where every packet is stored as
+/// time since the start (Int, milliseconds), length (Int), packet ID and data,
+/// and `metaData.json` describing the recording.
+pub struct McprSink {
+    dir: PathBuf,
+    server_ip: IpAddr,
+    /// Only opened once there is something to record, so status pings don't leave empty files
+    writer: Option<ZipWriter<BufWriter<File>>>,
+    path: PathBuf,
+    start: SystemTime,
+    duration: i32,
+    self_id: i32,
+    players: Vec<Uuid>,
+}
+
+impl McprSink {
+    pub fn new(dir: PathBuf, server_ip: IpAddr) -> Self {
+        Self {
+            dir,
+            server_ip,
+            writer: None,
+            path: PathBuf::new(),
+            start: UNIX_EPOCH,
+            duration: 0,
+            self_id: -1,
+            players: vec![],
+        }
+    }
+
+    /// ReplayMod starts a recording at Login Success and drops compression, so only
+    /// clientbound packets from there on are kept
+    fn should_record(record: &PacketRecord) -> bool {
+        !record.server_bounded
+            && match record.state {
+                State::Login => matches!(
+                    record.packet,
+                    Packet::LoginClientbound(LoginClientbound::LoginSuccess(_))
+                ),
+                State::Configuration | State::Play => true,
+                _ => false,
+            }
+    }
+
+    fn open(&mut self, record: &PacketRecord) -> color_eyre::Result<()> {
+        let name = format!("{}.mcpr", record.session_name());
+        std::fs::create_dir_all(&self.dir)
+            .wrap_err(format!("Can't create {}", self.dir.display()))?;
+        self.path = self.dir.join(name);
+        let file =
+            File::create(&self.path).wrap_err(format!("Can't create {}", self.path.display()))?;
+
+        let mut writer = ZipWriter::new(BufWriter::new(file));
+        writer.start_file("recording.tmcpr", SimpleFileOptions::default())?;
+        self.start = record.timestamp;
+        self.writer = Some(writer);
+        Ok(())
+    }
+}
+
+impl Sink for McprSink {
+    fn filtered(&self) -> bool {
+        false
+    }
+
+    fn packet(&mut self, record: &PacketRecord) -> color_eyre::Result<()> {
+        match record.packet {
+            Packet::LoginClientbound(LoginClientbound::LoginSuccess(packet)) => {
+                self.players.push(packet.uuid);
+            }
+            Packet::PlayClientbound(PlayClientbound::LoginPlay(packet)) => {
+                self.self_id = packet.entity_id;
+            }
+            _ => {}
+        }
+
+        if !Self::should_record(record) {
+            return Ok(());
+        }
+
+        if self.writer.is_none() {
+            self.open(record)?;
+        }
+        let writer = self.writer.as_mut().expect("Opened above");
+        let time = record
+            .timestamp
+            .duration_since(self.start)
+            .unwrap_or_default()
+            .as_millis() as i32;
+        writer.write_all(&time.to_be_bytes())?;
+        writer.write_all(&(record.frame.len() as i32).to_be_bytes())?;
+        writer.write_all(record.frame)?;
+        self.duration = time;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> color_eyre::Result<()> {
+        let Some(mut writer) = self.writer.take() else {
+            return Ok(());
+        };
+
+        let meta_data = MetaData {
+            singleplayer: false,
+            server_name: self.server_ip.to_string(),
+            duration: self.duration,
+            date: self
+                .start
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as i64,
+            mcversion: MINECRAFT_VERSION,
+            file_format: "MCPR",
+            file_format_version: FILE_FORMAT_VERSION,
+            protocol: PROTOCOL_VERSION,
+            generator: format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+            self_id: self.self_id,
+            players: self.players.iter().map(Uuid::to_string).collect(),
+        };
+        writer.start_file("metaData.json", SimpleFileOptions::default())?;
+        serde_json::to_writer(&mut writer, &meta_data)?;
+        writer.finish()?.flush()?;
+
+        info!("Saved replay to {}", self.path.display());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::protocol::packets::{FinishConfiguration, Unknown};
+    use bytes::Bytes;
+    use std::io::Read;
+    use std::time::Duration;
+    use zip::ZipArchive;
+
+    fn record<'a>(
+        millis: u64,
+        server_bounded: bool,
+        state: State,
+        frame: &'a [u8],
+        packet: &'a Packet,
+    ) -> PacketRecord<'a> {
+        PacketRecord {
+            timestamp: UNIX_EPOCH + Duration::from_millis(1_700_000_000_000 + millis),
+            client: "10.0.0.2:50000".parse().unwrap(),
+            server: "10.0.0.1:25565".parse().unwrap(),
+            server_bounded,
+            state,
+            player: None,
+            packet_id: frame[0] as i32,
+            raw: &[],
+            frame,
+            packet,
+        }
+    }
+
+    #[test]
+    fn recording() {
+        let dir = std::env::temp_dir().join(format!("mcpr_{}", std::process::id()));
+        let mut sink = McprSink::new(dir.clone(), [10, 0, 0, 1].into());
+        let unknown = Packet::from(Unknown {
+            packet_id: 0,
+            server_bounded: false,
+            payload: Bytes::new(),
+        });
+
+        // Nothing is created for a status ping
+        sink.packet(&record(0, false, State::Status, &[0x01, 0x00], &unknown))
+            .unwrap();
+        // Nor for a login packet that merely has Login Success's ID
+        sink.packet(&record(1, false, State::Login, &[0x02], &unknown))
+            .unwrap();
+        assert!(sink.writer.is_none());
+
+        sink.packet(&record(
+            5,
+            false,
+            State::Configuration,
+            &[0x03],
+            &FinishConfiguration.into(),
+        ))
+        .unwrap();
+        sink.packet(&record(7, true, State::Configuration, &[0x03], &unknown))
+            .unwrap();
+        sink.packet(&record(1005, false, State::Play, &[0x2A, 0xFF], &unknown))
+            .unwrap();
+        sink.finish().unwrap();
+        assert_eq!(sink.path, dir.join("10.0.0.2_50000_1700000000.mcpr"));
+
+        let mut archive = ZipArchive::new(File::open(&sink.path).unwrap()).unwrap();
+        let mut recording = vec![];
+        archive
+            .by_name("recording.tmcpr")
+            .unwrap()
+            .read_to_end(&mut recording)
+            .unwrap();
+        assert_eq!(
+            recording,
+            [
+                0, 0, 0, 0, 0, 0, 0, 1, 0x03, // FinishConfiguration at 0 ms
+                0, 0, 0x03, 0xE8, 0, 0, 0, 2, 0x2A, 0xFF, // Play packet at 1000 ms
+            ]
+        );
+
+        let meta_data: serde_json::Value =
+            serde_json::from_reader(archive.by_name("metaData.json").unwrap()).unwrap();
+        assert_eq!(meta_data["duration"], 1000);
+        assert_eq!(meta_data["protocol"], PROTOCOL_VERSION);
+        assert_eq!(meta_data["serverName"], "10.0.0.1");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
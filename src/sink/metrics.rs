@@ -0,0 +1,39 @@
+use crate::metrics::Metrics;
+use crate::session::SessionEvent;
+use crate::sink::{PacketRecord, Sink};
+use std::sync::Arc;
+
+/// Counts one connection in the metrics
+pub struct MetricsSink {
+    metrics: Arc<Metrics>,
+}
+
+impl MetricsSink {
+    pub fn new(metrics: Arc<Metrics>) -> Self {
+        metrics.opened();
+        Self { metrics }
+    }
+}
+
+impl Sink for MetricsSink {
+    fn packet(&mut self, record: &PacketRecord) -> color_eyre::Result<()> {
+        self.metrics.packet(record);
+        Ok(())
+    }
+
+    fn filtered(&self) -> bool {
+        false
+    }
+
+    fn event(&mut self, event: &SessionEvent) -> color_eyre::Result<()> {
+        if let SessionEvent::Malformed(e) = event {
+            self.metrics.error(e);
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> color_eyre::Result<()> {
+        self.metrics.closed();
+        Ok(())
+    }
+}
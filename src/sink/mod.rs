@@ -0,0 +1,157 @@
+mod json;
+mod latency;
+mod mcpr;
+mod metrics;
+mod pcapng;
+mod script;
+mod sqlite;
+mod stats;
+mod tui;
+mod world;
+
+use crate::filter::Filter;
+use crate::hooks::Hooks;
+use crate::metrics::Metrics;
+use crate::protocol::registry::Packet;
+use crate::protocol::types::State;
+use crate::session::SessionEvent;
+use crate::tui::TuiEvent;
+use crate::world::WorldExport;
+use flume::Sender;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub use json::{JsonOutput, JsonSink};
+pub use latency::{Latencies, LatencySink};
+pub use mcpr::McprSink;
+pub use metrics::MetricsSink;
+pub use pcapng::{PcapngOutput, PcapngSink};
+#[cfg(test)]
+pub(crate) use pcapng::segment;
+pub use script::{Script, ScriptSink};
+pub use sqlite::{Database, SqliteSink};
+pub use stats::{Counter, Statistics, StatsSink};
+pub use tui::TuiSink;
+pub use world::WorldSink;
+
+/// A decoded packet together with everything a sink may want to know about it
+pub struct PacketRecord<'a> {
+    pub timestamp: SystemTime,
+    pub client: SocketAddr,
+    pub server: SocketAddr,
+    pub server_bounded: bool,
+    /// State the packet was sent in, before the packet itself switched it
+    pub state: State,
+    /// Name the client logged in with, once known
+    pub player: Option<&'a str>,
+    pub packet_id: i32,
+    /// Packet as sent, after its length prefix (still compressed if compression is on)
+    pub raw: &'a [u8],
+    /// Uncompressed packet ID followed by the packet data
+    pub frame: &'a [u8],
+    pub packet: &'a Packet,
+}
+
+impl PacketRecord<'_> {
+    /// Seconds since the Unix epoch
+    pub fn unix_time(&self) -> f64 {
+        self.timestamp
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64()
+    }
+
+    /// File name for a session starting with this packet, from the client address and the time,
+    /// so reconnects and clients sharing an IP don't overwrite each other
+    pub(crate) fn session_name(&self) -> String {
+        let since_epoch = self.timestamp.duration_since(UNIX_EPOCH).unwrap_or_default();
+        // Colons from IPv6 addresses aren't allowed in Windows paths
+        let ip = self.client.ip().to_string().replace(':', "_");
+        format!("{ip}_{}_{}", self.client.port(), since_epoch.as_secs())
+    }
+
+    pub fn direction(&self) -> &'static str {
+        if self.server_bounded {
+            "serverbound"
+        } else {
+            "clientbound"
+        }
+    }
+}
+
+/// Receives every packet of one connection
+pub trait Sink: Send {
+    fn packet(&mut self, record: &PacketRecord) -> color_eyre::Result<()>;
+
+    /// Whether the packet filter applies, recordings that need every packet opt out
+    fn filtered(&self) -> bool {
+        true
+    }
+
+    /// Session events other than packets, which come through [`Self::packet`]
+    fn event(&mut self, _event: &SessionEvent) -> color_eyre::Result<()> {
+        Ok(())
+    }
+
+    /// Called once the connection is over, even if it ended on an error
+    fn finish(&mut self) -> color_eyre::Result<()>;
+}
+
+/// Which sinks to create for each new connection
+#[derive(Debug, Clone, Default)]
+pub struct SinkOptions {
+    pub world: Option<Arc<WorldExport>>,
+    /// Folder to write ReplayMod recordings into
+    pub mcpr: Option<PathBuf>,
+    pub json: Option<Arc<JsonOutput>>,
+    pub database: Option<Arc<Database>>,
+    pub pcapng: Option<Arc<PcapngOutput>>,
+    pub tui: Option<Sender<TuiEvent>>,
+    /// Packets to log and pass to the filtered sinks
+    pub filter: Option<Arc<Filter>>,
+    /// Callbacks for the sessions of every connection
+    pub hooks: Option<Arc<Hooks>>,
+    pub script: Option<Arc<Script>>,
+    pub stats: Option<Arc<Statistics>>,
+    pub metrics: Option<Arc<Metrics>>,
+    pub latency: Option<Arc<Latencies>>,
+}
+
+impl SinkOptions {
+    pub fn create(&self, server_ip: IpAddr, client_ip: IpAddr) -> Vec<Box<dyn Sink>> {
+        let mut sinks: Vec<Box<dyn Sink>> = vec![];
+        if let Some(export) = &self.world {
+            sinks.push(Box::new(WorldSink::new(export.clone())));
+        }
+        if let Some(dir) = &self.mcpr {
+            sinks.push(Box::new(McprSink::new(dir.clone(), server_ip)));
+        }
+        if let Some(output) = &self.json {
+            sinks.push(Box::new(JsonSink::new(output.clone())));
+        }
+        if let Some(database) = &self.database {
+            sinks.push(Box::new(SqliteSink::new(database.clone())));
+        }
+        if let Some(output) = &self.pcapng {
+            sinks.push(Box::new(PcapngSink::new(output.clone())));
+        }
+        if let Some(tx) = &self.tui {
+            sinks.push(Box::new(TuiSink::new(tx.clone())));
+        }
+        if let Some(script) = &self.script {
+            sinks.push(Box::new(ScriptSink::new(script.clone(), client_ip)));
+        }
+        if let Some(statistics) = &self.stats {
+            sinks.push(Box::new(StatsSink::new(statistics.clone())));
+        }
+        if let Some(metrics) = &self.metrics {
+            sinks.push(Box::new(MetricsSink::new(metrics.clone())));
+        }
+        if let Some(latencies) = &self.latency {
+            sinks.push(Box::new(LatencySink::new(latencies.clone(), client_ip)));
+        }
+        sinks
+    }
+}
@@ -0,0 +1,316 @@
+use crate::protocol::types::VarInt;
+use crate::sink::{PacketRecord, Sink};
+use color_eyre::eyre::{eyre, WrapErr};
+use std::fmt::{Debug, Formatter};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::net::{IpAddr, SocketAddr};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECTION_HEADER: u32 = 0x0A0D0D0A;
+const INTERFACE_DESCRIPTION: u32 = 1;
+const ENHANCED_PACKET: u32 = 6;
+const BYTE_ORDER_MAGIC: u32 = 0x1A2B3C4D;
+/// Packets start with their IPv4 or IPv6 header
+const LINKTYPE_RAW: u16 = 101;
+const OPT_END: u16 = 0;
+const OPT_COMMENT: u16 = 1;
+/// Keeps IPv4 total length and IPv6 payload length in range
+const MAX_SEGMENT: usize = 65_000;
+
+/// Minimal pcapng writer, timestamps in microseconds
+pub struct PcapngWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> PcapngWriter<W> {
+    pub fn new(mut writer: W) -> std::io::Result<Self> {
+        let mut section = vec![];
+        section.extend(BYTE_ORDER_MAGIC.to_le_bytes());
+        section.extend(1u16.to_le_bytes());
+        section.extend(0u16.to_le_bytes());
+        // Section length isn't known up front
+        section.extend((-1i64).to_le_bytes());
+        write_block(&mut writer, SECTION_HEADER, &section)?;
+
+        let mut interface = vec![];
+        interface.extend(LINKTYPE_RAW.to_le_bytes());
+        interface.extend(0u16.to_le_bytes());
+        interface.extend(0u32.to_le_bytes());
+        write_block(&mut writer, INTERFACE_DESCRIPTION, &interface)?;
+        Ok(Self { writer })
+    }
+
+    pub fn write_packet(
+        &mut self,
+        timestamp: SystemTime,
+        data: &[u8],
+        comment: Option<&str>,
+    ) -> std::io::Result<()> {
+        let micros = timestamp
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_micros() as u64;
+
+        let mut body = vec![];
+        body.extend(0u32.to_le_bytes());
+        body.extend(((micros >> 32) as u32).to_le_bytes());
+        body.extend((micros as u32).to_le_bytes());
+        body.extend((data.len() as u32).to_le_bytes());
+        body.extend((data.len() as u32).to_le_bytes());
+        body.extend(data);
+        pad(&mut body);
+        if let Some(comment) = comment {
+            body.extend(OPT_COMMENT.to_le_bytes());
+            body.extend((comment.len() as u16).to_le_bytes());
+            body.extend(comment.as_bytes());
+            pad(&mut body);
+            body.extend(OPT_END.to_le_bytes());
+            body.extend(0u16.to_le_bytes());
+        }
+        write_block(&mut self.writer, ENHANCED_PACKET, &body)
+    }
+
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+fn pad(bytes: &mut Vec<u8>) {
+    bytes.resize(bytes.len().next_multiple_of(4), 0);
+}
+
+fn write_block<W: Write>(writer: &mut W, block_type: u32, body: &[u8]) -> std::io::Result<()> {
+    let length = (body.len() + 12) as u32;
+    writer.write_all(&block_type.to_le_bytes())?;
+    writer.write_all(&length.to_le_bytes())?;
+    writer.write_all(body)?;
+    writer.write_all(&length.to_le_bytes())
+}
+
+/// Internet checksum, over the concatenation of `parts`
+fn checksum(parts: &[&[u8]]) -> u16 {
+    let bytes = parts.iter().flat_map(|x| x.iter().copied());
+    let mut sum = 0u32;
+    let mut high = None;
+    for byte in bytes {
+        match high.take() {
+            None => high = Some(byte),
+            Some(h) => sum += u16::from_be_bytes([h, byte]) as u32,
+        }
+    }
+    if let Some(h) = high {
+        sum += u16::from_be_bytes([h, 0]) as u32;
+    }
+    while sum > 0xFFFF {
+        sum = (sum & 0xFFFF) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+/// IP and TCP headers around `payload`, as a PSH/ACK segment
+pub(crate) fn segment(src: SocketAddr, dst: SocketAddr, seq: u32, ack: u32, payload: &[u8]) -> Vec<u8> {
+    let mut tcp = vec![];
+    tcp.extend(src.port().to_be_bytes());
+    tcp.extend(dst.port().to_be_bytes());
+    tcp.extend(seq.to_be_bytes());
+    tcp.extend(ack.to_be_bytes());
+    // Data offset of 5 words, PSH and ACK
+    tcp.extend([0x50, 0x18]);
+    tcp.extend(u16::MAX.to_be_bytes());
+    tcp.extend([0; 4]);
+    tcp.extend(payload);
+    let tcp_length = (tcp.len() as u32).to_be_bytes();
+
+    match (src.ip(), dst.ip()) {
+        (IpAddr::V4(src), IpAddr::V4(dst)) => {
+            let pseudo = [&src.octets()[..], &dst.octets(), &[0, 6], &tcp_length[2..]];
+            let tcp_checksum = checksum(&[&pseudo.concat(), &tcp]);
+            tcp[16..18].copy_from_slice(&tcp_checksum.to_be_bytes());
+
+            let mut ip = vec![0x45, 0];
+            ip.extend(((20 + tcp.len()) as u16).to_be_bytes());
+            // ID, don't fragment, TTL 64, TCP
+            ip.extend([0, 0, 0x40, 0, 64, 6, 0, 0]);
+            ip.extend(src.octets());
+            ip.extend(dst.octets());
+            let ip_checksum = checksum(&[&ip]);
+            ip[10..12].copy_from_slice(&ip_checksum.to_be_bytes());
+            ip.extend(tcp);
+            ip
+        }
+        (src, dst) => {
+            let src = match src {
+                IpAddr::V4(x) => x.to_ipv6_mapped(),
+                IpAddr::V6(x) => x,
+            };
+            let dst = match dst {
+                IpAddr::V4(x) => x.to_ipv6_mapped(),
+                IpAddr::V6(x) => x,
+            };
+            let pseudo = [&src.octets()[..], &dst.octets(), &tcp_length, &[0, 0, 0, 6]];
+            let tcp_checksum = checksum(&[&pseudo.concat(), &tcp]);
+            tcp[16..18].copy_from_slice(&tcp_checksum.to_be_bytes());
+
+            let mut ip = vec![0x60, 0, 0, 0];
+            ip.extend((tcp.len() as u16).to_be_bytes());
+            // TCP, hop limit 64
+            ip.extend([6, 64]);
+            ip.extend(src.octets());
+            ip.extend(dst.octets());
+            ip.extend(tcp);
+            ip
+        }
+    }
+}
+
+/// pcapng file shared by the sinks of every connection
+pub struct PcapngOutput {
+    writer: Mutex<PcapngWriter<BufWriter<File>>>,
+}
+
+impl PcapngOutput {
+    pub fn create(path: &Path) -> color_eyre::Result<Self> {
+        let file = File::create(path).wrap_err(format!("Can't create {}", path.display()))?;
+        Ok(Self {
+            writer: Mutex::new(PcapngWriter::new(BufWriter::new(file))?),
+        })
+    }
+
+    fn lock(&self) -> color_eyre::Result<std::sync::MutexGuard<'_, PcapngWriter<BufWriter<File>>>> {
+        self.writer
+            .lock()
+            .map_err(|_| eyre!("pcapng output poisoned"))
+    }
+}
+
+impl Debug for PcapngOutput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PcapngOutput").finish_non_exhaustive()
+    }
+}
+
+/// Writes each Minecraft packet as its own TCP segment, commented with its state and name.
+///
+/// Segments are rebuilt from the reassembled stream, so every packet of the connection
+/// (or of the filter) is there in order, without retransmissions or unrelated traffic.
+pub struct PcapngSink {
+    output: Arc<PcapngOutput>,
+    /// Next sequence number, client to server then server to client
+    seq: [u32; 2],
+}
+
+impl PcapngSink {
+    pub fn new(output: Arc<PcapngOutput>) -> Self {
+        Self {
+            output,
+            seq: [1, 1],
+        }
+    }
+}
+
+impl Sink for PcapngSink {
+    fn packet(&mut self, record: &PacketRecord) -> color_eyre::Result<()> {
+        let (src, dst, direction) = if record.server_bounded {
+            (record.client, record.server, 0)
+        } else {
+            (record.server, record.client, 1)
+        };
+        let mut payload = VarInt {
+            value: record.raw.len() as i32,
+        }
+        .to_bytes();
+        payload.extend(record.raw);
+
+        let comment = format!(
+            "{} | {} | {} (0x{:02X})",
+            record.direction(),
+            record.state,
+            record.packet.name(),
+            record.packet_id
+        );
+        let mut writer = self.output.lock()?;
+        for (i, chunk) in payload.chunks(MAX_SEGMENT).enumerate() {
+            let ack = self.seq[1 - direction];
+            let data = segment(src, dst, self.seq[direction], ack, chunk);
+            self.seq[direction] = self.seq[direction].wrapping_add(chunk.len() as u32);
+            writer.write_packet(
+                record.timestamp,
+                &data,
+                (i == 0).then_some(comment.as_str()),
+            )?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> color_eyre::Result<()> {
+        self.output.lock()?.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn read_u32(bytes: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn blocks() {
+        let mut writer = PcapngWriter::new(vec![]).unwrap();
+        let data = segment(
+            "10.0.0.2:50000".parse().unwrap(),
+            "10.0.0.1:25565".parse().unwrap(),
+            1,
+            1,
+            &[0x02, 0x00, 0x05],
+        );
+        writer
+            .write_packet(
+                UNIX_EPOCH + std::time::Duration::from_micros(0x1_0000_0002),
+                &data,
+                Some("Login"),
+            )
+            .unwrap();
+        let bytes = writer.writer;
+
+        // Section header, then interface description
+        assert_eq!(read_u32(&bytes, 0), SECTION_HEADER);
+        let section_length = read_u32(&bytes, 4) as usize;
+        assert_eq!(read_u32(&bytes, section_length + 4), 20);
+        let packet = section_length + 20;
+        assert_eq!(read_u32(&bytes, packet), ENHANCED_PACKET);
+        let length = read_u32(&bytes, packet + 4) as usize;
+        assert_eq!(read_u32(&bytes, packet + length - 4), length as u32);
+        assert_eq!(bytes.len(), packet + length);
+
+        // Timestamp high and low, lengths, then the packet and its comment
+        assert_eq!(read_u32(&bytes, packet + 12), 1);
+        assert_eq!(read_u32(&bytes, packet + 16), 2);
+        assert_eq!(read_u32(&bytes, packet + 20), 43);
+        let data = &bytes[packet + 28..packet + 28 + 43];
+        assert_eq!(checksum(&[&data[..20]]), 0);
+        assert_eq!(&data[40..], [0x02, 0x00, 0x05]);
+        assert_eq!(&bytes[packet + 76..packet + 81], b"Login");
+    }
+
+    #[test]
+    fn tcp_checksum() {
+        let data = segment(
+            "[::1]:50000".parse().unwrap(),
+            "[::1]:25565".parse().unwrap(),
+            1,
+            1,
+            &[1, 2, 3],
+        );
+        let src = &data[8..24];
+        let dst = &data[24..40];
+        let tcp = &data[40..];
+        let length = (tcp.len() as u32).to_be_bytes();
+        assert_eq!(checksum(&[src, dst, &length, &[0, 0, 0, 6], tcp]), 0);
+    }
+}
@@ -0,0 +1,269 @@
+use crate::session::SessionEvent;
+use crate::sink::json::JsonRecord;
+use crate::sink::{PacketRecord, Sink};
+use color_eyre::eyre::{eyre, WrapErr};
+use log::{debug, error, info, warn};
+use rhai::{CallFnOptions, Dynamic, Engine, EvalAltResult, FuncArgs, Map, Scope, AST};
+use std::fmt::{Debug, Formatter};
+use std::io::Write;
+use std::net::IpAddr;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// A Rhai script run on every connection, compiled once.
+///
+/// It can define `on_open(client)`, `on_packet(packet)`, `on_event(event)` and `on_close()`, each
+/// called with `this` bound to a map kept for the whole connection. Packets are maps with the
+/// same keys as the JSON output plus `player`, events have a `kind` (`state_changed`,
+/// `compression`, `player`, `malformed`, `round_trip` or `closed`). `alert(message)` logs a warning and
+/// `emit(line)` writes a line to the output.
+pub struct Script {
+    ast: AST,
+    output: Mutex<Box<dyn Write + Send>>,
+}
+
+impl Script {
+    pub fn new(source: &str, output: Box<dyn Write + Send>) -> color_eyre::Result<Self> {
+        let ast = Engine::new()
+            .compile(source)
+            .map_err(|e| eyre!("Script error: {e}"))?;
+        Ok(Self {
+            ast,
+            output: Mutex::new(output),
+        })
+    }
+
+    /// Compiles the script at `path`, emitting to stdout
+    pub fn load(path: &Path) -> color_eyre::Result<Self> {
+        let source =
+            std::fs::read_to_string(path).wrap_err(format!("Can't read {}", path.display()))?;
+        Self::new(&source, Box::new(std::io::stdout())).wrap_err(format!("In {}", path.display()))
+    }
+
+    fn defines(&self, name: &str) -> bool {
+        self.ast.iter_functions().any(|f| f.name == name)
+    }
+
+    /// Writes one line at once, so lines of concurrent connections don't interleave
+    fn write_line(&self, line: &str) -> color_eyre::Result<()> {
+        let mut output = self
+            .output
+            .lock()
+            .map_err(|_| eyre!("Script output poisoned"))?;
+        writeln!(output, "{line}")?;
+        Ok(())
+    }
+
+    fn flush(&self) -> color_eyre::Result<()> {
+        let mut output = self
+            .output
+            .lock()
+            .map_err(|_| eyre!("Script output poisoned"))?;
+        output.flush()?;
+        Ok(())
+    }
+}
+
+impl Debug for Script {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Script").finish_non_exhaustive()
+    }
+}
+
+/// Runs the script's functions for one connection
+pub struct ScriptSink {
+    script: Arc<Script>,
+    engine: Engine,
+    scope: Scope<'static>,
+    /// `this` of every call
+    state: Dynamic,
+}
+
+impl ScriptSink {
+    pub fn new(script: Arc<Script>, client_ip: IpAddr) -> Self {
+        let mut engine = Engine::new();
+        engine.on_print(move |x| info!("{client_ip}: {x}"));
+        engine.on_debug(move |x, _, _| debug!("{client_ip}: {x}"));
+        engine.register_fn("alert", move |message: &str| {
+            warn!("{client_ip}: {message}");
+        });
+        let output = script.clone();
+        engine.register_fn(
+            "emit",
+            move |line: &str| -> Result<(), Box<EvalAltResult>> {
+                output.write_line(line).map_err(|e| e.to_string().into())
+            },
+        );
+
+        let mut sink = Self {
+            script,
+            engine,
+            scope: Scope::new(),
+            state: Map::new().into(),
+        };
+        if let Err(e) = sink.call("on_open", (client_ip.to_string(),)) {
+            error!("{e:?}");
+        }
+        sink
+    }
+
+    /// Calls `name` if the script defines it
+    fn call(&mut self, name: &str, args: impl FuncArgs) -> color_eyre::Result<()> {
+        if !self.script.defines(name) {
+            return Ok(());
+        }
+        let options = CallFnOptions::new()
+            .eval_ast(false)
+            .bind_this_ptr(&mut self.state);
+        self.engine
+            .call_fn_with_options::<Dynamic>(options, &mut self.scope, &self.script.ast, name, args)
+            .map(|_| ())
+            .map_err(|e| eyre!("Script error in {name}: {e}"))
+    }
+}
+
+fn event(kind: &str, fields: impl IntoIterator<Item = (&'static str, Dynamic)>) -> Dynamic {
+    let mut map = Map::from_iter(fields.into_iter().map(|(k, v)| (k.into(), v)));
+    map.insert("kind".into(), kind.into());
+    map.into()
+}
+
+impl Sink for ScriptSink {
+    fn packet(&mut self, record: &PacketRecord) -> color_eyre::Result<()> {
+        let packet = rhai::serde::to_dynamic(JsonRecord::from(record))
+            .map_err(|e| eyre!("Can't convert {}: {e}", record.packet.name()))?;
+        let mut packet = packet.cast::<Map>();
+        let player = record.player.map_or(Dynamic::UNIT, |x| x.into());
+        packet.insert("player".into(), player);
+        self.call("on_packet", (Dynamic::from_map(packet),))
+    }
+
+    /// Scripts do their own filtering
+    fn filtered(&self) -> bool {
+        false
+    }
+
+    fn event(&mut self, event: &SessionEvent) -> color_eyre::Result<()> {
+        let event = match event {
+            SessionEvent::Packet(_) => return Ok(()),
+            SessionEvent::StateChanged { from, to } => self::event(
+                "state_changed",
+                [
+                    ("from", from.to_string().into()),
+                    ("to", to.to_string().into()),
+                ],
+            ),
+            SessionEvent::Compression(threshold) => {
+                self::event("compression", [("threshold", (*threshold as i64).into())])
+            }
+            SessionEvent::Player(name) => {
+                self::event("player", [("name", name.to_string().into())])
+            }
+            SessionEvent::Malformed(e) => {
+                self::event("malformed", [("error", e.to_string().into())])
+            }
+            SessionEvent::RoundTrip(x) => self::event(
+                "round_trip",
+                [
+                    ("probe", x.probe.to_string().into()),
+                    ("rtt_ms", (x.rtt.as_secs_f64() * 1000.0).into()),
+                ],
+            ),
+            SessionEvent::Closed => self::event("closed", []),
+        };
+        self.call("on_event", (event,))
+    }
+
+    fn finish(&mut self) -> color_eyre::Result<()> {
+        self.call("on_close", ())?;
+        self.script.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::session::Session;
+    use std::time::SystemTime;
+
+    /// Output the test can read back
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn script() {
+        let output = Shared::default();
+        let script = Script::new(
+            r#"
+            fn on_open(client) {
+                this.client = client;
+                this.packets = 0;
+            }
+            fn on_packet(packet) {
+                this.packets += 1;
+                if packet.name == "LoginStart" {
+                    emit(`${this.client} ${packet.direction} ${packet.fields.name} ${packet.state}`);
+                }
+            }
+            fn on_event(event) {
+                if event.kind == "state_changed" {
+                    emit(`${event.from} -> ${event.to}`);
+                }
+            }
+            fn on_close() {
+                emit(`${this.packets} packets`);
+            }
+            "#,
+            Box::new(output.clone()),
+        )
+        .unwrap();
+
+        let client = [10, 0, 0, 2].into();
+        let mut session = Session::new([10, 0, 0, 1].into(), client);
+        let mut sink = ScriptSink::new(Arc::new(script), client);
+        let mut payload = vec![
+            0x10, 0x00, 0x81, 0x06, 0x09, b'l', b'o', b'c', b'a', b'l', b'h', b'o', b's', b't',
+            0x63, 0xDD, 0x02, 0x17, 0x00, 0x05,
+        ];
+        payload.extend(b"Notch");
+        payload.extend([0; 16]);
+        let events = session
+            .feed_payload(&payload, true, SystemTime::UNIX_EPOCH)
+            .unwrap();
+        for event in &events {
+            match event {
+                SessionEvent::Packet(packet) => sink.packet(&packet.record()).unwrap(),
+                event => sink.event(event).unwrap(),
+            }
+        }
+        sink.finish().unwrap();
+
+        let output = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(
+            output,
+            "Handshaking -> Login\n10.0.0.2 serverbound Notch Login\n2 packets\n"
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert!(Script::new("fn on_packet(packet) {", Box::new(std::io::sink())).is_err());
+
+        let script = Script::new(
+            "fn on_close() { this.missing += 1 }",
+            Box::new(std::io::sink()),
+        );
+        let mut sink = ScriptSink::new(Arc::new(script.unwrap()), [10, 0, 0, 2].into());
+        assert!(sink.finish().is_err());
+    }
+}
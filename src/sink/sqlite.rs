@@ -0,0 +1,265 @@
+use crate::protocol::registry::{LoginClientbound, LoginServerbound, Packet};
+use crate::sink::{PacketRecord, Sink};
+use color_eyre::eyre::{eyre, WrapErr};
+use rusqlite::{params, Connection};
+use std::fmt::{Debug, Formatter};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// Times are Unix seconds. `packets.packet_id` is the protocol ID, `packets.id` the row ID.
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS connections (
+    id INTEGER PRIMARY KEY,
+    client TEXT NOT NULL,
+    server TEXT NOT NULL,
+    start_time REAL NOT NULL,
+    end_time REAL
+);
+CREATE TABLE IF NOT EXISTS players (
+    connection_id INTEGER NOT NULL REFERENCES connections(id),
+    uuid TEXT NOT NULL,
+    name TEXT NOT NULL,
+    time REAL NOT NULL,
+    PRIMARY KEY (connection_id, uuid)
+);
+CREATE TABLE IF NOT EXISTS packets (
+    id INTEGER PRIMARY KEY,
+    connection_id INTEGER NOT NULL REFERENCES connections(id),
+    player_uuid TEXT,
+    time REAL NOT NULL,
+    direction TEXT NOT NULL,
+    state TEXT NOT NULL,
+    packet_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    data BLOB NOT NULL
+);
+CREATE TABLE IF NOT EXISTS fields (
+    packet INTEGER NOT NULL REFERENCES packets(id),
+    name TEXT NOT NULL,
+    value TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS players_uuid ON players(uuid);
+CREATE INDEX IF NOT EXISTS packets_player_time ON packets(player_uuid, time);
+CREATE INDEX IF NOT EXISTS packets_time ON packets(time);
+CREATE INDEX IF NOT EXISTS fields_packet ON fields(packet);
+";
+
+/// SQLite file shared by the sinks of every connection
+pub struct Database {
+    connection: Mutex<Connection>,
+}
+
+impl Database {
+    pub fn open(path: &Path) -> color_eyre::Result<Self> {
+        let connection =
+            Connection::open(path).wrap_err(format!("Can't open {}", path.display()))?;
+        // Every packet is its own transaction, don't wait for each one to hit the disk
+        connection.execute_batch("PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL;")?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self {
+            connection: Mutex::new(connection),
+        })
+    }
+
+    fn lock(&self) -> color_eyre::Result<std::sync::MutexGuard<'_, Connection>> {
+        self.connection
+            .lock()
+            .map_err(|_| eyre!("Database poisoned"))
+    }
+}
+
+impl Debug for Database {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Database").finish_non_exhaustive()
+    }
+}
+
+/// Stores the connection, its player and every packet with its top level fields
+pub struct SqliteSink {
+    database: Arc<Database>,
+    /// Row of the connection, inserted with its first packet
+    connection_id: Option<i64>,
+    player: Option<Uuid>,
+    last_time: f64,
+}
+
+impl SqliteSink {
+    pub fn new(database: Arc<Database>) -> Self {
+        Self {
+            database,
+            connection_id: None,
+            player: None,
+            last_time: 0.0,
+        }
+    }
+
+    fn add_player(
+        &mut self,
+        connection: &Connection,
+        connection_id: i64,
+        uuid: Uuid,
+        name: &str,
+        time: f64,
+    ) -> color_eyre::Result<()> {
+        self.player = Some(uuid);
+        connection
+            .prepare_cached(
+                "INSERT INTO players (connection_id, uuid, name, time) VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT DO UPDATE SET name = excluded.name",
+            )?
+            .execute(params![connection_id, uuid.to_string(), name, time])?;
+        Ok(())
+    }
+}
+
+impl Sink for SqliteSink {
+    fn packet(&mut self, record: &PacketRecord) -> color_eyre::Result<()> {
+        let database = self.database.clone();
+        let mut connection = database.lock()?;
+        let time = record.unix_time();
+        self.last_time = time;
+
+        let connection_id = match self.connection_id {
+            Some(id) => id,
+            None => {
+                connection
+                    .prepare_cached(
+                        "INSERT INTO connections (client, server, start_time) VALUES (?1, ?2, ?3)",
+                    )?
+                    .execute(params![
+                        record.client.to_string(),
+                        record.server.to_string(),
+                        time
+                    ])?;
+                *self.connection_id.insert(connection.last_insert_rowid())
+            }
+        };
+
+        if let Packet::LoginServerbound(LoginServerbound::LoginStart(packet)) = record.packet {
+            self.add_player(&connection, connection_id, packet.uuid, &packet.name, time)?;
+        } else if let Packet::LoginClientbound(LoginClientbound::LoginSuccess(packet)) =
+            record.packet
+        {
+            self.add_player(
+                &connection,
+                connection_id,
+                packet.uuid,
+                &packet.username,
+                time,
+            )?;
+        }
+
+        let transaction = connection.transaction()?;
+        transaction
+            .prepare_cached(
+                "INSERT INTO packets (connection_id, player_uuid, time, direction, state, packet_id, name, data)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            )?
+            .execute(params![
+                connection_id,
+                self.player.map(|x| x.to_string()),
+                time,
+                record.direction(),
+                record.state.to_string(),
+                record.packet_id,
+                record.packet.name(),
+                record.frame
+            ])?;
+        let packet_row = transaction.last_insert_rowid();
+
+        // Nested values are kept as JSON, strings as they are so they compare easily
+        if let serde_json::Value::Object(fields) = serde_json::to_value(record.packet)? {
+            let mut statement = transaction
+                .prepare_cached("INSERT INTO fields (packet, name, value) VALUES (?1, ?2, ?3)")?;
+            for (name, value) in fields {
+                let value = match value {
+                    serde_json::Value::String(s) => s,
+                    value => value.to_string(),
+                };
+                statement.execute(params![packet_row, name, value])?;
+            }
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> color_eyre::Result<()> {
+        if let Some(connection_id) = self.connection_id {
+            self.database.lock()?.execute(
+                "UPDATE connections SET end_time = ?1 WHERE id = ?2",
+                params![self.last_time, connection_id],
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::protocol::packets::{LoginAcknowledged, LoginStart};
+    use crate::protocol::types::State;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn record<'a>(
+        secs: u64,
+        server_bounded: bool,
+        packet_id: i32,
+        packet: &'a Packet,
+    ) -> PacketRecord<'a> {
+        PacketRecord {
+            timestamp: UNIX_EPOCH + Duration::from_secs(secs),
+            client: "10.0.0.2:50000".parse().unwrap(),
+            server: "10.0.0.1:25565".parse().unwrap(),
+            server_bounded,
+            state: State::Login,
+            player: None,
+            packet_id,
+            raw: &[],
+            frame: &[],
+            packet,
+        }
+    }
+
+    #[test]
+    fn player_packets() {
+        let database = Arc::new(Database::open(Path::new(":memory:")).unwrap());
+        let mut sink = SqliteSink::new(database.clone());
+        let uuid = Uuid::from_u128(1);
+        let login_start = LoginStart {
+            name: "Steve".to_string(),
+            uuid,
+        };
+        sink.packet(&record(100, true, 0, &login_start.into()))
+            .unwrap();
+        sink.packet(&record(105, true, 3, &LoginAcknowledged.into()))
+            .unwrap();
+        sink.finish().unwrap();
+
+        let connection = database.lock().unwrap();
+        let names: Vec<(String, Option<String>)> = connection
+            .prepare(
+                "SELECT packets.name, fields.value FROM packets
+                 LEFT JOIN fields ON fields.packet = packets.id AND fields.name = 'name'
+                 WHERE player_uuid = ?1 AND time BETWEEN 100 AND 110 ORDER BY time",
+            )
+            .unwrap()
+            .query_map([uuid.to_string()], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            names,
+            vec![
+                ("LoginStart".to_string(), Some("Steve".to_string())),
+                ("LoginAcknowledged".to_string(), None),
+            ]
+        );
+
+        let end_time: f64 = connection
+            .query_row("SELECT end_time FROM connections", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(end_time, 105.0);
+    }
+}
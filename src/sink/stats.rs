@@ -0,0 +1,271 @@
+use crate::protocol::types::State;
+use crate::sink::{PacketRecord, Sink};
+use itertools::Itertools;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// Packet and byte counts
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Counter {
+    pub packets: u64,
+    /// Packet size as sent, compressed if compression was on
+    pub compressed: u64,
+    /// Size of the packet ID and data
+    pub uncompressed: u64,
+}
+
+impl Counter {
+    fn add(&mut self, other: Counter) {
+        self.packets += other.packets;
+        self.compressed += other.compressed;
+        self.uncompressed += other.uncompressed;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Key {
+    client: SocketAddr,
+    server_bounded: bool,
+    state: State,
+    packet_id: i32,
+    name: &'static str,
+}
+
+#[derive(Debug, Default)]
+struct Totals {
+    counters: HashMap<Key, Counter>,
+    first: Option<SystemTime>,
+    /// Every connection added up, one per second since the first packet
+    timeline: Vec<Counter>,
+}
+
+/// Traffic counters of every connection, by direction, state and packet type
+#[derive(Debug, Default)]
+pub struct Statistics {
+    totals: Mutex<Totals>,
+}
+
+impl Statistics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&self, record: &PacketRecord) {
+        let key = Key {
+            client: record.client,
+            server_bounded: record.server_bounded,
+            state: record.state,
+            packet_id: record.packet_id,
+            name: record.packet.name(),
+        };
+        let counter = Counter {
+            packets: 1,
+            compressed: record.raw.len() as u64,
+            uncompressed: record.frame.len() as u64,
+        };
+        let mut totals = self.totals.lock().unwrap();
+        totals.counters.entry(key).or_default().add(counter);
+
+        let first = *totals.first.get_or_insert(record.timestamp);
+        // Workers can be slightly out of order, count those in the first second
+        let second = record
+            .timestamp
+            .duration_since(first)
+            .unwrap_or_default()
+            .as_secs() as usize;
+        if totals.timeline.len() <= second {
+            totals.timeline.resize(second + 1, Counter::default());
+        }
+        totals.timeline[second].add(counter);
+    }
+
+    /// Totals of each second since the first packet
+    pub fn timeline(&self) -> Vec<Counter> {
+        self.totals.lock().unwrap().timeline.clone()
+    }
+}
+
+fn bytes(bytes: f64) -> String {
+    let units = ["B", "KiB", "MiB", "GiB"];
+    let exponent = ((bytes.max(1.0).log2() / 10.0) as usize).min(units.len() - 1);
+    let value = bytes / (1u64 << (10 * exponent)) as f64;
+    if exponent == 0 {
+        format!("{value:.0} {}", units[0])
+    } else {
+        format!("{value:.1} {}", units[exponent])
+    }
+}
+
+fn direction(server_bounded: bool) -> &'static str {
+    if server_bounded {
+        "c2s"
+    } else {
+        "s2c"
+    }
+}
+
+impl Display for Statistics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let totals = self.totals.lock().unwrap();
+        let mut total = Counter::default();
+        totals.timeline.iter().for_each(|x| total.add(*x));
+        let seconds = totals.timeline.len().max(1) as f64;
+        let peak = totals
+            .timeline
+            .iter()
+            .map(|x| x.compressed)
+            .max()
+            .unwrap_or(0);
+        writeln!(
+            f,
+            "{} packets over {seconds:.0}s, {} sent ({} uncompressed), {}/s average, {}/s peak",
+            total.packets,
+            bytes(total.compressed as f64),
+            bytes(total.uncompressed as f64),
+            bytes(total.compressed as f64 / seconds),
+            bytes(peak as f64),
+        )?;
+
+        let mut types: HashMap<_, Counter> = HashMap::new();
+        let mut connections: HashMap<_, [Counter; 2]> = HashMap::new();
+        for (key, counter) in &totals.counters {
+            types
+                .entry((key.server_bounded, key.state, key.packet_id, key.name))
+                .or_default()
+                .add(*counter);
+            connections.entry(key.client).or_default()[key.server_bounded as usize].add(*counter);
+        }
+
+        writeln!(f, "Packet types by bytes sent:")?;
+        let types = types
+            .into_iter()
+            .sorted_by_key(|(key, counter)| (std::cmp::Reverse(counter.compressed), *key));
+        for ((server_bounded, state, packet_id, name), counter) in types {
+            writeln!(
+                f,
+                "  {} {:<13} {packet_id:#04x} {name:<32} {:>8} packets {:>10} ({:>10} uncompressed, {:>9} each) {:>5.1}% {:>10}/s",
+                direction(server_bounded),
+                state.to_string(),
+                counter.packets,
+                bytes(counter.compressed as f64),
+                bytes(counter.uncompressed as f64),
+                bytes(counter.uncompressed as f64 / counter.packets as f64),
+                100.0 * counter.compressed as f64 / total.compressed.max(1) as f64,
+                bytes(counter.compressed as f64 / seconds),
+            )?;
+        }
+
+        writeln!(f, "Connections:")?;
+        for (client, [clientbound, serverbound]) in
+            connections.into_iter().sorted_by_key(|(client, _)| *client)
+        {
+            writeln!(
+                f,
+                "  {client:<21} c2s {:>8} packets {:>10}, s2c {:>8} packets {:>10}",
+                serverbound.packets,
+                bytes(serverbound.compressed as f64),
+                clientbound.packets,
+                bytes(clientbound.compressed as f64),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Counts the packets of one connection
+pub struct StatsSink {
+    statistics: Arc<Statistics>,
+}
+
+impl StatsSink {
+    pub fn new(statistics: Arc<Statistics>) -> Self {
+        Self { statistics }
+    }
+}
+
+impl Sink for StatsSink {
+    fn packet(&mut self, record: &PacketRecord) -> color_eyre::Result<()> {
+        self.statistics.add(record);
+        Ok(())
+    }
+
+    /// Counts all traffic, not just what's shown
+    fn filtered(&self) -> bool {
+        false
+    }
+
+    fn finish(&mut self) -> color_eyre::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::protocol::packets::LoginStart;
+    use crate::protocol::registry::Packet;
+    use std::time::{Duration, UNIX_EPOCH};
+    use uuid::Uuid;
+
+    #[test]
+    fn statistics() {
+        let packet = Packet::from(LoginStart {
+            name: "Steve".to_string(),
+            uuid: Uuid::from_u128(1),
+        });
+        let statistics = Statistics::new();
+        for (seconds, client) in [
+            (10, "10.0.0.2:50000"),
+            (10, "10.0.0.3:50000"),
+            (12, "10.0.0.2:50000"),
+        ] {
+            statistics.add(&PacketRecord {
+                timestamp: UNIX_EPOCH + Duration::from_secs(seconds),
+                client: client.parse().unwrap(),
+                server: "10.0.0.1:25565".parse().unwrap(),
+                server_bounded: true,
+                state: State::Login,
+                player: None,
+                packet_id: 0,
+                raw: &[0; 20],
+                frame: &[0; 30],
+                packet: &packet,
+            });
+        }
+
+        let second = Counter {
+            packets: 2,
+            compressed: 40,
+            uncompressed: 60,
+        };
+        assert_eq!(
+            statistics.timeline(),
+            [
+                second,
+                Counter::default(),
+                Counter {
+                    packets: 1,
+                    compressed: 20,
+                    uncompressed: 30
+                }
+            ]
+        );
+        let summary = statistics.to_string();
+        assert!(summary.starts_with(
+            "3 packets over 3s, 60 B sent (90 B uncompressed), 20 B/s average, 40 B/s peak"
+        ));
+        assert!(summary.contains("c2s Login         0x00 LoginStart"));
+        assert!(summary.contains("  10.0.0.3:50000        c2s        1 packets       20 B"));
+    }
+
+    #[test]
+    fn units() {
+        assert_eq!(bytes(0.0), "0 B");
+        assert_eq!(bytes(1023.0), "1023 B");
+        assert_eq!(bytes(1536.0), "1.5 KiB");
+        assert_eq!(bytes(3.0 * 1024.0 * 1024.0), "3.0 MiB");
+    }
+}
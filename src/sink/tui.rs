@@ -0,0 +1,52 @@
+use crate::protocol::registry::{LoginServerbound, Packet};
+use crate::sink::{PacketRecord, Sink};
+use crate::tui::{PacketEntry, TuiEvent};
+use flume::Sender;
+use std::net::SocketAddr;
+
+/// Forwards packets to the terminal UI
+pub struct TuiSink {
+    tx: Sender<TuiEvent>,
+    client: Option<SocketAddr>,
+}
+
+impl TuiSink {
+    pub fn new(tx: Sender<TuiEvent>) -> Self {
+        Self { tx, client: None }
+    }
+}
+
+impl Sink for TuiSink {
+    fn packet(&mut self, record: &PacketRecord) -> color_eyre::Result<()> {
+        self.client = Some(record.client);
+        if let Packet::LoginServerbound(LoginServerbound::LoginStart(packet)) = record.packet {
+            self.tx.send(TuiEvent::Player {
+                client: record.client,
+                name: packet.name.clone(),
+            })?;
+        }
+
+        self.tx.send(TuiEvent::Packet {
+            client: record.client,
+            server: record.server,
+            entry: PacketEntry {
+                seq: 0,
+                timestamp: record.timestamp,
+                server_bounded: record.server_bounded,
+                state: record.state,
+                packet_id: record.packet_id,
+                name: record.packet.name(),
+                frame: record.frame.to_vec(),
+                fields: serde_json::to_string(record.packet)?,
+            },
+        })?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> color_eyre::Result<()> {
+        if let Some(client) = self.client {
+            self.tx.send(TuiEvent::Closed { client })?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+use crate::sink::{PacketRecord, Sink};
+use crate::world::{World, WorldExport};
+use log::info;
+use std::sync::Arc;
+
+/// Rebuilds the world a client saw and saves it when the connection ends
+pub struct WorldSink {
+    export: Arc<WorldExport>,
+    /// Folder name, from the first packet
+    name: Option<String>,
+    world: World,
+}
+
+impl WorldSink {
+    pub fn new(export: Arc<WorldExport>) -> Self {
+        Self {
+            export,
+            name: None,
+            world: World::new(),
+        }
+    }
+}
+
+impl Sink for WorldSink {
+    fn filtered(&self) -> bool {
+        false
+    }
+
+    fn packet(&mut self, record: &PacketRecord) -> color_eyre::Result<()> {
+        if self.name.is_none() {
+            self.name = Some(record.session_name());
+        }
+        self.world.apply(record.packet);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> color_eyre::Result<()> {
+        // Status pings and clients leaving before any chunks have nothing worth a folder
+        let Some(name) = &self.name else {
+            return Ok(());
+        };
+        if self.world.chunk_count() == 0 {
+            return Ok(());
+        }
+        let path = self.export.save(&self.world, name)?;
+        info!(
+            "Saved {} chunks to {}",
+            self.world.chunk_count(),
+            path.display()
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::protocol::packets::Unknown;
+    use crate::protocol::registry::Packet;
+    use crate::protocol::types::State;
+    use crate::world::BlockRegistry;
+    use bytes::Bytes;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn nothing_to_save() {
+        let dir = std::env::temp_dir().join(format!("world_sink_{}", std::process::id()));
+        let export = WorldExport {
+            dir: dir.clone(),
+            blocks: BlockRegistry::from_report("{}").unwrap(),
+        };
+        let mut sink = WorldSink::new(Arc::new(export));
+        let packet = Packet::from(Unknown {
+            packet_id: 0,
+            server_bounded: false,
+            payload: Bytes::new(),
+        });
+        sink.packet(&PacketRecord {
+            timestamp: UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+            client: "[::1]:50000".parse().unwrap(),
+            server: "[::1]:25565".parse().unwrap(),
+            server_bounded: false,
+            state: State::Status,
+            player: None,
+            packet_id: 0,
+            raw: &[],
+            frame: &[0x00],
+            packet: &packet,
+        })
+        .unwrap();
+        assert_eq!(sink.name.as_deref(), Some("__1_50000_1700000000"));
+
+        sink.finish().unwrap();
+        assert!(!dir.exists());
+    }
+}
@@ -1,39 +1,132 @@
-use std::any::Any;
+use crate::filter::Filter;
+use crate::metrics::Metrics;
 use crate::parser::ether_ip::IPPacketInfo;
-use crate::protocol::types::{Datatype, VarInt};
-use crate::protocol::MinecraftListener;
-use crate::tcp_connection::{ConnectionState, TCPConnection};
-use flume::Receiver;
-use itertools::Itertools;
-use log::info;
-use std::collections::VecDeque;
+use crate::protocol::types::State;
+use crate::session::{Session, SessionEvent};
+use crate::sink::{Sink, SinkOptions};
+use log::{error, info, warn};
 use std::net::IpAddr;
-use crate::protocol::packets::Handshake;
+use std::sync::Arc;
+use std::time::SystemTime;
 
-pub fn sniffer(
-    rx: Receiver<(IPPacketInfo, bool)>,
-    server_ip: IpAddr,
+/// Decodes the packets of one connection from the capture and hands them to the sinks
+pub struct Sniffer {
+    session: Session,
     client_ip: IpAddr,
-) -> color_eyre::Result<()> {
-    let mut listener = MinecraftListener::new(server_ip, client_ip);
-    let mut tcp_conn = TCPConnection::new(rx);
-    let mut buffer = VecDeque::new();
-    let mut expected_length = 0;
-
-    while let ConnectionState::Data(payload, server_bounded) = tcp_conn.next_packet()? {
-        buffer.extend(payload);
-
-        if expected_length == 0 {
-            let length = VarInt::from_bytes(&mut buffer)?;
-            expected_length = length.value as usize;
+    sinks: Vec<Box<dyn Sink>>,
+    filter: Option<Arc<Filter>>,
+    metrics: Option<Arc<Metrics>>,
+    /// TCP gaps already counted in the metrics
+    gaps: u64,
+    /// Capture time of the last packet
+    pub last_seen: SystemTime,
+}
+
+impl Sniffer {
+    pub fn new(server_ip: IpAddr, client_ip: IpAddr, options: &SinkOptions) -> Self {
+        info!("New connection from {client_ip}");
+        Self {
+            session: match &options.hooks {
+                Some(hooks) => Session::with_hooks(server_ip, client_ip, hooks.clone()),
+                None => Session::new(server_ip, client_ip),
+            },
+            client_ip,
+            sinks: options.create(server_ip, client_ip),
+            filter: options.filter.clone(),
+            metrics: options.metrics.clone(),
+            gaps: 0,
+            last_seen: SystemTime::UNIX_EPOCH,
+        }
+    }
+
+    /// Feeds one IP packet, returns false once the connection is closed
+    pub fn feed(
+        &mut self,
+        ip_packet: &IPPacketInfo,
+        server_bounded: bool,
+        timestamp: SystemTime,
+    ) -> color_eyre::Result<bool> {
+        self.last_seen = timestamp;
+        let events = self.session.feed_ip(ip_packet, server_bounded, timestamp)?;
+        if let Some(metrics) = &self.metrics {
+            let gaps = self.session.gaps();
+            metrics.gaps(gaps - self.gaps);
+            self.gaps = gaps;
+        }
+        Ok(self.handle(&events))
+    }
+
+    /// Session decoding the connection, to feed it directly and [`Self::handle`] the events
+    pub fn session(&mut self) -> &mut Session {
+        &mut self.session
+    }
+
+    /// Ports of the connection, for when it isn't fed TCP segments
+    pub fn set_ports(&mut self, client_port: u16, server_port: u16) {
+        self.session.set_ports(client_port, server_port);
+    }
+
+    /// Logs and hands the decoded packets to the sinks, returns false once the connection is
+    /// closed
+    pub fn handle(&mut self, events: &[SessionEvent]) -> bool {
+        for event in events {
+            let packet = match event {
+                SessionEvent::Packet(packet) => packet,
+                SessionEvent::Closed => {
+                    self.event(event);
+                    return false;
+                }
+                SessionEvent::Malformed(e) => {
+                    warn!("{e}");
+                    self.event(event);
+                    continue;
+                }
+                SessionEvent::StateChanged { .. }
+                | SessionEvent::Compression(_)
+                | SessionEvent::Player(_)
+                | SessionEvent::RoundTrip(_) => {
+                    self.event(event);
+                    continue;
+                }
+            };
+            if packet.state == State::Login && !packet.server_bounded && packet.packet_id == 0x01 {
+                warn!(
+                    "{}: encryption requested, the rest of the connection can't be decoded",
+                    self.client_ip
+                );
+            }
+            let record = packet.record();
+            let shown = self.filter.as_ref().is_none_or(|x| x.matches(&record));
+            if shown {
+                info!("{}", packet.packet);
+            }
+            for sink in self.sinks.iter_mut() {
+                if !shown && sink.filtered() {
+                    continue;
+                }
+                if let Err(e) = sink.packet(&record) {
+                    error!("Sink error: {e:?}");
+                }
+            }
         }
+        true
+    }
+
+    fn event(&mut self, event: &SessionEvent) {
+        for sink in self.sinks.iter_mut() {
+            if let Err(e) = sink.event(event) {
+                error!("Sink error: {e:?}");
+            }
+        }
+    }
 
-        if buffer.len() >= expected_length {
-            let packet_data = buffer.drain(..expected_length).collect_vec();
-            expected_length = 0;
-            let minecraft_packet = listener.parse_packet(&packet_data, server_bounded)?;
-            info!("{minecraft_packet}");
+    /// Flushes whatever was seen, even if the connection ended on an error
+    pub fn finish(mut self) {
+        info!("Connection closed from {}", self.client_ip);
+        for sink in &mut self.sinks {
+            if let Err(e) = sink.finish() {
+                error!("Sink error for {}: {e:?}", self.client_ip);
+            }
         }
     }
-    Ok(())
 }
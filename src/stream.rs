@@ -0,0 +1,349 @@
+//! Async streams of decoded packets, for use from tokio.
+//!
+//! Capture, reassembly and decoding run on a background thread, the streams are fed through
+//! bounded channels so a slow consumer holds back the capture. Dropping a stream, or cancelling
+//! its token, stops the capture.
+//!
+//! ```no_run
+//! use minecraft_packet_sniffer::stream::{CaptureSource, ConnectionStream};
+//! use tokio_stream::StreamExt;
+//!
+//! # async fn run() -> color_eyre::Result<()> {
+//! let source = CaptureSource::File("capture.pcap".into());
+//! let mut connections = ConnectionStream::open(&source, ([192, 168, 1, 2], 25565).into())?;
+//! while let Some(mut connection) = connections.next().await {
+//!     tokio::spawn(async move {
+//!         while let Some(event) = connection.next().await {
+//!             println!("{}: {event:?}", connection.client);
+//!         }
+//!     });
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::get_client_from_packet;
+use crate::parser::ether_ip::IPPacketInfo;
+use crate::parser::PacketParser;
+use crate::session::{Session, SessionEvent};
+use bytes::Bytes;
+use color_eyre::eyre::OptionExt;
+use log::{error, info, warn};
+use pcap::{Activated, Capture, Device, PacketHeader};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+use tokio_stream::Stream;
+use tokio_util::sync::CancellationToken;
+
+/// Events buffered per stream before the capture waits
+const BUFFER: usize = 256;
+
+/// Where to capture packets from
+#[derive(Debug, Clone)]
+pub enum CaptureSource {
+    /// Name of a live capture device
+    Device(String),
+    /// Capture file
+    File(PathBuf),
+}
+
+impl CaptureSource {
+    /// Opens the capture, filtered to the server's Minecraft traffic
+    pub fn open(&self, server: SocketAddr) -> color_eyre::Result<Capture<dyn Activated>> {
+        let mut cap: Capture<dyn Activated> = match self {
+            CaptureSource::File(path) => Capture::from_file(path)?.into(),
+            CaptureSource::Device(name) => {
+                let device_list = Device::list()?;
+                info!("{:#?}", device_list);
+
+                let device = device_list
+                    .into_iter()
+                    .find(|x| &x.name == name)
+                    .ok_or_eyre("Device not found")?;
+                // The timeout lets a cancelled stream notice on a quiet network
+                Capture::from_device(device)?
+                    .promisc(true)
+                    .timeout(100)
+                    .immediate_mode(true)
+                    .open()?
+                    .into()
+            }
+        };
+        let (ip, port) = (server.ip(), server.port());
+        cap.filter(&format!("tcp and net {ip} and port {port}"), true)?;
+        Ok(cap)
+    }
+}
+
+/// Capture time of a packet
+pub fn timestamp(header: &PacketHeader) -> SystemTime {
+    UNIX_EPOCH + Duration::new(header.ts.tv_sec as u64, header.ts.tv_usec as u32 * 1000)
+}
+
+/// Events of every connection to the server, in capture order
+pub struct EventStream {
+    rx: mpsc::Receiver<SessionEvent>,
+    cancel: CancellationToken,
+}
+
+impl EventStream {
+    pub fn open(source: &CaptureSource, server: SocketAddr) -> color_eyre::Result<Self> {
+        let cancel = CancellationToken::new();
+        let frames = frames(source.open(server)?, cancel.clone());
+        Ok(Self::from_frames(frames, server.ip(), cancel))
+    }
+
+    fn from_frames<F: Frames>(frames: F, server_ip: IpAddr, cancel: CancellationToken) -> Self {
+        let (tx, rx) = mpsc::channel(BUFFER);
+        let token = cancel.clone();
+        thread::spawn(move || {
+            run(frames, server_ip, &token, |_, event| {
+                tx.blocking_send(event).is_ok()
+            })
+        });
+        Self { rx, cancel }
+    }
+
+    /// Token that stops the capture when cancelled, the stream then ends
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancel.clone()
+    }
+}
+
+impl Stream for EventStream {
+    type Item = SessionEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+impl Drop for EventStream {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
+
+/// Connections to the server as they are first seen, each a stream of its own events.
+/// One connection that isn't read holds back the others once its buffer is full.
+pub struct ConnectionStream {
+    rx: mpsc::Receiver<Connection>,
+    cancel: CancellationToken,
+}
+
+impl ConnectionStream {
+    pub fn open(source: &CaptureSource, server: SocketAddr) -> color_eyre::Result<Self> {
+        let cancel = CancellationToken::new();
+        let frames = frames(source.open(server)?, cancel.clone());
+        Ok(Self::from_frames(frames, server.ip(), cancel))
+    }
+
+    fn from_frames<F: Frames>(frames: F, server_ip: IpAddr, cancel: CancellationToken) -> Self {
+        let (tx, rx) = mpsc::channel(BUFFER);
+        let token = cancel.clone();
+        thread::spawn(move || {
+            let mut connections = HashMap::new();
+            run(frames, server_ip, &token, |client, event| {
+                let closed = matches!(event, SessionEvent::Closed);
+                let events = match connections.entry(client) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => {
+                        let (events, rx) = mpsc::channel(BUFFER);
+                        if tx.blocking_send(Connection { client, rx }).is_err() {
+                            return false;
+                        }
+                        entry.insert(events)
+                    }
+                };
+                // A dropped connection just isn't being followed
+                let _ = events.blocking_send(event);
+                if closed {
+                    connections.remove(&client);
+                }
+                true
+            })
+        });
+        Self { rx, cancel }
+    }
+
+    /// Token that stops the capture when cancelled, every stream then ends
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancel.clone()
+    }
+}
+
+impl Stream for ConnectionStream {
+    type Item = Connection;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+impl Drop for ConnectionStream {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
+
+/// Events of one client's connection, ending after [`SessionEvent::Closed`]
+pub struct Connection {
+    pub client: SocketAddr,
+    rx: mpsc::Receiver<SessionEvent>,
+}
+
+impl Stream for Connection {
+    type Item = SessionEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+trait Frames: Iterator<Item = color_eyre::Result<(Bytes, SystemTime)>> + Send + 'static {}
+
+impl<T: Iterator<Item = color_eyre::Result<(Bytes, SystemTime)>> + Send + 'static> Frames for T {}
+
+/// Captured frames until the capture ends or is cancelled
+fn frames(mut cap: Capture<dyn Activated>, cancel: CancellationToken) -> impl Frames {
+    std::iter::from_fn(move || {
+        while !cancel.is_cancelled() {
+            match cap.next_packet() {
+                Ok(packet) => {
+                    let data = Bytes::copy_from_slice(packet.data);
+                    return Some(Ok((data, timestamp(packet.header))));
+                }
+                Err(pcap::Error::TimeoutExpired) => continue,
+                Err(pcap::Error::NoMorePackets) => return None,
+                Err(e) => return Some(Err(e.into())),
+            }
+        }
+        None
+    })
+}
+
+/// Follows every connection in `frames`, handing their events to `emit` until it returns false
+fn run(
+    frames: impl Frames,
+    server_ip: IpAddr,
+    cancel: &CancellationToken,
+    mut emit: impl FnMut(SocketAddr, SessionEvent) -> bool,
+) {
+    let mut sessions = HashMap::new();
+    for frame in frames {
+        if cancel.is_cancelled() {
+            break;
+        }
+        let (data, timestamp) = match frame {
+            Ok(frame) => frame,
+            Err(e) => {
+                error!("Capture error: {e}");
+                break;
+            }
+        };
+        let ip_packet = match IPPacketInfo::from_bytes(&data) {
+            Ok(ip_packet) => ip_packet,
+            Err(e) => {
+                warn!("{e}");
+                continue;
+            }
+        };
+        // Keyed by port too, one IP can have several connections
+        let (client, server_bounded) = get_client_from_packet(&ip_packet, server_ip);
+        let session = sessions
+            .entry(client)
+            .or_insert_with(|| Session::new(server_ip, client.ip()));
+        let events = session
+            .feed_ip(&ip_packet, server_bounded, timestamp)
+            .unwrap_or_else(|e| {
+                error!("Session error for {client}: {e}");
+                vec![SessionEvent::Closed]
+            });
+        for event in events {
+            if matches!(event, SessionEvent::Closed) {
+                sessions.remove(&client);
+            }
+            if !emit(client, event) {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sink::segment;
+    use std::net::Ipv4Addr;
+    use tokio_stream::StreamExt;
+
+    const SERVER: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)), 25565);
+
+    /// Status handshake and request of a client, as Ethernet frames. Every client has the same
+    /// IP, only the port tells them apart.
+    fn status(port: u16) -> Vec<color_eyre::Result<(Bytes, SystemTime)>> {
+        let client = SocketAddr::new([10, 0, 1, 2].into(), port);
+        let handshake = [
+            0x10, 0x00, 0x81, 0x06, 0x09, b'l', b'o', b'c', b'a', b'l', b'h', b'o', b's', b't',
+            0x63, 0xDD, 0x01,
+        ];
+        let request = [0x01, 0x00];
+        [(0, &handshake[..]), (handshake.len(), &request)]
+            .into_iter()
+            .map(|(seq, payload)| {
+                let mut frame = vec![0; 12];
+                frame.extend(0x0800u16.to_be_bytes());
+                frame.extend(segment(client, SERVER, seq as u32, 0, payload));
+                Ok((Bytes::from(frame), UNIX_EPOCH))
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn events() {
+        let frames = status(50000).into_iter().chain(status(50001));
+        let stream = EventStream::from_frames(frames, SERVER.ip(), CancellationToken::new());
+        let events: Vec<_> = stream.collect().await;
+        assert_eq!(events.len(), 6);
+        assert!(matches!(events[1], SessionEvent::StateChanged { .. }));
+        let SessionEvent::Packet(packet) = &events[5] else {
+            panic!("Expected a packet, got {:?}", events[5]);
+        };
+        assert_eq!(packet.client, SocketAddr::new([10, 0, 1, 2].into(), 50001));
+        assert_eq!(packet.packet.name(), "StatusRequest");
+    }
+
+    #[tokio::test]
+    async fn connections() {
+        let (mut a, b) = (status(50000), status(50001));
+        // Interleaved
+        a.insert(1, b.into_iter().next().unwrap());
+        let frames = a.into_iter().chain(status(50001).into_iter().skip(1));
+        let mut stream =
+            ConnectionStream::from_frames(frames, SERVER.ip(), CancellationToken::new());
+
+        let mut clients = vec![];
+        while let Some(connection) = stream.next().await {
+            let client = connection.client;
+            clients.push((client.port(), connection.collect::<Vec<_>>().await.len()));
+        }
+        assert_eq!(clients, [(50000, 3), (50001, 3)]);
+    }
+
+    #[tokio::test]
+    async fn cancel() {
+        let frames = std::iter::repeat_with(|| status(50000)).flatten();
+        let mut stream = EventStream::from_frames(frames, SERVER.ip(), CancellationToken::new());
+        stream.next().await.unwrap();
+        stream.cancellation_token().cancel();
+        // Whatever was buffered, then the end
+        while stream.next().await.is_some() {}
+    }
+}
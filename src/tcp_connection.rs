@@ -1,85 +1,169 @@
-use crate::get_client_ip_from_packet;
+use crate::latency::{Probe, RoundTrip};
+use crate::parser::error::PacketError;
 use crate::parser::ether_ip::IPPacketInfo;
 use crate::parser::tcp::{TCPFlag, TCPPacketInfo};
 use crate::parser::PacketParser;
-use flume::Receiver;
-use std::collections::HashMap;
+use bytes::{Bytes, BytesMut};
+use std::collections::{HashMap, VecDeque};
+use std::time::SystemTime;
+
+/// Segments kept waiting for an acknowledgement, per direction
+const MAX_UNACKED: usize = 1024;
 
 #[derive(Debug, Clone)]
 pub enum ConnectionState {
-    Data(Vec<u8>, bool),
+    /// Payload, whether it is server bounded, and the capture time of its last segment
+    Data(Bytes, bool, SystemTime),
     Closed,
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct TCPData {
-    data: Vec<u8>,
+    data: BytesMut,
     next_seq: u32,
     buffer: HashMap<u32, TCPPacketInfo>,
+    /// Sequence number after each segment sent, and when it was captured
+    unacked: VecDeque<(u32, SystemTime)>,
 }
 
-#[derive(Debug, Clone)]
+/// Puts the segments of one TCP connection back in order
+#[derive(Debug, Clone, Default)]
 pub struct TCPConnection {
-    rx: Receiver<(IPPacketInfo, bool)>,
     c2s_data: TCPData,
     s2c_data: TCPData,
     closing_state: u8,
+    client_port: u16,
+    server_port: u16,
+    gaps: u64,
+    round_trips: Vec<RoundTrip>,
 }
 
 impl TCPConnection {
-    pub fn new(rx: Receiver<(IPPacketInfo, bool)>) -> Self {
-        Self {
-            rx,
-            c2s_data: Default::default(),
-            s2c_data: Default::default(),
-            closing_state: 0,
-        }
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    pub fn next_packet(&mut self) -> color_eyre::Result<ConnectionState> {
-        let rx = &self.rx;
-        loop {
-            let (ip_packet, server_bounded) = rx.recv()?;
-            let tcp_packet = TCPPacketInfo::from_bytes(&ip_packet.payload)?;
-            {
-                let tcp_data = if server_bounded {
-                    &mut self.c2s_data
-                } else {
-                    &mut self.s2c_data
-                };
+    /// Client and server port of the connection, as of the last segment read
+    pub fn ports(&self) -> (u16, u16) {
+        (self.client_port, self.server_port)
+    }
 
-                if tcp_packet.flags.contains(TCPFlag::ACK) && self.closing_state > 0 {
-                    self.closing_state += 1;
-                    if self.closing_state == 4 {
-                        return Ok(ConnectionState::Closed);
-                    }
-                    continue;
-                } else if tcp_packet.flags.contains(TCPFlag::SYN) {
-                    tcp_data.next_seq = tcp_packet.seq_num + 1;
-                    continue;
-                } else if tcp_packet.flags.contains(TCPFlag::FIN) {
-                    self.closing_state += 1;
-                    continue;
-                } else if !tcp_packet.payload.is_empty() {
-                    tcp_data.buffer.insert(tcp_packet.seq_num, tcp_packet);
-                }
+    pub fn set_ports(&mut self, client_port: u16, server_port: u16) {
+        (self.client_port, self.server_port) = (client_port, server_port);
+    }
+
+    /// Segments that arrived ahead of a missing one, lost or reordered
+    pub fn gaps(&self) -> u64 {
+        self.gaps
+    }
 
-                loop {
-                    match tcp_data.buffer.remove(&tcp_data.next_seq) {
-                        None => break,
-                        Some(tcp_packet) => {
-                            let flags = tcp_packet.flags.clone();
-                            tcp_data.next_seq += tcp_packet.payload.len() as u32;
-                            tcp_data.data.extend(tcp_packet.payload);
-                            if flags.contains(TCPFlag::PSH) {
-                                let mut output = vec![];
-                                output.append(&mut tcp_data.data);
-                                return Ok(ConnectionState::Data(output, server_bounded));
-                            }
-                        }
+    /// Round trips timed from data to its acknowledgement since the last call
+    pub fn take_round_trips(&mut self) -> Vec<RoundTrip> {
+        std::mem::take(&mut self.round_trips)
+    }
+
+    /// Takes in one segment, returning the payload it completes or the end of the connection
+    pub fn segment(
+        &mut self,
+        ip_packet: &IPPacketInfo,
+        server_bounded: bool,
+        timestamp: SystemTime,
+    ) -> Result<Option<ConnectionState>, PacketError> {
+        let tcp_packet = TCPPacketInfo::from_bytes(&ip_packet.payload)?;
+        (self.client_port, self.server_port) = if server_bounded {
+            (tcp_packet.src_port, tcp_packet.dst_port)
+        } else {
+            (tcp_packet.dst_port, tcp_packet.src_port)
+        };
+        if tcp_packet.flags.contains(TCPFlag::ACK) {
+            self.acknowledge(server_bounded, tcp_packet.ack_num, timestamp);
+        }
+        let tcp_data = if server_bounded {
+            &mut self.c2s_data
+        } else {
+            &mut self.s2c_data
+        };
+
+        if tcp_packet.flags.contains(TCPFlag::ACK) && self.closing_state > 0 {
+            self.closing_state += 1;
+            if self.closing_state == 4 {
+                return Ok(Some(ConnectionState::Closed));
+            }
+            return Ok(None);
+        } else if tcp_packet.flags.contains(TCPFlag::SYN) {
+            tcp_data.next_seq = tcp_packet.seq_num + 1;
+            return Ok(None);
+        } else if tcp_packet.flags.contains(TCPFlag::FIN) {
+            self.closing_state += 1;
+            return Ok(None);
+        } else if !tcp_packet.payload.is_empty() {
+            // Only known once the handshake set where the stream starts
+            if tcp_data.next_seq != 0 && after(tcp_packet.seq_num, tcp_data.next_seq) {
+                self.gaps += 1;
+            }
+            let end = tcp_packet
+                .seq_num
+                .wrapping_add(tcp_packet.payload.len() as u32);
+            match tcp_data.unacked.back() {
+                // A retransmission can't be timed, its acknowledgement may be for either copy
+                Some(&(last, _)) if !after(end, last) => tcp_data.unacked.clear(),
+                _ => {
+                    if tcp_data.unacked.len() == MAX_UNACKED {
+                        tcp_data.unacked.pop_front();
                     }
+                    tcp_data.unacked.push_back((end, timestamp));
                 }
             }
+            tcp_data.buffer.insert(tcp_packet.seq_num, tcp_packet);
+        }
+
+        while let Some(tcp_packet) = tcp_data.buffer.remove(&tcp_data.next_seq) {
+            let flags = tcp_packet.flags.clone();
+            tcp_data.next_seq += tcp_packet.payload.len() as u32;
+            if flags.contains(TCPFlag::PSH) {
+                // Usually a push is a single segment, which can be handed on as is
+                let output = if tcp_data.data.is_empty() {
+                    tcp_packet.payload
+                } else {
+                    tcp_data.data.extend_from_slice(&tcp_packet.payload);
+                    tcp_data.data.split().freeze()
+                };
+                return Ok(Some(ConnectionState::Data(
+                    output,
+                    server_bounded,
+                    timestamp,
+                )));
+            }
+            tcp_data.data.extend_from_slice(&tcp_packet.payload);
         }
+        Ok(None)
     }
+
+    /// Times the data of the other direction that `ack` covers
+    fn acknowledge(&mut self, server_bounded: bool, ack: u32, timestamp: SystemTime) {
+        let (unacked, probe) = if server_bounded {
+            (&mut self.s2c_data.unacked, Probe::TcpClient)
+        } else {
+            (&mut self.c2s_data.unacked, Probe::TcpServer)
+        };
+        let mut sent = None;
+        while let Some(&(end, time)) = unacked.front()
+            && !after(end, ack)
+        {
+            unacked.pop_front();
+            sent = Some(time);
+        }
+        if let Some(rtt) = sent.and_then(|x| timestamp.duration_since(x).ok()) {
+            self.round_trips.push(RoundTrip {
+                timestamp,
+                probe,
+                rtt,
+            });
+        }
+    }
+}
+
+/// Whether sequence number `a` comes after `b`, across wrapping
+fn after(a: u32, b: u32) -> bool {
+    (a.wrapping_sub(b) as i32) > 0
 }
@@ -0,0 +1,427 @@
+mod ui;
+
+use crate::protocol::types::State;
+use flume::Receiver;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::time::{Duration, SystemTime};
+
+/// Older packets of a connection are dropped past this, so long sessions don't eat all memory
+const MAX_PACKETS: usize = 100_000;
+
+#[derive(Debug, Clone)]
+pub struct PacketEntry {
+    /// Position in the connection, set when the entry is stored
+    pub seq: u64,
+    pub timestamp: SystemTime,
+    pub server_bounded: bool,
+    pub state: State,
+    pub packet_id: i32,
+    pub name: &'static str,
+    /// Uncompressed packet ID and data
+    pub frame: Vec<u8>,
+    /// Decoded fields as compact JSON, only pretty printed when shown
+    pub fields: String,
+}
+
+impl PacketEntry {
+    fn matches(&self, text: &str) -> bool {
+        self.name.to_lowercase().contains(text) || self.fields.to_lowercase().contains(text)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum TuiEvent {
+    Packet {
+        client: SocketAddr,
+        server: SocketAddr,
+        entry: PacketEntry,
+    },
+    Player {
+        client: SocketAddr,
+        name: String,
+    },
+    Closed {
+        client: SocketAddr,
+    },
+    /// Shown in the status bar
+    Message(String),
+}
+
+#[derive(Debug)]
+struct Connection {
+    client: SocketAddr,
+    server: SocketAddr,
+    player: Option<String>,
+    closed: bool,
+    packets: VecDeque<PacketEntry>,
+    next_seq: u64,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Focus {
+    Connections,
+    Packets,
+    Detail,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum InputKind {
+    Filter,
+    Search,
+}
+
+#[derive(Debug)]
+struct App {
+    connections: Vec<Connection>,
+    connection: usize,
+    /// Sequence number of the selected packet, `None` follows the newest one
+    selected: Option<u64>,
+    /// First row of the packet table
+    offset: usize,
+    detail_scroll: u16,
+    focus: Focus,
+    /// Events that arrived while paused
+    paused: Option<Vec<TuiEvent>>,
+    /// Lowercase packet name filter
+    filter: String,
+    /// Lowercase text searched in names and fields
+    search: String,
+    input: Option<(InputKind, String)>,
+    message: String,
+    quit: bool,
+}
+
+impl App {
+    fn new() -> Self {
+        Self {
+            connections: vec![],
+            connection: 0,
+            selected: None,
+            offset: 0,
+            detail_scroll: 0,
+            focus: Focus::Packets,
+            paused: None,
+            filter: String::new(),
+            search: String::new(),
+            input: None,
+            message: String::new(),
+            quit: false,
+        }
+    }
+
+    fn connection_mut(&mut self, client: SocketAddr, server: SocketAddr) -> &mut Connection {
+        // A client reconnecting from a new port is a new connection
+        let index = match self
+            .connections
+            .iter()
+            .position(|x| x.client == client && !x.closed)
+        {
+            Some(index) => index,
+            None => {
+                self.connections.push(Connection {
+                    client,
+                    server,
+                    player: None,
+                    closed: false,
+                    packets: VecDeque::new(),
+                    next_seq: 0,
+                });
+                self.connections.len() - 1
+            }
+        };
+        &mut self.connections[index]
+    }
+
+    fn apply(&mut self, event: TuiEvent) {
+        if let Some(pending) = &mut self.paused
+            && !matches!(event, TuiEvent::Message(_))
+        {
+            pending.push(event);
+            return;
+        }
+
+        match event {
+            TuiEvent::Packet {
+                client,
+                server,
+                mut entry,
+            } => {
+                let connection = self.connection_mut(client, server);
+                entry.seq = connection.next_seq;
+                connection.next_seq += 1;
+                if connection.packets.len() == MAX_PACKETS {
+                    connection.packets.pop_front();
+                }
+                connection.packets.push_back(entry);
+            }
+            TuiEvent::Player { client, name } => {
+                if let Some(connection) = self
+                    .connections
+                    .iter_mut()
+                    .rev()
+                    .find(|x| x.client == client)
+                {
+                    connection.player = Some(name);
+                }
+            }
+            TuiEvent::Closed { client } => {
+                if let Some(connection) = self
+                    .connections
+                    .iter_mut()
+                    .rev()
+                    .find(|x| x.client == client)
+                {
+                    connection.closed = true;
+                }
+            }
+            TuiEvent::Message(message) => self.message = message,
+        }
+    }
+
+    fn current(&self) -> Option<&Connection> {
+        self.connections.get(self.connection)
+    }
+
+    /// Packets of the current connection passing the filter
+    fn visible(&self) -> Vec<&PacketEntry> {
+        self.current().map_or(vec![], |connection| {
+            connection
+                .packets
+                .iter()
+                .filter(|x| self.filter.is_empty() || x.name.to_lowercase().contains(&self.filter))
+                .collect()
+        })
+    }
+
+    /// Row of the selected packet in the visible packets
+    fn selected_row(&self, visible: &[&PacketEntry]) -> Option<usize> {
+        match self.selected {
+            None => visible.len().checked_sub(1),
+            // The selected packet may have been filtered out or dropped, take the one after it
+            Some(seq) => Some(
+                visible
+                    .partition_point(|x| x.seq < seq)
+                    .min(visible.len().saturating_sub(1)),
+            )
+            .filter(|_| !visible.is_empty()),
+        }
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        let visible = self.visible();
+        let Some(row) = self.selected_row(&visible) else {
+            return;
+        };
+        let row = row.saturating_add_signed(delta).min(visible.len() - 1);
+        self.selected = if row == visible.len() - 1 && delta > 0 {
+            None
+        } else {
+            Some(visible[row].seq)
+        };
+        self.detail_scroll = 0;
+    }
+
+    fn select_connection(&mut self, delta: isize) {
+        if self.connections.is_empty() {
+            return;
+        }
+        self.connection = self
+            .connection
+            .saturating_add_signed(delta)
+            .min(self.connections.len() - 1);
+        self.selected = None;
+        self.offset = 0;
+        self.detail_scroll = 0;
+    }
+
+    /// Selects the next (or previous) visible packet matching the search
+    fn find(&mut self, forward: bool) {
+        if self.search.is_empty() {
+            return;
+        }
+        let visible = self.visible();
+        let row = self.selected_row(&visible).unwrap_or(0);
+        let found = if forward {
+            visible
+                .iter()
+                .skip(row + 1)
+                .find(|x| x.matches(&self.search))
+        } else {
+            visible
+                .iter()
+                .take(row)
+                .rev()
+                .find(|x| x.matches(&self.search))
+        };
+        let found = found.map(|x| x.seq);
+        match found {
+            Some(seq) => {
+                self.selected = Some(seq);
+                self.detail_scroll = 0;
+                self.message.clear();
+            }
+            None => self.message = format!("No match for \"{}\"", self.search),
+        }
+    }
+
+    fn toggle_pause(&mut self) {
+        match self.paused.take() {
+            Some(pending) => pending.into_iter().for_each(|x| self.apply(x)),
+            None => self.paused = Some(vec![]),
+        }
+    }
+
+    fn handle_input(&mut self, key: KeyEvent) {
+        let Some((kind, text)) = &mut self.input else {
+            return;
+        };
+        match key.code {
+            KeyCode::Enter => {
+                let text = text.to_lowercase();
+                match kind {
+                    InputKind::Filter => self.filter = text,
+                    InputKind::Search => self.search = text,
+                }
+                let kind = *kind;
+                self.input = None;
+                if kind == InputKind::Search {
+                    self.find(true);
+                }
+            }
+            KeyCode::Esc => self.input = None,
+            KeyCode::Backspace => {
+                text.pop();
+            }
+            KeyCode::Char(c) => text.push(c),
+            _ => {}
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        if self.input.is_some() {
+            self.handle_input(key);
+            return;
+        }
+
+        match key.code {
+            KeyCode::Char('q') => self.quit = true,
+            KeyCode::Tab => {
+                self.focus = match self.focus {
+                    Focus::Connections => Focus::Packets,
+                    Focus::Packets => Focus::Detail,
+                    Focus::Detail => Focus::Connections,
+                }
+            }
+            KeyCode::Char(' ') | KeyCode::Char('p') => self.toggle_pause(),
+            KeyCode::Char('f') => self.input = Some((InputKind::Filter, self.filter.clone())),
+            KeyCode::Char('/') => self.input = Some((InputKind::Search, String::new())),
+            KeyCode::Char('n') => self.find(true),
+            KeyCode::Char('N') => self.find(false),
+            KeyCode::Esc => {
+                self.filter.clear();
+                self.search.clear();
+                self.message.clear();
+            }
+            KeyCode::End | KeyCode::Char('G') => self.selected = None,
+            KeyCode::Home | KeyCode::Char('g') => self.move_selection(isize::MIN),
+            code => {
+                let delta = match code {
+                    KeyCode::Up | KeyCode::Char('k') => -1,
+                    KeyCode::Down | KeyCode::Char('j') => 1,
+                    KeyCode::PageUp => -20,
+                    KeyCode::PageDown => 20,
+                    _ => return,
+                };
+                match self.focus {
+                    Focus::Connections => self.select_connection(delta),
+                    Focus::Packets => self.move_selection(delta),
+                    Focus::Detail => {
+                        self.detail_scroll = self.detail_scroll.saturating_add_signed(delta as i16)
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Runs the UI until the user quits, taking events from the sinks
+pub fn run(rx: Receiver<TuiEvent>) -> color_eyre::Result<()> {
+    let mut terminal = ratatui::init();
+    let mut app = App::new();
+
+    let result = loop {
+        for event in rx.try_iter() {
+            app.apply(event);
+        }
+        if let Err(e) = terminal.draw(|frame| ui::draw(frame, &mut app)) {
+            break Err(e.into());
+        }
+
+        match event::poll(Duration::from_millis(100)) {
+            Ok(true) => match event::read() {
+                Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => app.handle_key(key),
+                Ok(_) => {}
+                Err(e) => break Err(e.into()),
+            },
+            Ok(false) => {}
+            Err(e) => break Err(e.into()),
+        }
+        if app.quit {
+            break Ok(());
+        }
+    };
+
+    ratatui::restore();
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    pub(super) fn packet(name: &'static str) -> TuiEvent {
+        TuiEvent::Packet {
+            client: "10.0.0.2:50000".parse().unwrap(),
+            server: "10.0.0.1:25565".parse().unwrap(),
+            entry: PacketEntry {
+                seq: 0,
+                timestamp: UNIX_EPOCH,
+                server_bounded: true,
+                state: State::Play,
+                packet_id: 0,
+                name,
+                frame: vec![0],
+                fields: "null".to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn selection() {
+        let mut app = App::new();
+        for name in ["A", "B", "A", "B"] {
+            app.apply(packet(name));
+        }
+        assert_eq!(app.selected_row(&app.visible()), Some(3));
+
+        app.move_selection(-1);
+        assert_eq!(app.selected, Some(2));
+        app.filter = "b".to_string();
+        // Packet 2 is filtered out, so the selection moves to the next shown one
+        assert_eq!(app.selected_row(&app.visible()), Some(1));
+
+        app.filter.clear();
+        app.search = "a".to_string();
+        app.find(false);
+        assert_eq!(app.selected, Some(0));
+
+        app.toggle_pause();
+        app.apply(packet("C"));
+        assert_eq!(app.visible().len(), 4);
+        app.toggle_pause();
+        assert_eq!(app.visible().len(), 5);
+    }
+}
@@ -0,0 +1,249 @@
+use crate::tui::{App, Focus, InputKind, PacketEntry};
+use itertools::Itertools;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Row, Table, TableState};
+use ratatui::Frame;
+use std::time::UNIX_EPOCH;
+
+pub fn draw(frame: &mut Frame, app: &mut App) {
+    let [main, status] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+    let [connections, right] =
+        Layout::horizontal([Constraint::Length(34), Constraint::Min(0)]).areas(main);
+    let [packets, detail] =
+        Layout::vertical([Constraint::Percentage(60), Constraint::Percentage(40)]).areas(right);
+    let [fields, hex] =
+        Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(detail);
+
+    draw_connections(frame, app, connections);
+    let selected = draw_packets(frame, app, packets);
+    draw_detail(frame, app, selected.as_ref(), fields, hex);
+    draw_status(frame, app, status);
+}
+
+fn block(title: &str, focused: bool) -> Block<'_> {
+    let block = Block::bordered().title(title);
+    if focused {
+        block.border_style(Style::new().fg(Color::Yellow))
+    } else {
+        block
+    }
+}
+
+fn draw_connections(frame: &mut Frame, app: &App, area: Rect) {
+    let items = app.connections.iter().map(|x| {
+        let label = match &x.player {
+            Some(player) => format!("{} {}", x.client, player),
+            None => x.client.to_string(),
+        };
+        let style = if x.closed {
+            Style::new().dark_gray()
+        } else {
+            Style::new()
+        };
+        ListItem::new(label).style(style)
+    });
+    let list = List::new(items)
+        .block(block("Connections", app.focus == Focus::Connections))
+        .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+    let mut state = ListState::default().with_selected(Some(app.connection));
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+/// Draws only the rows on screen, returns the selected packet
+fn draw_packets(frame: &mut Frame, app: &mut App, area: Rect) -> Option<PacketEntry> {
+    let visible = app.visible();
+    let selected_row = app.selected_row(&visible);
+    // Borders and header
+    let height = area.height.saturating_sub(3).max(1) as usize;
+
+    let mut offset = app.offset.min(visible.len().saturating_sub(1));
+    if let Some(row) = selected_row {
+        if row < offset {
+            offset = row;
+        } else if row >= offset + height {
+            offset = row + 1 - height;
+        }
+    }
+
+    let start = visible.first().map_or(UNIX_EPOCH, |x| x.timestamp);
+    let rows = visible.iter().skip(offset).take(height).map(|x| {
+        let time = x.timestamp.duration_since(start).unwrap_or_default();
+        let (direction, color) = if x.server_bounded {
+            ("C -> S", Color::LightBlue)
+        } else {
+            ("S -> C", Color::LightRed)
+        };
+        Row::new(vec![
+            Span::raw(format!("{:.3}", time.as_secs_f64())),
+            Span::styled(direction, Style::new().fg(color)),
+            Span::raw(x.state.to_string()),
+            Span::raw(format!("0x{:02X}", x.packet_id)),
+            Span::raw(x.name),
+            Span::raw(x.frame.len().to_string()),
+        ])
+    });
+
+    let title = match app.current() {
+        Some(connection) => format!(
+            "Packets {} -> {} ({}/{})",
+            connection.client,
+            connection.server,
+            visible.len(),
+            connection.packets.len()
+        ),
+        None => "Packets".to_string(),
+    };
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(10),
+            Constraint::Length(6),
+            Constraint::Length(13),
+            Constraint::Length(4),
+            Constraint::Min(20),
+            Constraint::Length(8),
+        ],
+    )
+    .header(Row::new(["Time", "Dir", "State", "ID", "Name", "Size"]).bold())
+    .block(block(&title, app.focus == Focus::Packets))
+    .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+
+    let mut state = TableState::default().with_selected(selected_row.map(|x| x - offset));
+    let selected = selected_row.map(|x| visible[x].clone());
+    app.offset = offset;
+    frame.render_stateful_widget(table, area, &mut state);
+    selected
+}
+
+fn draw_detail(
+    frame: &mut Frame,
+    app: &App,
+    packet: Option<&PacketEntry>,
+    fields_area: Rect,
+    hex_area: Rect,
+) {
+    let focused = app.focus == Focus::Detail;
+    let (fields, hex) = match packet {
+        Some(packet) => {
+            let fields = serde_json::from_str::<serde_json::Value>(&packet.fields)
+                .and_then(|x| serde_json::to_string_pretty(&x))
+                .unwrap_or_else(|_| packet.fields.clone());
+            (fields, hex_dump(&packet.frame))
+        }
+        None => (String::new(), String::new()),
+    };
+
+    let fields = Paragraph::new(fields)
+        .block(block("Fields", focused))
+        .scroll((app.detail_scroll, 0));
+    let hex = Paragraph::new(hex)
+        .block(block("Hex", focused))
+        .scroll((app.detail_scroll, 0));
+    frame.render_widget(fields, fields_area);
+    frame.render_widget(hex, hex_area);
+}
+
+fn draw_status(frame: &mut Frame, app: &App, area: Rect) {
+    let line = match &app.input {
+        Some((kind, text)) => {
+            let prompt = match kind {
+                InputKind::Filter => "Filter name: ",
+                InputKind::Search => "Search: ",
+            };
+            Line::from(vec![
+                Span::raw(prompt).bold(),
+                Span::raw(text),
+                Span::raw("_"),
+            ])
+        }
+        None => {
+            let mut spans = vec![];
+            if let Some(pending) = &app.paused {
+                spans.push(
+                    format!(" PAUSED ({} waiting) ", pending.len())
+                        .black()
+                        .on_yellow(),
+                );
+                spans.push(Span::raw(" "));
+            }
+            if !app.filter.is_empty() {
+                spans.push(format!("[filter: {}] ", app.filter).cyan());
+            }
+            if !app.search.is_empty() {
+                spans.push(format!("[search: {}] ", app.search).cyan());
+            }
+            if !app.message.is_empty() {
+                spans.push(format!("{} ", app.message).yellow());
+            }
+            spans.push(
+                "q quit | Tab focus | space pause | f filter | / search | n/N next/prev | End follow | Esc clear"
+                    .dark_gray(),
+            );
+            Line::from(spans)
+        }
+    };
+    frame.render_widget(Paragraph::new(line), area);
+}
+
+/// Offset, 16 bytes in hex and their printable ASCII per line
+fn hex_dump(bytes: &[u8]) -> String {
+    bytes
+        .chunks(16)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let ascii: String = chunk
+                .iter()
+                .map(|&x| {
+                    if x.is_ascii_graphic() || x == b' ' {
+                        x as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            format!(
+                "{:04x}  {:<47}  {}",
+                i * 16,
+                chunk.iter().map(|x| format!("{x:02x}")).join(" "),
+                ascii
+            )
+        })
+        .join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    #[test]
+    fn hex() {
+        let bytes = b"\x00\x05Hello, packet sniffer!";
+        assert_eq!(
+            hex_dump(bytes),
+            "0000  00 05 48 65 6c 6c 6f 2c 20 70 61 63 6b 65 74 20  ..Hello, packet \n\
+             0010  73 6e 69 66 66 65 72 21                          sniffer!"
+        );
+    }
+
+    #[test]
+    fn scrolling() {
+        let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+        let mut app = App::new();
+        for _ in 0..100 {
+            app.apply(crate::tui::test::packet("KeepAlive"));
+        }
+
+        terminal.draw(|frame| draw(frame, &mut app)).unwrap();
+        // 60% of the 29 rows above the status bar, minus borders and header
+        assert_eq!(app.offset, 100 - 14);
+
+        app.move_selection(isize::MIN);
+        terminal.draw(|frame| draw(frame, &mut app)).unwrap();
+        assert_eq!(app.offset, 0);
+    }
+}
@@ -0,0 +1,380 @@
+use crate::protocol::types::{BlockEntity, Nbt};
+use crate::world::{BlockRegistry, Dimension, StoredChunk};
+use color_eyre::eyre::WrapErr;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use log::warn;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Data version of 1.21.4, written into every chunk and level.dat
+pub(crate) const DATA_VERSION: i32 = 4189;
+
+const SECTOR_SIZE: usize = 4096;
+/// Zlib, the compression vanilla uses by default
+const COMPRESSION_TYPE: u8 = 2;
+
+/// Block entity type registry, as of protocol 769 (1.21.4)
+const BLOCK_ENTITY_TYPES: [&str; 45] = [
+    "furnace",
+    "chest",
+    "trapped_chest",
+    "ender_chest",
+    "jukebox",
+    "dispenser",
+    "dropper",
+    "sign",
+    "hanging_sign",
+    "mob_spawner",
+    "creaking_heart",
+    "piston",
+    "brewing_stand",
+    "enchanting_table",
+    "end_portal",
+    "beacon",
+    "skull",
+    "daylight_detector",
+    "hopper",
+    "comparator",
+    "banner",
+    "structure_block",
+    "end_gateway",
+    "command_block",
+    "shulker_box",
+    "bed",
+    "conduit",
+    "barrel",
+    "smoker",
+    "blast_furnace",
+    "lectern",
+    "bell",
+    "jigsaw",
+    "campfire",
+    "beehive",
+    "sculk_sensor",
+    "calibrated_sculk_sensor",
+    "sculk_catalyst",
+    "sculk_shrieker",
+    "chiseled_bookshelf",
+    "brushable_block",
+    "decorated_pot",
+    "crafter",
+    "trial_spawner",
+    "vault",
+];
+
+/// Region-local chunk index (x + z * 32) and compressed chunk NBT
+type RegionChunk = (usize, Vec<u8>);
+
+/// Where vanilla keeps a dimension, relative to the world folder
+pub(crate) fn dimension_folder(name: &str) -> PathBuf {
+    match name {
+        "minecraft:overworld" => PathBuf::new(),
+        "minecraft:the_nether" => PathBuf::from("DIM-1"),
+        "minecraft:the_end" => PathBuf::from("DIM1"),
+        _ => {
+            let (namespace, path) = name.split_once(':').unwrap_or(("minecraft", name));
+            PathBuf::from("dimensions").join(namespace).join(path)
+        }
+    }
+}
+
+/// Packs values into a palette and a long array, entries don't straddle longs.
+/// The data array is left empty when the palette has a single value.
+fn pack(values: &[i32], min_bits: u32) -> (Vec<i32>, Vec<i64>) {
+    let mut palette = vec![];
+    let mut indexes = HashMap::new();
+    let entries = values
+        .iter()
+        .map(|value| {
+            *indexes.entry(*value).or_insert_with(|| {
+                palette.push(*value);
+                palette.len() as u64 - 1
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if palette.len() == 1 {
+        return (palette, vec![]);
+    }
+
+    let bits = (usize::BITS - (palette.len() - 1).leading_zeros()).max(min_bits) as usize;
+    let entries_per_long = 64 / bits;
+    let data = entries
+        .chunks(entries_per_long)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .enumerate()
+                .fold(0u64, |long, (i, entry)| long | (entry << (i * bits))) as i64
+        })
+        .collect();
+    (palette, data)
+}
+
+fn light_nbt(array: &[u8]) -> Nbt {
+    Nbt::ByteArray(array.iter().map(|x| *x as i8).collect())
+}
+
+fn block_state_nbt(id: i32, blocks: &BlockRegistry, missing: &mut HashSet<i32>) -> Nbt {
+    let Some(state) = blocks.get(id) else {
+        missing.insert(id);
+        return Nbt::Compound(vec![(
+            "Name".to_string(),
+            Nbt::String("minecraft:air".to_string()),
+        )]);
+    };
+
+    let mut entries = vec![("Name".to_string(), Nbt::String(state.name.clone()))];
+    if !state.properties.is_empty() {
+        let properties = state
+            .properties
+            .iter()
+            .map(|(k, v)| (k.clone(), Nbt::String(v.clone())))
+            .collect();
+        entries.push(("Properties".to_string(), Nbt::Compound(properties)));
+    }
+    Nbt::Compound(entries)
+}
+
+fn block_entity_nbt(entity: &BlockEntity, chunk_x: i32, chunk_z: i32) -> Nbt {
+    let id = usize::try_from(entity.block_entity_type.value)
+        .ok()
+        .and_then(|i| BLOCK_ENTITY_TYPES.get(i))
+        .map_or("minecraft:unknown".to_string(), |x| {
+            format!("minecraft:{x}")
+        });
+
+    let mut entries = match &entity.data {
+        Nbt::Compound(entries) => entries.clone(),
+        _ => vec![],
+    };
+    entries.extend([
+        ("id".to_string(), Nbt::String(id)),
+        ("x".to_string(), Nbt::Int(chunk_x * 16 + entity.x() as i32)),
+        ("y".to_string(), Nbt::Int(entity.y as i32)),
+        ("z".to_string(), Nbt::Int(chunk_z * 16 + entity.z() as i32)),
+        ("keepPacked".to_string(), Nbt::Byte(0)),
+    ]);
+    Nbt::Compound(entries)
+}
+
+fn chunk_nbt(
+    chunk_x: i32,
+    chunk_z: i32,
+    min_section: i32,
+    chunk: &StoredChunk,
+    blocks: &BlockRegistry,
+    biome_name: &impl Fn(i32) -> String,
+    missing: &mut HashSet<i32>,
+) -> Nbt {
+    // Light sections run from one below the world to one above it
+    let mut sections = vec![];
+    for light_index in 0..chunk.sky_light.len().max(chunk.sections.len() + 2) {
+        let y = min_section - 1 + light_index as i32;
+        let mut entries = vec![("Y".to_string(), Nbt::Byte(y as i8))];
+
+        if let Some(section) = light_index
+            .checked_sub(1)
+            .and_then(|i| chunk.sections.get(i))
+        {
+            let (palette, data) = pack(&section.blocks, 4);
+            let palette = palette
+                .into_iter()
+                .map(|id| block_state_nbt(id, blocks, missing))
+                .collect();
+            let mut block_states = vec![("palette".to_string(), Nbt::List(palette))];
+            if !data.is_empty() {
+                block_states.push(("data".to_string(), Nbt::LongArray(data)));
+            }
+            entries.push(("block_states".to_string(), Nbt::Compound(block_states)));
+
+            let (palette, data) = pack(&section.biomes, 1);
+            let palette = palette
+                .into_iter()
+                .map(|id| Nbt::String(biome_name(id)))
+                .collect();
+            let mut biomes = vec![("palette".to_string(), Nbt::List(palette))];
+            if !data.is_empty() {
+                biomes.push(("data".to_string(), Nbt::LongArray(data)));
+            }
+            entries.push(("biomes".to_string(), Nbt::Compound(biomes)));
+        }
+
+        if let Some(Some(light)) = chunk.block_light.get(light_index) {
+            entries.push(("BlockLight".to_string(), light_nbt(light)));
+        }
+        if let Some(Some(light)) = chunk.sky_light.get(light_index) {
+            entries.push(("SkyLight".to_string(), light_nbt(light)));
+        }
+
+        if entries.len() > 1 {
+            sections.push(Nbt::Compound(entries));
+        }
+    }
+
+    let has_light = chunk
+        .sky_light
+        .iter()
+        .chain(&chunk.block_light)
+        .any(Option::is_some);
+    let block_entities = chunk
+        .block_entities
+        .iter()
+        .map(|x| block_entity_nbt(x, chunk_x, chunk_z))
+        .collect();
+
+    let mut entries = vec![
+        ("DataVersion".to_string(), Nbt::Int(DATA_VERSION)),
+        ("xPos".to_string(), Nbt::Int(chunk_x)),
+        ("yPos".to_string(), Nbt::Int(min_section)),
+        ("zPos".to_string(), Nbt::Int(chunk_z)),
+        (
+            "Status".to_string(),
+            Nbt::String("minecraft:full".to_string()),
+        ),
+        ("LastUpdate".to_string(), Nbt::Long(0)),
+        ("InhabitedTime".to_string(), Nbt::Long(0)),
+        ("isLightOn".to_string(), Nbt::Byte(has_light as i8)),
+        ("sections".to_string(), Nbt::List(sections)),
+        ("block_entities".to_string(), Nbt::List(block_entities)),
+        ("block_ticks".to_string(), Nbt::List(vec![])),
+        ("fluid_ticks".to_string(), Nbt::List(vec![])),
+    ];
+    if let Nbt::Compound(_) = chunk.heightmaps {
+        entries.push(("Heightmaps".to_string(), chunk.heightmaps.clone()));
+    }
+    Nbt::Compound(entries)
+}
+
+/// Lays out a region file: a table of sector offsets, a table of timestamps, then the chunks.
+fn region_bytes(chunks: &[RegionChunk], timestamp: u32) -> Vec<u8> {
+    let mut header = vec![0u8; SECTOR_SIZE * 2];
+    let mut body = vec![];
+    let mut sector = 2;
+
+    for (index, compressed) in chunks {
+        let mut entry = (compressed.len() as u32 + 1).to_be_bytes().to_vec();
+        entry.push(COMPRESSION_TYPE);
+        entry.extend(compressed);
+        entry.resize(entry.len().div_ceil(SECTOR_SIZE) * SECTOR_SIZE, 0);
+
+        // Bigger chunks go to separate .mcc files in vanilla, which we don't write
+        let sectors = entry.len() / SECTOR_SIZE;
+        if sectors > u8::MAX as usize {
+            warn!("World: chunk {index} of a region is too big ({sectors} sectors), skipping");
+            continue;
+        }
+
+        let location = ((sector as u32) << 8) | sectors as u32;
+        header[index * 4..index * 4 + 4].copy_from_slice(&location.to_be_bytes());
+        header[SECTOR_SIZE + index * 4..SECTOR_SIZE + index * 4 + 4]
+            .copy_from_slice(&timestamp.to_be_bytes());
+        body.extend(entry);
+        sector += sectors;
+    }
+
+    header.extend(body);
+    header
+}
+
+pub(crate) fn write_regions(
+    dir: &Path,
+    dimension: &Dimension,
+    blocks: &BlockRegistry,
+    biome_name: impl Fn(i32) -> String,
+) -> color_eyre::Result<()> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |x| x.as_secs() as u32);
+    let min_section = dimension.min_y.div_euclid(16);
+    let mut missing = HashSet::new();
+
+    let mut regions: BTreeMap<(i32, i32), Vec<RegionChunk>> = BTreeMap::new();
+    for (&(x, z), chunk) in &dimension.chunks {
+        let nbt = chunk_nbt(x, z, min_section, chunk, blocks, &biome_name, &mut missing);
+        let mut raw = vec![];
+        nbt.write_named("", &mut raw);
+        let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+        encoder.write_all(&raw)?;
+
+        let index = (x.rem_euclid(32) + z.rem_euclid(32) * 32) as usize;
+        regions
+            .entry((x.div_euclid(32), z.div_euclid(32)))
+            .or_default()
+            .push((index, encoder.finish()?));
+    }
+
+    for ((region_x, region_z), chunks) in regions {
+        let path = dir.join(format!("r.{region_x}.{region_z}.mca"));
+        std::fs::write(&path, region_bytes(&chunks, timestamp))
+            .wrap_err(format!("Can't write {}", path.display()))?;
+    }
+
+    if !missing.is_empty() {
+        warn!(
+            "World: {} block states are missing from the block report and were written as air",
+            missing.len()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn packing() {
+        assert_eq!(pack(&[7; 64], 1), (vec![7], vec![]));
+
+        let mut values = vec![0; 4096];
+        values[1] = 9;
+        values[16] = 3;
+        let (palette, data) = pack(&values, 4);
+        assert_eq!(palette, [0, 9, 3]);
+        assert_eq!(data.len(), 256);
+        assert_eq!(data[0], 0x10);
+        assert_eq!(data[1], 0x2);
+
+        // 5 bits per entry leaves 4 bits of padding in each long
+        let values = (0..17).collect::<Vec<_>>();
+        let (_, data) = pack(&values, 4);
+        assert_eq!(data.len(), 2);
+        assert_eq!(data[0] as u64 >> 60, 0);
+        assert_eq!(data[1] & 0x1F, 12);
+    }
+
+    #[test]
+    fn region_layout() {
+        let region = region_bytes(&[(33, vec![0xAB; 10]), (0, vec![0xCD; 5000])], 1234);
+        assert_eq!(region.len(), SECTOR_SIZE * 5);
+        // Chunk (1, 1) at sector 2, one sector long
+        assert_eq!(region[132..136], [0x00, 0x00, 0x02, 0x01]);
+        assert_eq!(
+            region[SECTOR_SIZE + 132..SECTOR_SIZE + 136],
+            1234u32.to_be_bytes()
+        );
+        // Chunk (0, 0) after it, two sectors long
+        assert_eq!(region[0..4], [0x00, 0x00, 0x03, 0x02]);
+        assert_eq!(
+            region[SECTOR_SIZE * 2..SECTOR_SIZE * 2 + 5],
+            [0x00, 0x00, 0x00, 0x0B, 0x02]
+        );
+    }
+
+    #[test]
+    fn folders() {
+        assert_eq!(dimension_folder("minecraft:overworld"), PathBuf::new());
+        assert_eq!(
+            dimension_folder("minecraft:the_nether"),
+            PathBuf::from("DIM-1")
+        );
+        assert_eq!(
+            dimension_folder("custom:mining"),
+            PathBuf::from("dimensions/custom/mining")
+        );
+    }
+}
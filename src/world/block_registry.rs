@@ -0,0 +1,101 @@
+use color_eyre::eyre::WrapErr;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// Block state network IDs to block names and properties.
+///
+/// The protocol only sends state IDs, so this is loaded from the `blocks.json` report made by
+/// the vanilla data generator (`java -DbundlerMainClass=net.minecraft.data.Main -jar server.jar --reports`).
+#[derive(Debug, Clone, Default)]
+pub struct BlockRegistry {
+    states: Vec<Option<BlockState>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockState {
+    pub name: String,
+    pub properties: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct ReportBlock {
+    states: Vec<ReportState>,
+}
+
+#[derive(Deserialize)]
+struct ReportState {
+    id: usize,
+    #[serde(default)]
+    properties: BTreeMap<String, String>,
+}
+
+impl BlockRegistry {
+    pub fn from_report(json: &str) -> color_eyre::Result<Self> {
+        let report: HashMap<String, ReportBlock> = serde_json::from_str(json)?;
+        let mut states = vec![];
+        for (name, block) in report {
+            for state in block.states {
+                if states.len() <= state.id {
+                    states.resize(state.id + 1, None);
+                }
+                states[state.id] = Some(BlockState {
+                    name: name.clone(),
+                    properties: state.properties,
+                });
+            }
+        }
+        Ok(Self { states })
+    }
+
+    pub fn load(path: &Path) -> color_eyre::Result<Self> {
+        let json = std::fs::read_to_string(path)
+            .wrap_err(format!("Can't read block report {}", path.display()))?;
+        Self::from_report(&json).wrap_err(format!("Can't parse block report {}", path.display()))
+    }
+
+    pub fn get(&self, id: i32) -> Option<&BlockState> {
+        usize::try_from(id)
+            .ok()
+            .and_then(|i| self.states.get(i))
+            .and_then(Option::as_ref)
+    }
+
+    pub fn len(&self) -> usize {
+        self.states.iter().flatten().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn report() {
+        let registry = BlockRegistry::from_report(
+            r#"{
+                "minecraft:air": {"states": [{"default": true, "id": 0}]},
+                "minecraft:grass_block": {
+                    "properties": {"snowy": ["true", "false"]},
+                    "states": [
+                        {"id": 8, "properties": {"snowy": "true"}},
+                        {"default": true, "id": 9, "properties": {"snowy": "false"}}
+                    ]
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(registry.len(), 3);
+        assert_eq!(registry.get(0).unwrap().name, "minecraft:air");
+        let grass = registry.get(9).unwrap();
+        assert_eq!(grass.name, "minecraft:grass_block");
+        assert_eq!(grass.properties["snowy"], "false");
+        assert_eq!(registry.get(1), None);
+        assert_eq!(registry.get(-1), None);
+    }
+}
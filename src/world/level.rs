@@ -0,0 +1,110 @@
+use crate::protocol::types::Nbt;
+use crate::protocol::MINECRAFT_VERSION;
+use crate::world::anvil::DATA_VERSION;
+use color_eyre::eyre::WrapErr;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::io::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn compound(entries: Vec<(&str, Nbt)>) -> Nbt {
+    Nbt::Compound(
+        entries
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect(),
+    )
+}
+
+fn string(value: &str) -> Nbt {
+    Nbt::String(value.to_string())
+}
+
+/// Chunks that weren't captured generate as empty void instead of new terrain
+fn void_dimension(dimension_type: &str) -> Nbt {
+    compound(vec![
+        ("type", string(dimension_type)),
+        (
+            "generator",
+            compound(vec![
+                ("type", string("minecraft:flat")),
+                (
+                    "settings",
+                    compound(vec![
+                        ("biome", string("minecraft:the_void")),
+                        ("features", Nbt::Byte(0)),
+                        ("lakes", Nbt::Byte(0)),
+                        ("layers", Nbt::List(vec![])),
+                        ("structure_overrides", Nbt::List(vec![])),
+                    ]),
+                ),
+            ]),
+        ),
+    ])
+}
+
+fn level_dat(level_name: &str, hardcore: bool, game_mode: u8, last_played: i64) -> Nbt {
+    let data = compound(vec![
+        ("DataVersion", Nbt::Int(DATA_VERSION)),
+        // NBT format version of level.dat, unchanged since Anvil was introduced
+        ("version", Nbt::Int(19133)),
+        (
+            "Version",
+            compound(vec![
+                ("Id", Nbt::Int(DATA_VERSION)),
+                ("Name", string(MINECRAFT_VERSION)),
+                ("Series", string("main")),
+                ("Snapshot", Nbt::Byte(0)),
+            ]),
+        ),
+        ("LevelName", string(level_name)),
+        ("GameType", Nbt::Int(game_mode as i32)),
+        ("hardcore", Nbt::Byte(hardcore as i8)),
+        ("allowCommands", Nbt::Byte(1)),
+        ("initialized", Nbt::Byte(1)),
+        ("LastPlayed", Nbt::Long(last_played)),
+        ("Time", Nbt::Long(0)),
+        ("DayTime", Nbt::Long(6000)),
+        ("SpawnX", Nbt::Int(0)),
+        ("SpawnY", Nbt::Int(64)),
+        ("SpawnZ", Nbt::Int(0)),
+        (
+            "WorldGenSettings",
+            compound(vec![
+                ("seed", Nbt::Long(0)),
+                ("generate_features", Nbt::Byte(0)),
+                ("bonus_chest", Nbt::Byte(0)),
+                (
+                    "dimensions",
+                    compound(vec![
+                        ("minecraft:overworld", void_dimension("minecraft:overworld")),
+                        (
+                            "minecraft:the_nether",
+                            void_dimension("minecraft:the_nether"),
+                        ),
+                        ("minecraft:the_end", void_dimension("minecraft:the_end")),
+                    ]),
+                ),
+            ]),
+        ),
+    ]);
+    compound(vec![("Data", data)])
+}
+
+pub(crate) fn write_level_dat(
+    path: &Path,
+    level_name: &str,
+    hardcore: bool,
+    game_mode: u8,
+) -> color_eyre::Result<()> {
+    let last_played = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |x| x.as_millis() as i64);
+
+    let mut raw = vec![];
+    level_dat(level_name, hardcore, game_mode, last_played).write_named("", &mut raw);
+    let mut encoder = GzEncoder::new(vec![], Compression::default());
+    encoder.write_all(&raw)?;
+    std::fs::write(path, encoder.finish()?).wrap_err(format!("Can't write {}", path.display()))
+}
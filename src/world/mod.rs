@@ -0,0 +1,391 @@
+mod anvil;
+mod block_registry;
+mod level;
+
+use crate::protocol::packets::*;
+use crate::protocol::registry::{ConfigurationClientbound, Packet, PlayClientbound};
+use crate::protocol::types::{BitSet, BlockEntity, ChunkData, Identifier, LightData, Nbt, Position};
+use color_eyre::eyre::WrapErr;
+use log::{debug, info};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+pub use block_registry::BlockRegistry;
+
+/// Where to save the world rebuilt from each connection
+#[derive(Debug)]
+pub struct WorldExport {
+    pub dir: PathBuf,
+    pub blocks: BlockRegistry,
+}
+
+impl WorldExport {
+    /// Saves into a folder called `name`, also the level name, returning that folder
+    pub fn save(&self, world: &World, name: &str) -> color_eyre::Result<PathBuf> {
+        let dir = self.dir.join(name);
+        world.save(&dir, name, &self.blocks)?;
+        Ok(dir)
+    }
+}
+
+/// Everything one client was sent about the world, rebuilt from its packets
+#[derive(Debug, Clone, Default)]
+pub struct World {
+    /// Biome network IDs index into this, as sent in Registry Data
+    biomes: Vec<Identifier>,
+    /// Dimension type network ID to its minimum Y, when the server sent the full entry
+    dimension_min_y: HashMap<i32, i32>,
+    dimension: Option<String>,
+    dimensions: HashMap<String, Dimension>,
+    hardcore: bool,
+    game_mode: u8,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Dimension {
+    pub(crate) min_y: i32,
+    pub(crate) chunks: HashMap<(i32, i32), StoredChunk>,
+}
+
+/// Chunk column with the palettes expanded so single blocks can be changed
+#[derive(Debug, Clone)]
+pub(crate) struct StoredChunk {
+    pub(crate) heightmaps: Nbt,
+    pub(crate) sections: Vec<StoredSection>,
+    pub(crate) block_entities: Vec<BlockEntity>,
+    /// Indexed by light section, which starts one section below the world
+    pub(crate) sky_light: Vec<Option<Vec<u8>>>,
+    pub(crate) block_light: Vec<Option<Vec<u8>>>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct StoredSection {
+    /// 4096 block states in YZX order
+    pub(crate) blocks: Vec<i32>,
+    /// 64 biomes in YZX order
+    pub(crate) biomes: Vec<i32>,
+}
+
+impl StoredChunk {
+    fn new(data: &ChunkData, light: &LightData) -> Self {
+        let sections = data
+            .sections
+            .iter()
+            .map(|section| StoredSection {
+                blocks: section.block_states.values().map(|x| x.unwrap_or(0)).collect(),
+                biomes: section.biomes.values().map(|x| x.unwrap_or(0)).collect(),
+            })
+            .collect::<Vec<_>>();
+        let light_sections = sections.len() + 2;
+
+        let mut chunk = Self {
+            heightmaps: data.heightmaps.clone(),
+            sections,
+            block_entities: data.block_entities.clone(),
+            sky_light: vec![None; light_sections],
+            block_light: vec![None; light_sections],
+        };
+        chunk.update_light(light);
+        chunk
+    }
+
+    fn update_light(&mut self, light: &LightData) {
+        Self::merge_light(&mut self.sky_light, light.sky_light(), &light.empty_sky_light_mask);
+        Self::merge_light(&mut self.block_light, light.block_light(), &light.empty_block_light_mask);
+    }
+
+    fn merge_light<'a>(
+        stored: &mut [Option<Vec<u8>>],
+        arrays: impl Iterator<Item = (usize, &'a Vec<u8>)>,
+        empty_mask: &BitSet,
+    ) {
+        for (index, array) in arrays {
+            if let Some(slot) = stored.get_mut(index) {
+                *slot = Some(array.clone());
+            }
+        }
+        for index in empty_mask.ones() {
+            if let Some(slot) = stored.get_mut(index) {
+                *slot = Some(vec![0; LightData::ARRAY_LENGTH]);
+            }
+        }
+    }
+
+    /// `y` is counted from the bottom of the world
+    fn set_block(&mut self, x: usize, y: usize, z: usize, state: i32) {
+        if let Some(section) = self.sections.get_mut(y / 16) {
+            section.blocks[((y % 16) * 16 + z) * 16 + x] = state;
+        }
+    }
+}
+
+impl Dimension {
+    fn new(min_y: i32) -> Self {
+        Self {
+            min_y,
+            chunks: HashMap::new(),
+        }
+    }
+
+    fn set_block(&mut self, position: Position, state: i32) {
+        let Some(chunk) = self
+            .chunks
+            .get_mut(&(position.x.div_euclid(16), position.z.div_euclid(16)))
+        else {
+            return;
+        };
+        let Ok(y) = usize::try_from(position.y - self.min_y) else {
+            return;
+        };
+        let x = position.x.rem_euclid(16) as usize;
+        let z = position.z.rem_euclid(16) as usize;
+        chunk.set_block(x, y, z, state);
+
+        // Air never has a block entity, anything else keeps it until the server says otherwise
+        if state == 0 {
+            chunk.block_entities.retain(|entity| {
+                (entity.x() as usize, entity.y as i32, entity.z() as usize) != (x, position.y, z)
+            });
+        }
+    }
+}
+
+impl World {
+    pub fn new() -> Self {
+        Self {
+            biomes: vec![],
+            dimension_min_y: HashMap::new(),
+            dimension: None,
+            dimensions: HashMap::new(),
+            hardcore: false,
+            game_mode: 0,
+        }
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.dimensions.values().map(|x| x.chunks.len()).sum()
+    }
+
+    fn change_dimension(&mut self, spawn_info: &SpawnInfo) {
+        let name = spawn_info.dimension_name.to_string();
+        let min_y = self
+            .dimension_min_y
+            .get(&spawn_info.dimension_type.value)
+            .copied()
+            .unwrap_or(if name == "minecraft:overworld" { -64 } else { 0 });
+        debug!("World: entering {name} (min Y {min_y})");
+        self.dimensions
+            .entry(name.clone())
+            .or_insert_with(|| Dimension::new(min_y));
+        self.dimension = Some(name);
+        self.game_mode = spawn_info.game_mode;
+    }
+
+    fn current_dimension(&mut self) -> Option<&mut Dimension> {
+        self.dimensions.get_mut(self.dimension.as_ref()?)
+    }
+
+    /// Updates the world with a decoded packet, anything unrelated is ignored
+    pub fn apply(&mut self, packet: &Packet) {
+        match packet {
+            Packet::ConfigurationClientbound(ConfigurationClientbound::RegistryData(packet)) => {
+                match packet.registry_id.to_string().as_str() {
+                    "minecraft:worldgen/biome" => {
+                        self.biomes = packet.entries.iter().map(|x| x.entry_id.clone()).collect();
+                    }
+                    "minecraft:dimension_type" => {
+                        for (id, entry) in packet.entries.iter().enumerate() {
+                            if let Some(min_y) = entry.data.as_ref().and_then(|x| x.get("min_y")) {
+                                self.dimension_min_y
+                                    .insert(id as i32, min_y.as_i64().unwrap_or(0) as i32);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Packet::PlayClientbound(packet) => self.apply_play(packet),
+            _ => {}
+        }
+    }
+
+    fn apply_play(&mut self, packet: &PlayClientbound) {
+        match packet {
+            PlayClientbound::LoginPlay(packet) => {
+                self.hardcore = packet.is_hardcore;
+                self.change_dimension(&packet.spawn_info);
+            }
+            PlayClientbound::Respawn(packet) => self.change_dimension(&packet.spawn_info),
+            PlayClientbound::ChunkDataAndUpdateLight(packet) => {
+                if let Some(dimension) = self.current_dimension() {
+                    let chunk = StoredChunk::new(&packet.data, &packet.light);
+                    dimension.chunks.insert((packet.chunk_x, packet.chunk_z), chunk);
+                }
+            }
+            PlayClientbound::UpdateLight(packet) => {
+                if let Some(chunk) = self
+                    .current_dimension()
+                    .and_then(|x| x.chunks.get_mut(&(packet.chunk_x.value, packet.chunk_z.value)))
+                {
+                    chunk.update_light(&packet.light);
+                }
+            }
+            PlayClientbound::BlockUpdate(packet) => {
+                if let Some(dimension) = self.current_dimension() {
+                    dimension.set_block(packet.location, packet.block_id.value);
+                }
+            }
+            PlayClientbound::UpdateSectionBlocks(packet) => {
+                if let Some(dimension) = self.current_dimension() {
+                    for (position, state) in packet.changes() {
+                        dimension.set_block(position, state);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn biome_name(&self, id: i32) -> String {
+        usize::try_from(id)
+            .ok()
+            .and_then(|i| self.biomes.get(i))
+            .map_or("minecraft:plains".to_string(), |x| x.to_string())
+    }
+
+    /// Writes region files for every dimension plus a level.dat into `dir`
+    pub fn save(&self, dir: &Path, level_name: &str, blocks: &BlockRegistry) -> color_eyre::Result<()> {
+        for (name, dimension) in &self.dimensions {
+            if dimension.chunks.is_empty() {
+                continue;
+            }
+            let region_dir = dir.join(anvil::dimension_folder(name)).join("region");
+            std::fs::create_dir_all(&region_dir)
+                .wrap_err(format!("Can't create {}", region_dir.display()))?;
+            anvil::write_regions(&region_dir, dimension, blocks, |id| self.biome_name(id))
+                .wrap_err(format!("Can't write regions of {name}"))?;
+            info!("World: saved {} chunks of {name}", dimension.chunks.len());
+        }
+
+        level::write_level_dat(&dir.join("level.dat"), level_name, self.hardcore, self.game_mode)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::protocol::types::*;
+
+    fn single_value<const ENTRIES: usize, const MAX_INDIRECT_BITS: u8>(
+        value: i32,
+    ) -> PalettedContainer<ENTRIES, MAX_INDIRECT_BITS> {
+        PalettedContainer {
+            bits_per_entry: 0,
+            palette: Palette::SingleValue(VarInt { value }),
+            data: vec![],
+        }
+    }
+
+    /// One section of stone with full sky light
+    fn stone_chunk() -> (ChunkData, LightData) {
+        let data = ChunkData {
+            heightmaps: Nbt::Compound(vec![]),
+            sections: vec![ChunkSection {
+                block_count: 4096,
+                block_states: single_value(1),
+                biomes: single_value(0),
+            }],
+            block_entities: vec![],
+        };
+        let light = LightData {
+            sky_light_mask: BitSet { data: vec![0b10] },
+            block_light_mask: BitSet::default(),
+            empty_sky_light_mask: BitSet::default(),
+            empty_block_light_mask: BitSet::default(),
+            sky_light_arrays: vec![vec![0xFF; LightData::ARRAY_LENGTH]],
+            block_light_arrays: vec![],
+        };
+        (data, light)
+    }
+
+    fn spawn_info(dimension: &str) -> SpawnInfo {
+        SpawnInfo {
+            dimension_type: VarInt { value: 0 },
+            dimension_name: Identifier {
+                namespace: "minecraft".to_string(),
+                value: dimension.to_string(),
+            },
+            hashed_seed: 0,
+            game_mode: 1,
+            previous_game_mode: -1,
+            is_debug: false,
+            is_flat: false,
+            death_location: None,
+            portal_cooldown: VarInt { value: 0 },
+            sea_level: VarInt { value: 63 },
+        }
+    }
+
+    #[test]
+    fn block_updates() {
+        let mut world = World::new();
+        world.apply(&Packet::from(Respawn {
+            spawn_info: spawn_info("overworld"),
+            data_kept: 0,
+        }));
+        let (data, light) = stone_chunk();
+        world.apply(&Packet::from(ChunkDataAndUpdateLight {
+            chunk_x: -1,
+            chunk_z: 2,
+            data,
+            light,
+        }));
+        world.apply(&Packet::from(BlockUpdate {
+            location: Position { x: -16, y: -64, z: 32 },
+            block_id: VarInt { value: 5 },
+        }));
+        let mut data = ((-1i64 << 42) | (2 << 20) | 0xFFFFC).to_be_bytes().to_vec();
+        data.extend([0x01, 0x80, 0x82, 0x01]); // state 4 at local (1, 0, 0)
+        world.apply(&Packet::from(UpdateSectionBlocks::from_bytes(&mut &data[..]).unwrap()));
+
+        let dimension = &world.dimensions["minecraft:overworld"];
+        assert_eq!(dimension.min_y, -64);
+        let chunk = &dimension.chunks[&(-1, 2)];
+        assert_eq!(chunk.sections[0].blocks[0], 5);
+        assert_eq!(chunk.sections[0].blocks[1], 4);
+        assert_eq!(chunk.sections[0].blocks[2], 1);
+        assert!(chunk.sky_light[0].is_none());
+        assert_eq!(chunk.sky_light[1].as_ref().map(Vec::len), Some(2048));
+        assert_eq!(world.chunk_count(), 1);
+    }
+
+    #[test]
+    fn save() {
+        let mut world = World::new();
+        world.apply(&Packet::from(Respawn {
+            spawn_info: spawn_info("the_nether"),
+            data_kept: 0,
+        }));
+        let (data, light) = stone_chunk();
+        world.apply(&Packet::from(ChunkDataAndUpdateLight {
+            chunk_x: 40,
+            chunk_z: -3,
+            data,
+            light,
+        }));
+
+        let dir = std::env::temp_dir().join(format!("world_save_{}", std::process::id()));
+        let blocks = BlockRegistry::from_report(
+            r#"{"minecraft:stone": {"states": [{"id": 1}]}}"#,
+        )
+        .unwrap();
+        world.save(&dir, "test", &blocks).unwrap();
+
+        assert!(dir.join("level.dat").is_file());
+        let region = std::fs::read(dir.join("DIM-1/region/r.1.-1.mca")).unwrap();
+        // Chunk (8, 29) within the region
+        let index = (8 + 29 * 32) * 4;
+        assert_eq!(region[index..index + 4], [0x00, 0x00, 0x02, 0x01]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}